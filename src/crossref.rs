@@ -0,0 +1,535 @@
+//! Numbering of and references to figures, tables, equations, listings and
+//! sections, in the style of pandoc-crossref.
+//!
+//! Elements are labelled through their identifier prefix:
+//!
+//! * `fig:` on a `Block::Figure`
+//! * `tbl:` on a `Block::Table`
+//! * `eq:` on a `Span` around display math, or `$$ .. $$ {#eq:label}`
+//! * `lst:` on a `CodeBlock`, with an optional `caption` attribute
+//! * `sec:` on a `Header`
+//!
+//! Citations like `[@fig:plot; @tbl:data]` are turned into references
+//! like "Fig. 3, Table 1". `[-@fig:plot]` leaves out the prefix and
+//! `@Fig:plot` capitalizes it.
+
+use super::meta::tokenize;
+use super::*;
+use serde_derive::Deserialize;
+
+/// The word put in front of references, in singular and plural
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(from = "PrefixDef")]
+pub struct Prefix {
+    pub singular: String,
+    pub plural: String,
+}
+
+impl Prefix {
+    pub fn new(singular: &str, plural: &str) -> Self {
+        Prefix {
+            singular: singular.to_string(),
+            plural: plural.to_string(),
+        }
+    }
+}
+
+/// A single string or a list of singular and plural in the metadata
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PrefixDef {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<PrefixDef> for Prefix {
+    fn from(def: PrefixDef) -> Self {
+        match def {
+            PrefixDef::One(s) => Prefix::new(&s, &s),
+            PrefixDef::Many(list) => {
+                let singular = list.first().cloned().unwrap_or_default();
+                let plural = list.get(1).cloned().unwrap_or_else(|| singular.clone());
+                Prefix { singular, plural }
+            }
+        }
+    }
+}
+
+/// Settings, read from the metadata fields named like pandoc-crossref's
+/// (`figPrefix`, `figureTitle`, `chapters`, ...) either at the top level
+/// or inside a `crossref` map
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub fig_prefix: Prefix,
+    pub tbl_prefix: Prefix,
+    pub eqn_prefix: Prefix,
+    pub lst_prefix: Prefix,
+    pub sec_prefix: Prefix,
+    /// Caption prefixes
+    pub figure_title: String,
+    pub table_title: String,
+    pub listing_title: String,
+    /// Separates the number from the caption text
+    pub title_delim: String,
+    /// Separates the ends of a range of numbers like `2–4`
+    pub range_delim: String,
+    /// Separates references
+    pub ref_delim: String,
+    /// Prefix figure, table, equation and listing numbers with the number
+    /// of the enclosing level 1 section and restart counting in each
+    pub chapters: bool,
+    /// Turn references into links to the referenced element
+    pub link_references: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            fig_prefix: Prefix::new("Fig.", "Figs."),
+            tbl_prefix: Prefix::new("Table", "Tables"),
+            eqn_prefix: Prefix::new("Eq.", "Eqs."),
+            lst_prefix: Prefix::new("Listing", "Listings"),
+            sec_prefix: Prefix::new("Sec.", "Secs."),
+            figure_title: "Figure".to_string(),
+            table_title: "Table".to_string(),
+            listing_title: "Listing".to_string(),
+            title_delim: ":".to_string(),
+            range_delim: "\u{2013}".to_string(),
+            ref_delim: ", ".to_string(),
+            chapters: false,
+            link_references: true,
+        }
+    }
+}
+
+impl Settings {
+    pub fn from_meta(meta: &Map<String, MetaValue>) -> Result<Self, MetaError> {
+        let mut meta = meta.clone();
+        if let Some(nested) = meta.remove("crossref") {
+            if let Some(nested) = nested.into_map() {
+                merge_meta(&mut meta, nested, ListMerge::Replace);
+            }
+        }
+        from_meta(&meta)
+    }
+
+    fn prefix(&self, kind: Kind) -> &Prefix {
+        match kind {
+            Kind::Figure => &self.fig_prefix,
+            Kind::Table => &self.tbl_prefix,
+            Kind::Equation => &self.eqn_prefix,
+            Kind::Listing => &self.lst_prefix,
+            Kind::Section => &self.sec_prefix,
+        }
+    }
+}
+
+/// The kind of a labelled element
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Kind {
+    Figure,
+    Table,
+    Equation,
+    Listing,
+    Section,
+}
+
+impl Kind {
+    /// The kind of element an identifier like `fig:plot` refers to
+    pub fn of(id: &str) -> Option<Kind> {
+        let (prefix, _) = id.split_once(':')?;
+        match &prefix.to_lowercase()[..] {
+            "fig" => Some(Kind::Figure),
+            "tbl" => Some(Kind::Table),
+            "eq" => Some(Kind::Equation),
+            "lst" => Some(Kind::Listing),
+            "sec" => Some(Kind::Section),
+            _ => None,
+        }
+    }
+}
+
+/// A numbered element
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub kind: Kind,
+    pub number: String,
+}
+
+/// Assigns numbers to labelled elements and resolves references to them
+#[derive(Debug, Clone, Default)]
+pub struct Crossref {
+    pub settings: Settings,
+    labels: Map<String, Label>,
+}
+
+/// Numbers a whole document with the settings from its metadata
+pub fn crossref(pandoc: &mut Pandoc) -> Result<(), MetaError> {
+    Crossref::from_meta(&pandoc.meta)?.process(pandoc);
+    Ok(())
+}
+
+impl Crossref {
+    pub fn new(settings: Settings) -> Self {
+        Crossref {
+            settings,
+            labels: Map::new(),
+        }
+    }
+
+    pub fn from_meta(meta: &Map<String, MetaValue>) -> Result<Self, MetaError> {
+        Ok(Crossref::new(Settings::from_meta(meta)?))
+    }
+
+    /// All labels found so far, by identifier
+    pub fn labels(&self) -> &Map<String, Label> {
+        &self.labels
+    }
+
+    /// Numbers the labelled elements, prefixes their captions and replaces
+    /// citations of labels by references
+    pub fn process(&mut self, pandoc: &mut Pandoc) {
+        let mut numberer = Numberer {
+            settings: &self.settings,
+            labels: &mut self.labels,
+            chapter: 0,
+            sections: Vec::new(),
+            counters: Map::new(),
+        };
+        numberer.visit_vec_block(&mut pandoc.blocks);
+        Resolver(self).visit_vec_block(&mut pandoc.blocks);
+    }
+
+    /// The inlines replacing a citation of labels
+    fn reference(&self, citations: &[Citation]) -> Vec<Inline> {
+        let mut out = Vec::new();
+        let mut rest = citations;
+        while let Some(first) = rest.first() {
+            let kind = Kind::of(&first.citationId);
+            let len = rest
+                .iter()
+                .position(|c| Kind::of(&c.citationId) != kind)
+                .unwrap_or(rest.len());
+            let (group, tail) = rest.split_at(len);
+            rest = tail;
+            if !out.is_empty() {
+                self.delimiter(&mut out);
+            }
+            out.extend(self.group(group));
+        }
+        out
+    }
+
+    /// References to labels of a single kind
+    fn group(&self, group: &[Citation]) -> Vec<Inline> {
+        let first = &group[0];
+        let mut out = first.citationPrefix.clone();
+        if !out.is_empty() {
+            out.push(Inline::Space);
+        }
+        if first.citationMode != CitationMode::SuppressAuthor {
+            let prefix = self.settings.prefix(Kind::of(&first.citationId).unwrap());
+            let mut word = if group.len() > 1 {
+                prefix.plural.clone()
+            } else {
+                prefix.singular.clone()
+            };
+            if first.citationId.starts_with(|c: char| c.is_uppercase()) {
+                word = capitalize(&word);
+            }
+            if !word.is_empty() {
+                out.push(Inline::Str(format!("{}\u{a0}", word)));
+            }
+        }
+        let mut i = 0;
+        while i < group.len() {
+            let start = i;
+            // extend runs of consecutive numbers
+            while i + 1 < group.len() && self.follows(&group[i], &group[i + 1]) {
+                i += 1;
+            }
+            if start > 0 {
+                self.delimiter(&mut out);
+            }
+            if i - start >= 2 {
+                out.push(self.number(&group[start]));
+                out.push(Inline::Str(self.settings.range_delim.clone()));
+                out.push(self.number(&group[i]));
+            } else {
+                for (n, citation) in group[start..=i].iter().enumerate() {
+                    if n > 0 {
+                        self.delimiter(&mut out);
+                    }
+                    out.push(self.number(citation));
+                }
+            }
+            i += 1;
+        }
+        for citation in group {
+            out.extend(citation.citationSuffix.iter().cloned());
+        }
+        merge_strs(out)
+    }
+
+    fn delimiter(&self, out: &mut Vec<Inline>) {
+        out.extend(tokenize(&self.settings.ref_delim));
+        if self.settings.ref_delim.ends_with(' ') {
+            out.push(Inline::Space);
+        }
+    }
+
+    /// The label cited by `id`, which may have its first letter capitalized
+    fn label(&self, id: &str) -> Option<(&String, &Label)> {
+        let mut chars = id.chars();
+        let first = chars.next()?;
+        self.labels.get_key_value(id).or_else(|| {
+            self.labels
+                .get_key_value(&format!("{}{}", first.to_lowercase(), chars.as_str()))
+        })
+    }
+
+    /// Whether `b` is numbered directly after `a`
+    fn follows(&self, a: &Citation, b: &Citation) -> bool {
+        let (a, b) = match (self.label(&a.citationId), self.label(&b.citationId)) {
+            (Some((_, a)), Some((_, b))) => (&a.number, &b.number),
+            _ => return false,
+        };
+        let split = |n: &str| match n.rsplit_once('.') {
+            Some((head, last)) => (head.to_string(), last.parse::<Int>().ok()),
+            None => (String::new(), n.parse::<Int>().ok()),
+        };
+        match (split(a), split(b)) {
+            ((head_a, Some(a)), (head_b, Some(b))) => head_a == head_b && a + 1 == b,
+            _ => false,
+        }
+    }
+
+    fn number(&self, citation: &Citation) -> Inline {
+        let id = &citation.citationId;
+        match self.label(id) {
+            Some((id, label)) if self.settings.link_references => {
+                let attr = (String::new(), Vec::new(), Vec::new());
+                let target = (format!("#{}", id), String::new());
+                Inline::Link(attr, vec![Inline::Str(label.number.clone())], target)
+            }
+            Some((_, label)) => Inline::Str(label.number.clone()),
+            None => Inline::Strong(vec![Inline::Str(format!("\u{bf}{}?", id))]),
+        }
+    }
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Joins adjacent `Str`s
+fn merge_strs(inlines: Vec<Inline>) -> Vec<Inline> {
+    let mut out: Vec<Inline> = Vec::with_capacity(inlines.len());
+    for inline in inlines {
+        if let (Some(Inline::Str(ref mut last)), Inline::Str(ref s)) = (out.last_mut(), &inline) {
+            last.push_str(s);
+            continue;
+        }
+        out.push(inline);
+    }
+    out
+}
+
+struct Numberer<'a> {
+    settings: &'a Settings,
+    labels: &'a mut Map<String, Label>,
+    chapter: Int,
+    sections: Vec<Int>,
+    counters: Map<Kind, Int>,
+}
+
+impl Numberer<'_> {
+    fn next(&mut self, kind: Kind, id: &str) -> String {
+        let counter = self.counters.entry(kind).or_insert(0);
+        *counter += 1;
+        let number = if self.settings.chapters && self.chapter > 0 {
+            format!("{}.{}", self.chapter, counter)
+        } else {
+            counter.to_string()
+        };
+        self.labels.insert(
+            id.to_string(),
+            Label {
+                kind,
+                number: number.clone(),
+            },
+        );
+        number
+    }
+
+    /// Prefixes a caption with e.g. `Figure 3:`
+    fn caption(&self, caption: &mut Caption, title: &str, number: &str) {
+        let text = if caption.1.is_empty() {
+            format!("{} {}", title, number)
+        } else {
+            format!("{} {}{}", title, number, self.settings.title_delim)
+        };
+        let mut prefix = tokenize(&text);
+        match caption.1.first_mut() {
+            Some(Block::Plain(ref mut inlines)) | Some(Block::Para(ref mut inlines)) => {
+                prefix.push(Inline::Space);
+                inlines.splice(0..0, prefix);
+            }
+            _ => caption.1.insert(0, Block::Plain(prefix)),
+        }
+    }
+
+    fn header(&mut self, level: Int, attr: &Attr) {
+        let (ref id, ref classes, ref kvs) = *attr;
+        let unnumbered = classes.iter().any(|c| c == "unnumbered" || c == "-");
+        if level == 1 && !unnumbered {
+            self.chapter += 1;
+            if self.settings.chapters {
+                self.counters.clear();
+            }
+        }
+        if unnumbered || level < 1 {
+            return;
+        }
+        let depth = level as usize;
+        self.sections.resize(depth, 0);
+        self.sections[depth - 1] += 1;
+        if Kind::of(id) != Some(Kind::Section) {
+            return;
+        }
+        let number = match kvs.iter().find(|(k, _)| k == "number") {
+            Some((_, number)) => number.clone(),
+            None => self
+                .sections
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join("."),
+        };
+        let label = Label {
+            kind: Kind::Section,
+            number,
+        };
+        self.labels.insert(id.clone(), label);
+    }
+
+    fn equation(&mut self, id: &str, tex: &mut String) {
+        let number = self.next(Kind::Equation, id);
+        tex.push_str(&format!("\\qquad({})", number));
+    }
+}
+
+/// The label of `$$ .. $$ {#eq:label}`, which pandoc parses as a `Str`
+fn equation_label(inline: &Inline) -> Option<&str> {
+    match *inline {
+        Inline::Str(ref s) => s
+            .strip_prefix("{#")?
+            .strip_suffix('}')
+            .filter(|id| Kind::of(id) == Some(Kind::Equation)),
+        _ => None,
+    }
+}
+
+impl MutVisitor for Numberer<'_> {
+    fn visit_block(&mut self, block: &mut Block) {
+        match *block {
+            Block::Header(level, ref attr, _) => self.header(level, attr),
+            Block::Figure((ref id, _, _), ref mut caption, _)
+                if Kind::of(id) == Some(Kind::Figure) =>
+            {
+                let number = self.next(Kind::Figure, id);
+                self.caption(caption, &self.settings.figure_title, &number);
+            }
+            Block::Table((ref id, _, _), ref mut caption, ..)
+                if Kind::of(id) == Some(Kind::Table) =>
+            {
+                let number = self.next(Kind::Table, id);
+                self.caption(caption, &self.settings.table_title, &number);
+            }
+            Block::CodeBlock((ref id, ref classes, ref kvs), ref code)
+                if Kind::of(id) == Some(Kind::Listing) =>
+            {
+                let number = self.next(Kind::Listing, id);
+                let caption_text = kvs
+                    .iter()
+                    .find(|(k, _)| k == "caption")
+                    .map(|(_, v)| &v[..]);
+                let mut caption = (
+                    None,
+                    vec![Block::Para(tokenize(caption_text.unwrap_or("")))],
+                );
+                if caption_text.is_none() {
+                    caption.1.clear();
+                }
+                self.caption(&mut caption, &self.settings.listing_title, &number);
+                let kvs = kvs
+                    .iter()
+                    .filter(|(k, _)| k != "caption")
+                    .cloned()
+                    .collect();
+                let code = Block::CodeBlock((String::new(), classes.clone(), kvs), code.clone());
+                let mut content: Vec<Block> = caption.1;
+                content.push(code);
+                let attr = (id.clone(), vec!["listing".to_string()], vec![]);
+                *block = Block::Div(attr, content);
+                return;
+            }
+            _ => {}
+        }
+        self.walk_block(block)
+    }
+
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        let mut i = 0;
+        while i < vec_inline.len() {
+            if let Inline::Math(MathType::DisplayMath, _) = vec_inline[i] {
+                let space = matches!(vec_inline.get(i + 1), Some(Inline::Space));
+                let label_at = if space { i + 2 } else { i + 1 };
+                if let Some(id) = vec_inline.get(label_at).and_then(equation_label) {
+                    let attr = (id.to_string(), Vec::new(), Vec::new());
+                    vec_inline.drain(i + 1..=label_at);
+                    let math = vec_inline.remove(i);
+                    vec_inline.insert(i, Inline::Span(attr, vec![math]));
+                }
+            }
+            if let Inline::Span((ref id, _, _), ref mut content) = vec_inline[i] {
+                if Kind::of(id) == Some(Kind::Equation) {
+                    if let [Inline::Math(MathType::DisplayMath, ref mut tex)] = content[..] {
+                        let id = id.clone();
+                        self.equation(&id, tex);
+                        i += 1;
+                        continue;
+                    }
+                }
+            }
+            self.visit_inline(&mut vec_inline[i]);
+            i += 1;
+        }
+    }
+}
+
+struct Resolver<'a>(&'a Crossref);
+
+impl MutVisitor for Resolver<'_> {
+    fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+        let mut i = 0;
+        while i < vec_inline.len() {
+            if let Inline::Cite(ref citations, _) = vec_inline[i] {
+                if citations.iter().all(|c| Kind::of(&c.citationId).is_some()) {
+                    let reference = self.0.reference(citations);
+                    let len = reference.len();
+                    vec_inline.splice(i..=i, reference);
+                    i += len;
+                    continue;
+                }
+            }
+            self.visit_inline(&mut vec_inline[i]);
+            i += 1;
+        }
+    }
+}
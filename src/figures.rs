@@ -0,0 +1,144 @@
+use super::*;
+
+/// The image of a paragraph consisting of nothing else
+fn lone_image(block: &Block) -> Option<(&Attr, &Vec<Inline>, &Target)> {
+    match *block {
+        Block::Para(ref inlines) => match inlines[..] {
+            [Inline::Image(ref attr, ref alt, ref target)] => Some((attr, alt, target)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+struct Upgrade {
+    standalone: bool,
+}
+
+impl MutVisitor for Upgrade {
+    fn visit_block(&mut self, block: &mut Block) {
+        if let Some((attr, alt, (url, title))) = lone_image(block) {
+            let legacy = title.strip_prefix("fig:");
+            if legacy.is_some() || (self.standalone && !alt.is_empty()) {
+                let (id, classes, kvs) = attr.clone();
+                let title = legacy.unwrap_or(title).to_string();
+                let image = Inline::Image(
+                    (String::new(), classes, kvs),
+                    alt.clone(),
+                    (url.clone(), title),
+                );
+                let caption = if alt.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![Block::Plain(alt.clone())]
+                };
+                *block = Block::Figure(
+                    (id, vec![], vec![]),
+                    (None, caption),
+                    vec![Block::Plain(vec![image])],
+                );
+                return;
+            }
+        }
+        self.walk_block(block)
+    }
+}
+
+/// Turns paragraphs holding just an image into `Block::Figure`s, like
+/// pandoc 3 reads them.
+///
+/// Images whose title starts with `fig:` (the convention of pandoc before
+/// 3.0) are always converted and lose the prefix. With `standalone`, the
+/// `implicit_figures` behaviour, other lone images with a non-empty alt text
+/// are converted too. The alt text becomes the caption and the image's
+/// identifier moves to the figure.
+pub fn upgrade_figures(blocks: &mut Vec<Block>, standalone: bool) {
+    Upgrade { standalone }.visit_vec_block(blocks)
+}
+
+struct Downgrade;
+
+impl MutVisitor for Downgrade {
+    fn visit_block(&mut self, block: &mut Block) {
+        self.walk_block(block);
+        if let Block::Figure((ref id, ref classes, ref kvs), ref caption, ref content) = *block {
+            let caption_text = caption
+                .1
+                .iter()
+                .filter_map(|block| match *block {
+                    Block::Plain(ref inlines) | Block::Para(ref inlines) => Some(inlines.clone()),
+                    _ => None,
+                })
+                .reduce(|mut text, next| {
+                    text.push(Inline::Space);
+                    text.extend(next);
+                    text
+                })
+                .unwrap_or_default();
+            let image = match content[..] {
+                [Block::Plain(ref inlines)] | [Block::Para(ref inlines)] => match inlines[..] {
+                    [Inline::Image(ref attr, _, ref target)] => Some((attr, target)),
+                    _ => None,
+                },
+                _ => None,
+            };
+            *block = match image {
+                Some(((image_id, image_classes, image_kvs), (url, title))) => {
+                    let id = if id.is_empty() { image_id } else { id };
+                    let mut image_classes = image_classes.clone();
+                    for class in classes {
+                        if !image_classes.contains(class) {
+                            image_classes.push(class.clone());
+                        }
+                    }
+                    let mut image_kvs = image_kvs.clone();
+                    for (key, value) in kvs {
+                        if !image_kvs.iter().any(|(k, _)| k == key) {
+                            image_kvs.push((key.clone(), value.clone()));
+                        }
+                    }
+                    let image = Inline::Image(
+                        (id.clone(), image_classes, image_kvs),
+                        caption_text,
+                        (url.clone(), format!("fig:{}", title)),
+                    );
+                    Block::Para(vec![image])
+                }
+                None => {
+                    let mut classes = classes.clone();
+                    classes.insert(0, "figure".to_string());
+                    let mut blocks = content.clone();
+                    if !caption_text.is_empty() {
+                        let attr = (String::new(), vec!["caption".to_string()], vec![]);
+                        blocks.push(Block::Div(attr, vec![Block::Para(caption_text)]));
+                    }
+                    Block::Div((id.clone(), classes, kvs.clone()), blocks)
+                }
+            };
+        }
+    }
+}
+
+/// Reverts `upgrade_figures` for consumers that predate `Block::Figure`.
+///
+/// Figures holding a single image become a paragraph with that image, the
+/// caption as alt text and `fig:` prepended to its title. The figure's
+/// identifier, classes and attributes are merged into the image's, where
+/// the image's own attributes win. Other figures
+/// become a `Div` with class `figure`, followed by the caption in a `Div`
+/// with class `caption`.
+pub fn downgrade_figures(blocks: &mut Vec<Block>) {
+    Downgrade.visit_vec_block(blocks)
+}
+
+impl Pandoc {
+    /// Applies `upgrade_figures` to the document's blocks
+    pub fn upgrade_figures(&mut self, standalone: bool) {
+        upgrade_figures(&mut self.blocks, standalone)
+    }
+
+    /// Applies `downgrade_figures` to the document's blocks
+    pub fn downgrade_figures(&mut self) {
+        downgrade_figures(&mut self.blocks)
+    }
+}
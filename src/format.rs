@@ -0,0 +1,128 @@
+use super::*;
+
+/// Extension modifiers of a format spec like `markdown+smart-citations`
+///
+/// Modifiers are applied left to right, so a later `-ext` overrides an
+/// earlier `+ext` for the same extension.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Extensions {
+    changes: Map<String, bool>,
+}
+
+impl Extensions {
+    /// Parses a sequence of `+ext`/`-ext` modifiers
+    ///
+    /// Anything before the first `+` or `-` is ignored.
+    pub fn parse(spec: &str) -> Self {
+        let mut changes = Map::new();
+        let start = spec.find(['+', '-']).unwrap_or(spec.len());
+        let mut rest = &spec[start..];
+        while let Some(sign) = rest.chars().next() {
+            rest = &rest[sign.len_utf8()..];
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let name = &rest[..end];
+            rest = &rest[end..];
+            if name.is_empty() {
+                continue;
+            }
+            changes.insert(name.to_lowercase(), sign == '+');
+        }
+        Extensions { changes }
+    }
+
+    /// `Some(true)` if the extension is explicitly enabled,
+    /// `Some(false)` if it is explicitly disabled, `None` otherwise
+    pub fn get(&self, name: &str) -> Option<bool> {
+        self.changes.get(&name.to_lowercase()).copied()
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.get(name) == Some(true)
+    }
+
+    pub fn is_disabled(&self, name: &str) -> bool {
+        self.get(name) == Some(false)
+    }
+
+    /// Extensions switched on with `+ext`
+    pub fn enabled(&self) -> impl Iterator<Item = &str> {
+        self.changes
+            .iter()
+            .filter(|(_, &on)| on)
+            .map(|(name, _)| &name[..])
+    }
+
+    /// Extensions switched off with `-ext`
+    pub fn disabled(&self) -> impl Iterator<Item = &str> {
+        self.changes
+            .iter()
+            .filter(|(_, &on)| !on)
+            .map(|(name, _)| &name[..])
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+/// Collapses aliases that pandoc treats as the same raw format
+fn canonical(name: &str) -> &str {
+    match name {
+        "tex" => "latex",
+        "html4" | "html5" => "html",
+        "markdown_strict" | "markdown_phpextra" | "markdown_mmd" | "markdown_github" => "markdown",
+        other => other,
+    }
+}
+
+/// Raw formats accepted by a writer in addition to its own
+fn also_accepts(target: &str) -> &'static [&'static str] {
+    match target {
+        "beamer" => &["latex"],
+        "epub" | "epub2" | "epub3" | "chunkedhtml" | "revealjs" | "slidy" | "slideous" | "s5"
+        | "dzslides" => &["html"],
+        "docx" | "pptx" => &["openxml"],
+        "odt" => &["opendocument"],
+        _ => &[],
+    }
+}
+
+impl Format {
+    pub fn new(name: impl Into<String>) -> Self {
+        Format(name.into())
+    }
+
+    /// The format name without extension modifiers, lowercased and with
+    /// aliases like `tex` or `html5` resolved to their canonical name
+    pub fn base_name(&self) -> String {
+        let end = self.0.find(['+', '-']).unwrap_or(self.0.len());
+        canonical(&self.0[..end].to_lowercase()).to_string()
+    }
+
+    /// The `+ext-ext` modifiers following the format name
+    pub fn extensions(&self) -> Extensions {
+        let start = self.0.find(['+', '-']).unwrap_or(self.0.len());
+        Extensions::parse(&self.0[start..])
+    }
+
+    /// Whether a raw block or inline in this format should be emitted when
+    /// writing to `target`, following pandoc's rules (e.g. `tex` content
+    /// is included in `latex` and `beamer` output)
+    pub fn matches(&self, target: &Format) -> bool {
+        let raw = self.base_name();
+        let target = target.base_name();
+        raw == target || also_accepts(&target).contains(&&raw[..])
+    }
+}
+
+impl From<&str> for Format {
+    fn from(name: &str) -> Self {
+        Format(name.to_string())
+    }
+}
+
+impl From<String> for Format {
+    fn from(name: String) -> Self {
+        Format(name)
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+use std::error::Error;
+use std::fmt;
+
+/// What `shift_headings` does with headers that end up outside 1 to 6
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Overflow {
+    /// Use level 1 or 6 instead
+    #[default]
+    Clamp,
+    /// Replace the header by a paragraph with its text in bold
+    Paragraph,
+    /// Fail without changing the document
+    Error,
+}
+
+/// A header would end up outside 1 to 6 with `Overflow::Error`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShiftError {
+    /// The identifier of the header
+    pub id: String,
+    /// The level the header would have been shifted to
+    pub level: Int,
+}
+
+impl fmt::Display for ShiftError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "header ")?;
+        if !self.id.is_empty() {
+            write!(f, "`{}` ", self.id)?;
+        }
+        write!(f, "would be shifted to level {}", self.level)
+    }
+}
+
+impl Error for ShiftError {}
+
+/// Adds `delta` to the level of every header, like pandoc's
+/// `--shift-heading-level-by`
+pub fn shift_headings(
+    pandoc: &mut Pandoc,
+    delta: Int,
+    overflow: Overflow,
+) -> Result<(), ShiftError> {
+    struct Check(Int, Option<ShiftError>);
+    impl Visitor for Check {
+        fn visit_block(&mut self, block: &Block) {
+            if let Block::Header(level, (ref id, _, _), _) = *block {
+                let level = level + self.0;
+                if self.1.is_none() && !(1..=6).contains(&level) {
+                    self.1 = Some(ShiftError {
+                        id: id.clone(),
+                        level,
+                    });
+                }
+            }
+            self.walk_block(block)
+        }
+    }
+    struct Shift(Int, Overflow);
+    impl MutVisitor for Shift {
+        fn visit_block(&mut self, block: &mut Block) {
+            if let Block::Header(ref mut level, _, ref mut title) = *block {
+                *level += self.0;
+                if !(1..=6).contains(level) {
+                    match self.1 {
+                        Overflow::Paragraph => {
+                            let strong = Inline::Strong(std::mem::take(title));
+                            *block = Block::Para(vec![strong]);
+                        }
+                        _ => *level = (*level).clamp(1, 6),
+                    }
+                }
+            }
+            self.walk_block(block)
+        }
+    }
+    if overflow == Overflow::Error {
+        let mut check = Check(delta, None);
+        check.visit_vec_block(&pandoc.blocks);
+        if let Some(error) = check.1 {
+            return Err(error);
+        }
+    }
+    Shift(delta, overflow).visit_vec_block(&mut pandoc.blocks);
+    Ok(())
+}
+
+/// Moves a leading level 1 header into the `title` metadata field, unless
+/// the document already has a title. Returns whether it did.
+pub fn lift_title(pandoc: &mut Pandoc) -> bool {
+    if pandoc.meta.contains_key("title") {
+        return false;
+    }
+    match pandoc.blocks.first() {
+        Some(Block::Header(1, ..)) => {}
+        _ => return false,
+    }
+    if let Block::Header(_, _, title) = pandoc.blocks.remove(0) {
+        pandoc
+            .meta
+            .insert("title".to_string(), MetaValue::MetaInlines(title));
+    }
+    true
+}
+
+/// Fixes skipped levels: a header is at most one level deeper than the
+/// closest preceding header of a lower level, so `H1 H3 H4 H2` becomes
+/// `H1 H2 H3 H2`. The outermost headers keep their level.
+pub fn normalize_headings(blocks: &mut Vec<Block>) {
+    struct Normalize(Vec<(Int, Int)>);
+    impl MutVisitor for Normalize {
+        fn visit_block(&mut self, block: &mut Block) {
+            if let Block::Header(ref mut level, ..) = *block {
+                while self.0.last().is_some_and(|&(orig, _)| orig >= *level) {
+                    self.0.pop();
+                }
+                let orig = *level;
+                if let Some(&(_, parent)) = self.0.last() {
+                    *level = (*level).min(parent + 1);
+                }
+                self.0.push((orig, *level));
+            }
+            self.walk_block(block)
+        }
+    }
+    Normalize(Vec::new()).visit_vec_block(blocks)
+}
+
+impl Pandoc {
+    /// Applies `shift_headings` to the document
+    pub fn shift_headings(&mut self, delta: Int, overflow: Overflow) -> Result<(), ShiftError> {
+        shift_headings(self, delta, overflow)
+    }
+
+    /// Applies `lift_title` to the document
+    pub fn lift_title(&mut self) -> bool {
+        lift_title(self)
+    }
+
+    /// Applies `normalize_headings` to the document's blocks
+    pub fn normalize_headings(&mut self) {
+        normalize_headings(&mut self.blocks)
+    }
+}
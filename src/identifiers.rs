@@ -0,0 +1,219 @@
+use super::*;
+use std::collections::HashSet;
+
+/// Which of pandoc's algorithms turns header text into an identifier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum IdentifierStyle {
+    /// The `auto_identifiers` extension: punctuation other than `_`, `-`
+    /// and `.` is removed, words are joined by `-` and everything up to the
+    /// first letter is dropped
+    #[default]
+    Pandoc,
+    /// The `gfm_auto_identifiers` extension: like GitHub, every whitespace
+    /// character becomes `-` and leading non-letters are kept
+    Gfm,
+}
+
+/// Generates header identifiers the way pandoc does, without collisions
+///
+/// Identifiers that are already taken get a numeric suffix: the second
+/// `Intro` header becomes `intro-1`.
+#[derive(Debug, Clone, Default)]
+pub struct IdGenerator {
+    style: IdentifierStyle,
+    ascii: bool,
+    used: HashSet<String>,
+}
+
+impl IdGenerator {
+    pub fn new(style: IdentifierStyle) -> Self {
+        IdGenerator {
+            style,
+            ..IdGenerator::default()
+        }
+    }
+
+    /// Picks the style from the `gfm_auto_identifiers` and
+    /// `ascii_identifiers` extensions
+    pub fn from_extensions(extensions: &Extensions) -> Self {
+        let style = if extensions.is_enabled("gfm_auto_identifiers") {
+            IdentifierStyle::Gfm
+        } else {
+            IdentifierStyle::Pandoc
+        };
+        IdGenerator::new(style).ascii(extensions.is_enabled("ascii_identifiers"))
+    }
+
+    /// Restricts identifiers to ASCII (the `ascii_identifiers` extension);
+    /// accented letters lose their accents, other characters are dropped
+    pub fn ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// Marks the identifiers of all headers, divs, spans, figures and code
+    /// blocks in the document as taken
+    pub fn seed(&mut self, pandoc: &Pandoc) {
+        struct Collect<'a>(&'a mut HashSet<String>);
+        impl Collect<'_> {
+            fn insert(&mut self, id: &str) {
+                if !id.is_empty() {
+                    self.0.insert(id.to_string());
+                }
+            }
+        }
+        impl Visitor for Collect<'_> {
+            fn visit_block(&mut self, block: &Block) {
+                match *block {
+                    Block::Header(_, (ref id, _, _), _)
+                    | Block::Div((ref id, _, _), _)
+                    | Block::Figure((ref id, _, _), _, _)
+                    | Block::CodeBlock((ref id, _, _), _) => self.insert(id),
+                    _ => {}
+                }
+                self.walk_block(block)
+            }
+            fn visit_inline(&mut self, inline: &Inline) {
+                if let Inline::Span((ref id, _, _), _) = *inline {
+                    self.insert(id);
+                }
+                self.walk_inline(inline)
+            }
+        }
+        Collect(&mut self.used).walk_pandoc(pandoc);
+    }
+
+    /// Marks `id` as taken, returns `false` if it already was
+    pub fn reserve(&mut self, id: impl Into<String>) -> bool {
+        self.used.insert(id.into())
+    }
+
+    pub fn is_used(&self, id: &str) -> bool {
+        self.used.contains(id)
+    }
+
+    /// The identifier pandoc derives from the inlines, without checking
+    /// for collisions. May be empty.
+    pub fn identifier(&self, inlines: &[Inline]) -> String {
+        struct Unemojify;
+        impl MutVisitor for Unemojify {
+            fn visit_inline(&mut self, inline: &mut Inline) {
+                if let Inline::Span((ref id, ref classes, ref kvs), _) = *inline {
+                    let emoji = kvs.iter().find(|(k, _)| k == "data-emoji");
+                    if let (true, [class], Some((_, name))) = (id.is_empty(), &classes[..], emoji) {
+                        if class == "emoji" {
+                            *inline = Inline::Str(name.clone());
+                            return;
+                        }
+                    }
+                }
+                self.walk_inline(inline)
+            }
+        }
+        let mut inlines = inlines.to_vec();
+        Unemojify.visit_vec_inline(&mut inlines);
+        text_to_identifier(&stringify(&inlines), self.style, self.ascii)
+    }
+
+    /// Makes `base` unique by appending `-1`, `-2`, ... and marks the result
+    /// as taken. An empty `base` is replaced by `section`.
+    pub fn unique(&mut self, base: &str) -> String {
+        let base = if base.is_empty() { "section" } else { base };
+        let mut id = base.to_string();
+        let mut n = 0;
+        while self.used.contains(&id) {
+            n += 1;
+            id = format!("{}-{}", base, n);
+        }
+        self.used.insert(id.clone());
+        id
+    }
+
+    /// A fresh identifier for a header with the given text
+    pub fn generate(&mut self, inlines: &[Inline]) -> String {
+        let base = self.identifier(inlines);
+        self.unique(&base)
+    }
+}
+
+/// Applies pandoc's identifier rules to plain text
+pub fn text_to_identifier(text: &str, style: IdentifierStyle, ascii: bool) -> String {
+    let lower = text.to_lowercase();
+    let ident: String = match style {
+        IdentifierStyle::Pandoc => {
+            let kept: String = lower
+                .chars()
+                .filter(|&c| c.is_whitespace() || c.is_alphanumeric() || "_-.".contains(c))
+                .collect();
+            kept.split_whitespace().collect::<Vec<_>>().join("-")
+        }
+        IdentifierStyle::Gfm => lower
+            .chars()
+            .map(|c| if c.is_whitespace() { '-' } else { c })
+            .filter(|&c| {
+                c.is_alphanumeric() || c == '-' || is_mark(c) || is_connector_punctuation(c)
+            })
+            .collect(),
+    };
+    let ident = if ascii {
+        ident.chars().filter_map(to_ascii).collect()
+    } else {
+        ident
+    };
+    match style {
+        IdentifierStyle::Pandoc => ident
+            .trim_start_matches(|c: char| !c.is_alphabetic())
+            .to_string(),
+        IdentifierStyle::Gfm => ident,
+    }
+}
+
+fn is_mark(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE20}'..='\u{FE2F}')
+}
+
+fn is_connector_punctuation(c: char) -> bool {
+    matches!(
+        c,
+        '_' | '\u{203F}' | '\u{2040}' | '\u{2054}' | '\u{FE33}' | '\u{FE34}' | '\u{FE4D}'
+            ..='\u{FE4F}' | '\u{FF3F}'
+    )
+}
+
+/// The base letter of characters whose canonical decomposition starts with
+/// an ASCII character, `None` for all other non-ASCII characters
+fn to_ascii(c: char) -> Option<char> {
+    const TABLE: &[(&str, char)] = &[
+        ("àáâãäåāăą", 'a'),
+        ("çćĉċč", 'c'),
+        ("ď", 'd'),
+        ("èéêëēĕėęě", 'e'),
+        ("ĝğġģ", 'g'),
+        ("ĥ", 'h'),
+        ("ìíîïĩīĭį", 'i'),
+        ("ĵ", 'j'),
+        ("ķ", 'k'),
+        ("ĺļľ", 'l'),
+        ("ñńņň", 'n'),
+        ("òóôõöōŏő", 'o'),
+        ("ŕŗř", 'r'),
+        ("śŝşš", 's'),
+        ("ţť", 't'),
+        ("ùúûüũūŭůűų", 'u'),
+        ("ŵ", 'w'),
+        ("ýÿŷ", 'y'),
+        ("źżž", 'z'),
+    ];
+    if c.is_ascii() {
+        return Some(c);
+    }
+    TABLE
+        .iter()
+        .find(|(accented, _)| accented.contains(c))
+        .map(|&(_, base)| base)
+}
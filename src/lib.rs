@@ -1,9 +1,40 @@
+pub mod crossref;
+mod figures;
+mod format;
+mod headings;
+mod identifiers;
+mod merge;
+mod meta;
+mod meta_serde;
+mod native;
+mod numbering;
+pub mod readers;
+mod sections;
+mod split;
+mod stringify;
+mod toc;
+mod tree;
 mod visitor;
+pub mod writers;
 
 use serde_derive::{Deserialize, Serialize};
 use serde_json::{from_str, to_string};
 
+pub use figures::*;
+pub use format::*;
+pub use headings::*;
+pub use identifiers::*;
+pub use merge::*;
+pub use meta::{merge_meta, JsonOptions, ListMerge};
+pub use meta_serde::*;
+pub use native::*;
+pub use numbering::*;
+pub use sections::*;
+pub use split::*;
 pub use std::collections::BTreeMap as Map;
+pub use stringify::{stringify, Stringify};
+pub use toc::*;
+pub use tree::*;
 pub use visitor::*;
 pub type Int = i64;
 pub type Double = f64;
@@ -0,0 +1,184 @@
+use super::*;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+
+/// Which document wins when metadata fields clash in `Pandoc::merge`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum MetaPolicy {
+    /// Fields of later documents override those of earlier ones
+    #[default]
+    LastWins,
+    /// Fields of earlier documents are kept
+    FirstWins,
+}
+
+/// Options for `Pandoc::merge`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MergeOptions {
+    pub meta: MetaPolicy,
+    /// How clashing metadata lists are combined
+    pub lists: ListMerge,
+    /// Wrap the blocks of each document in a `Div` with a `source`
+    /// attribute holding the document's name
+    pub wrap: bool,
+}
+
+/// Why `Pandoc::merge` failed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MergeError {
+    /// There were no documents to merge
+    Empty,
+    /// A document uses a different `pandoc-api-version` than the first one
+    VersionMismatch {
+        name: String,
+        expected: Vec<u32>,
+        found: Vec<u32>,
+    },
+}
+
+impl fmt::Display for MergeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MergeError::Empty => write!(f, "no documents to merge"),
+            MergeError::VersionMismatch {
+                ref name,
+                ref expected,
+                ref found,
+            } => write!(
+                f,
+                "`{}` has pandoc-api-version {:?}, expected {:?}",
+                name, found, expected
+            ),
+        }
+    }
+}
+
+impl Error for MergeError {}
+
+/// Like `MetaValue::merge`, but values of `self` win
+fn keep_merge(this: &mut MetaValue, other: MetaValue, lists: ListMerge) {
+    match (this, other) {
+        (MetaValue::MetaMap(ref mut this), MetaValue::MetaMap(other)) => {
+            for (key, value) in other {
+                match this.get_mut(&key) {
+                    Some(existing) => keep_merge(existing, *value, lists),
+                    None => {
+                        this.insert(key, value);
+                    }
+                }
+            }
+        }
+        (MetaValue::MetaList(ref mut this), MetaValue::MetaList(other))
+            if lists == ListMerge::Append =>
+        {
+            this.extend(other)
+        }
+        _ => {}
+    }
+}
+
+/// Renames identifiers and the `#` links pointing to them
+struct Rename<'a>(&'a HashMap<String, String>);
+
+impl MutVisitor for Rename<'_> {
+    fn visit_attr(&mut self, attr: &mut Attr) {
+        if let Some(new) = self.0.get(&attr.0) {
+            attr.0 = new.clone();
+        }
+    }
+
+    fn visit_inline(&mut self, inline: &mut Inline) {
+        if let Inline::Link(_, _, (ref mut url, _)) = *inline {
+            let renamed = url.strip_prefix('#').and_then(|id| self.0.get(id));
+            if let Some(new) = renamed {
+                *url = format!("#{}", new);
+            }
+        }
+        self.walk_inline(inline)
+    }
+}
+
+impl Pandoc {
+    /// Concatenates named documents into one.
+    ///
+    /// Metadata is deep merged as in `merge_meta`, with `options.meta`
+    /// deciding which side wins. Identifiers that an earlier document
+    /// already uses are renamed like `IdGenerator::unique` does (`intro`
+    /// becomes `intro-1`), and links to `#intro` within the renamed
+    /// document follow. All documents must share a `pandoc-api-version`.
+    pub fn merge<I>(docs: I, options: MergeOptions) -> Result<Pandoc, MergeError>
+    where
+        I: IntoIterator<Item = (String, Pandoc)>,
+    {
+        let mut merged: Option<Pandoc> = None;
+        let mut ids = IdGenerator::default();
+        for (name, mut doc) in docs {
+            if let Some(ref merged) = merged {
+                if merged.pandoc_api_version != doc.pandoc_api_version {
+                    return Err(MergeError::VersionMismatch {
+                        name,
+                        expected: merged.pandoc_api_version.clone(),
+                        found: doc.pandoc_api_version,
+                    });
+                }
+            }
+
+            struct Collect(HashSet<String>);
+            impl Visitor for Collect {
+                fn visit_attr(&mut self, attr: &Attr) {
+                    if !attr.0.is_empty() {
+                        self.0.insert(attr.0.clone());
+                    }
+                }
+            }
+            let mut collect = Collect(HashSet::new());
+            collect.walk_pandoc(&doc);
+            let (mut clashing, fresh): (Vec<String>, Vec<String>) =
+                collect.0.into_iter().partition(|id| ids.is_used(id));
+            // reserve the fresh ones first so renamed ids can't collide
+            // with them
+            for id in fresh {
+                ids.reserve(id);
+            }
+            clashing.sort();
+            let renames: HashMap<String, String> = clashing
+                .into_iter()
+                .map(|id| {
+                    let new = ids.unique(&id);
+                    (id, new)
+                })
+                .collect();
+            if !renames.is_empty() {
+                Rename(&renames).walk_pandoc(&mut doc);
+            }
+
+            if options.wrap {
+                let attr = (String::new(), vec![], vec![("source".to_string(), name)]);
+                doc.blocks = vec![Block::Div(attr, doc.blocks)];
+            }
+            match merged {
+                None => merged = Some(doc),
+                Some(ref mut merged) => {
+                    merged.blocks.extend(doc.blocks);
+                    match options.meta {
+                        MetaPolicy::LastWins => {
+                            merge_meta(&mut merged.meta, doc.meta, options.lists)
+                        }
+                        MetaPolicy::FirstWins => {
+                            for (key, value) in doc.meta {
+                                match merged.meta.get_mut(&key) {
+                                    Some(existing) => keep_merge(existing, value, options.lists),
+                                    None => {
+                                        merged.meta.insert(key, value);
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        merged.ok_or(MergeError::Empty)
+    }
+}
@@ -0,0 +1,354 @@
+use super::*;
+use stringify::Stringify;
+
+impl MetaValue {
+    /// The contents of a `MetaString`
+    pub fn as_str(&self) -> Option<&str> {
+        match *self {
+            MetaValue::MetaString(ref s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// The contents of a `MetaBool`
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            MetaValue::MetaBool(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// The elements of a `MetaList`
+    pub fn as_list(&self) -> Option<&[MetaValue]> {
+        match *self {
+            MetaValue::MetaList(ref list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<MetaValue>> {
+        match *self {
+            MetaValue::MetaList(ref mut list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// The entries of a `MetaMap`
+    pub fn as_map(&self) -> Option<&Map<String, Box<MetaValue>>> {
+        match *self {
+            MetaValue::MetaMap(ref map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_map_mut(&mut self) -> Option<&mut Map<String, Box<MetaValue>>> {
+        match *self {
+            MetaValue::MetaMap(ref mut map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Flattens the value to plain text.
+    ///
+    /// `MetaInlines` and `MetaBlocks` lose all formatting, booleans become
+    /// `true`/`false` and the elements of lists and maps are concatenated.
+    pub fn stringify(&self) -> String {
+        Stringify::stringify(self)
+    }
+
+    /// Looks up a direct child: a key of a `MetaMap` or an index into a `MetaList`
+    pub fn get(&self, key: &str) -> Option<&MetaValue> {
+        match *self {
+            MetaValue::MetaMap(ref map) => map.get(key).map(|v| &**v),
+            MetaValue::MetaList(ref list) => list.get(key.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut MetaValue> {
+        match *self {
+            MetaValue::MetaMap(ref mut map) => map.get_mut(key).map(|v| &mut **v),
+            MetaValue::MetaList(ref mut list) => list.get_mut(key.parse::<usize>().ok()?),
+            _ => None,
+        }
+    }
+
+    /// Looks up a dot separated path like `author.0.name`
+    pub fn get_path(&self, path: &str) -> Option<&MetaValue> {
+        path.split('.').try_fold(self, |value, key| value.get(key))
+    }
+
+    pub fn get_path_mut(&mut self, path: &str) -> Option<&mut MetaValue> {
+        path.split('.')
+            .try_fold(self, |value, key| value.get_mut(key))
+    }
+
+    /// Returns the child `key`, creating it if necessary.
+    ///
+    /// Values that are neither maps nor lists are replaced by an empty map.
+    /// An index past the end of a list appends a new element.
+    fn entry(&mut self, key: &str) -> &mut MetaValue {
+        let index = match *self {
+            MetaValue::MetaList(ref list) => key.parse::<usize>().ok().map(|i| i.min(list.len())),
+            _ => None,
+        };
+        if let Some(index) = index {
+            let list = self.as_list_mut().unwrap();
+            if index == list.len() {
+                list.push(MetaValue::MetaMap(Map::new()));
+            }
+            return &mut list[index];
+        }
+        if self.as_map().is_none() {
+            *self = MetaValue::MetaMap(Map::new());
+        }
+        let map = self.as_map_mut().unwrap();
+        map.entry(key.to_string())
+            .or_insert_with(|| Box::new(MetaValue::MetaMap(Map::new())))
+    }
+
+    /// Sets the value at a dot separated path, creating intermediate maps
+    pub fn set_path(&mut self, path: &str, value: impl Into<MetaValue>) {
+        let target = path.split('.').fold(self, |value, key| value.entry(key));
+        *target = value.into();
+    }
+}
+
+/// Options for converting between `MetaValue` and `serde_json::Value`
+///
+/// The mapping is: JSON strings become `MetaString` (or `MetaInlines` with
+/// `strings_as_inlines`), arrays `MetaList`, objects `MetaMap`, booleans
+/// `MetaBool`, numbers `MetaString` and `null` an empty `MetaString`.
+/// In the other direction `MetaInlines` and `MetaBlocks` are flattened to
+/// strings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// Tokenize JSON strings into `Str`, `Space` and `SoftBreak` inlines
+    pub strings_as_inlines: bool,
+    /// Turn strings that are valid JSON numbers back into numbers, making
+    /// a JSON to metadata to JSON round trip lossless for numbers
+    pub parse_numbers: bool,
+}
+
+/// How lists are combined when merging metadata
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ListMerge {
+    /// The list of the overriding side replaces the original list
+    #[default]
+    Replace,
+    /// The elements of the overriding side are appended to the original list
+    Append,
+}
+
+/// Splits text into `Str` inlines separated by `Space` or `SoftBreak`
+pub(crate) fn tokenize(text: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    let mut word = String::new();
+    let mut space: Option<Inline> = None;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !word.is_empty() {
+                inlines.push(Inline::Str(std::mem::take(&mut word)));
+            }
+            if c == '\n' {
+                space = Some(Inline::SoftBreak);
+            } else if space.is_none() {
+                space = Some(Inline::Space);
+            }
+        } else {
+            if let Some(space) = space.take() {
+                if !inlines.is_empty() {
+                    inlines.push(space);
+                }
+            }
+            word.push(c);
+        }
+    }
+    if !word.is_empty() {
+        inlines.push(Inline::Str(word));
+    }
+    inlines
+}
+
+impl MetaValue {
+    /// Converts a JSON value according to the mapping described at `JsonOptions`
+    pub fn from_json_value(value: &serde_json::Value, options: &JsonOptions) -> Self {
+        use serde_json::Value;
+        match *value {
+            Value::Null => MetaValue::MetaString(String::new()),
+            Value::Bool(b) => MetaValue::MetaBool(b),
+            Value::Number(ref n) => MetaValue::MetaString(n.to_string()),
+            Value::String(ref s) if options.strings_as_inlines => {
+                MetaValue::MetaInlines(tokenize(s))
+            }
+            Value::String(ref s) => MetaValue::MetaString(s.clone()),
+            Value::Array(ref a) => MetaValue::MetaList(
+                a.iter()
+                    .map(|v| MetaValue::from_json_value(v, options))
+                    .collect(),
+            ),
+            Value::Object(ref o) => MetaValue::MetaMap(
+                o.iter()
+                    .map(|(k, v)| (k.clone(), Box::new(MetaValue::from_json_value(v, options))))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Converts to a JSON value according to the mapping described at `JsonOptions`
+    pub fn to_json_value(&self, options: &JsonOptions) -> serde_json::Value {
+        use serde_json::Value;
+        match *self {
+            MetaValue::MetaMap(ref map) => Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), v.to_json_value(options)))
+                    .collect(),
+            ),
+            MetaValue::MetaList(ref list) => {
+                Value::Array(list.iter().map(|v| v.to_json_value(options)).collect())
+            }
+            MetaValue::MetaBool(b) => Value::Bool(b),
+            MetaValue::MetaString(ref s) => match s.parse::<serde_json::Number>() {
+                Ok(n) if options.parse_numbers => Value::Number(n),
+                _ => Value::String(s.clone()),
+            },
+            MetaValue::MetaInlines(_) | MetaValue::MetaBlocks(_) => Value::String(self.stringify()),
+        }
+    }
+
+    /// Unwraps a `MetaMap` into the representation used by `Pandoc::meta`
+    pub fn into_map(self) -> Option<Map<String, MetaValue>> {
+        match self {
+            MetaValue::MetaMap(map) => Some(map.into_iter().map(|(k, v)| (k, *v)).collect()),
+            _ => None,
+        }
+    }
+
+    /// Deep-merges `other` into `self`.
+    ///
+    /// Maps are merged key by key, lists according to `lists` and all other
+    /// values of `other` replace the ones in `self`.
+    pub fn merge(&mut self, other: MetaValue, lists: ListMerge) {
+        match (self, other) {
+            (MetaValue::MetaMap(ref mut this), MetaValue::MetaMap(other)) => {
+                for (key, value) in other {
+                    match this.get_mut(&key) {
+                        Some(existing) => existing.merge(*value, lists),
+                        None => {
+                            this.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (MetaValue::MetaList(ref mut this), MetaValue::MetaList(other))
+                if lists == ListMerge::Append =>
+            {
+                this.extend(other)
+            }
+            (this, other) => *this = other,
+        }
+    }
+}
+
+/// Deep-merges the metadata `other` into `meta`, see `MetaValue::merge`
+pub fn merge_meta(
+    meta: &mut Map<String, MetaValue>,
+    other: Map<String, MetaValue>,
+    lists: ListMerge,
+) {
+    for (key, value) in other {
+        match meta.get_mut(&key) {
+            Some(existing) => existing.merge(value, lists),
+            None => {
+                meta.insert(key, value);
+            }
+        }
+    }
+}
+
+impl Pandoc {
+    /// Looks up a metadata value by a dot separated path like `author.0.name`.
+    /// Numeric path segments index into lists.
+    pub fn meta_get(&self, path: &str) -> Option<&MetaValue> {
+        let (first, rest) = split_path(path);
+        let value = self.meta.get(first)?;
+        match rest {
+            Some(rest) => value.get_path(rest),
+            None => Some(value),
+        }
+    }
+
+    pub fn meta_get_mut(&mut self, path: &str) -> Option<&mut MetaValue> {
+        let (first, rest) = split_path(path);
+        let value = self.meta.get_mut(first)?;
+        match rest {
+            Some(rest) => value.get_path_mut(rest),
+            None => Some(value),
+        }
+    }
+
+    /// Sets a metadata value by a dot separated path, creating intermediate
+    /// maps as needed: `pandoc.meta_set("toc", true)`
+    pub fn meta_set(&mut self, path: &str, value: impl Into<MetaValue>) {
+        let (first, rest) = split_path(path);
+        match rest {
+            Some(rest) => self
+                .meta
+                .entry(first.to_string())
+                .or_insert_with(|| MetaValue::MetaMap(Map::new()))
+                .set_path(rest, value),
+            None => {
+                self.meta.insert(first.to_string(), value.into());
+            }
+        }
+    }
+}
+
+fn split_path(path: &str) -> (&str, Option<&str>) {
+    match path.split_once('.') {
+        Some((first, rest)) => (first, Some(rest)),
+        None => (path, None),
+    }
+}
+
+impl From<bool> for MetaValue {
+    fn from(b: bool) -> Self {
+        MetaValue::MetaBool(b)
+    }
+}
+
+impl From<&str> for MetaValue {
+    fn from(s: &str) -> Self {
+        MetaValue::MetaString(s.to_string())
+    }
+}
+
+impl From<String> for MetaValue {
+    fn from(s: String) -> Self {
+        MetaValue::MetaString(s)
+    }
+}
+
+impl From<Vec<MetaValue>> for MetaValue {
+    fn from(list: Vec<MetaValue>) -> Self {
+        MetaValue::MetaList(list)
+    }
+}
+
+impl From<Vec<Inline>> for MetaValue {
+    fn from(inlines: Vec<Inline>) -> Self {
+        MetaValue::MetaInlines(inlines)
+    }
+}
+
+impl From<Vec<Block>> for MetaValue {
+    fn from(blocks: Vec<Block>) -> Self {
+        MetaValue::MetaBlocks(blocks)
+    }
+}
+
+impl From<Map<String, MetaValue>> for MetaValue {
+    fn from(map: Map<String, MetaValue>) -> Self {
+        MetaValue::MetaMap(map.into_iter().map(|(k, v)| (k, Box::new(v))).collect())
+    }
+}
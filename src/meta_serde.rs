@@ -0,0 +1,841 @@
+//! Mapping document metadata onto Rust types via serde
+
+use super::*;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::ser::{self, Serialize};
+use std::fmt;
+
+/// Error raised when converting between metadata and Rust types
+///
+/// Carries the dot separated path (as accepted by `Pandoc::meta_get`) of
+/// the metadata value that could not be converted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MetaError {
+    path: Option<String>,
+    message: String,
+}
+
+impl MetaError {
+    /// Path of the offending value, `None` if the error is about the root
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    fn at(mut self, path: &str) -> Self {
+        if self.path.is_none() && !path.is_empty() {
+            self.path = Some(path.to_string());
+        }
+        self
+    }
+
+    /// Prefixes the path with the key of the enclosing container
+    fn within(mut self, key: &str) -> Self {
+        self.path = Some(match self.path {
+            Some(path) => child_path(key, &path),
+            None => key.to_string(),
+        });
+        self
+    }
+}
+
+impl fmt::Display for MetaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path {
+            Some(ref path) => write!(f, "{}: {}", path, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for MetaError {}
+
+impl de::Error for MetaError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        MetaError {
+            path: None,
+            message: msg.to_string(),
+        }
+    }
+}
+
+impl ser::Error for MetaError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        de::Error::custom(msg)
+    }
+}
+
+/// Deserializes a Rust value from document metadata:
+/// `let cfg: MyCfg = pandoc_ast::from_meta(&pandoc.meta)?`
+pub fn from_meta<'de, T: de::Deserialize<'de>>(
+    meta: &'de Map<String, MetaValue>,
+) -> Result<T, MetaError> {
+    let map = MapDeserializer {
+        iter: meta.iter().map(|(k, v)| (&k[..], v)),
+        len: meta.len(),
+        value: None,
+        path: String::new(),
+    };
+    T::deserialize(de::value::MapAccessDeserializer::new(map))
+}
+
+/// Deserializes a Rust value from a single metadata value
+pub fn from_meta_value<'de, T: de::Deserialize<'de>>(
+    value: &'de MetaValue,
+) -> Result<T, MetaError> {
+    T::deserialize(MetaDeserializer::new(value))
+}
+
+/// Serializes a struct or map into document metadata
+pub fn to_meta<T: Serialize + ?Sized>(value: &T) -> Result<Map<String, MetaValue>, MetaError> {
+    match to_meta_value(value)? {
+        MetaValue::MetaMap(map) => Ok(map.into_iter().map(|(k, v)| (k, *v)).collect()),
+        _ => Err(de::Error::custom("document metadata must be a map")),
+    }
+}
+
+/// Serializes a Rust value into a single metadata value
+pub fn to_meta_value<T: Serialize + ?Sized>(value: &T) -> Result<MetaValue, MetaError> {
+    Ok(value
+        .serialize(MetaSerializer)?
+        .unwrap_or_else(|| MetaValue::MetaString(String::new())))
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+/// A `serde::Deserializer` reading from a `MetaValue`
+///
+/// `MetaString`, `MetaInlines` and `MetaBlocks` are read as strings (the
+/// latter two flattened to plain text), strings that look like numbers
+/// can be read as numbers, `MetaBool` as bool, `MetaMap` as struct or map
+/// and `MetaList` as sequence.
+pub struct MetaDeserializer<'de> {
+    value: &'de MetaValue,
+    path: String,
+}
+
+impl<'de> MetaDeserializer<'de> {
+    pub fn new(value: &'de MetaValue) -> Self {
+        MetaDeserializer {
+            value,
+            path: String::new(),
+        }
+    }
+
+    fn error(&self, exp: &dyn de::Expected) -> MetaError {
+        use MetaValue::*;
+        let unexp = match *self.value {
+            MetaMap(_) => de::Unexpected::Map,
+            MetaList(_) => de::Unexpected::Seq,
+            MetaBool(b) => de::Unexpected::Bool(b),
+            MetaString(ref s) => de::Unexpected::Str(s),
+            MetaInlines(_) => de::Unexpected::Other("inlines"),
+            MetaBlocks(_) => de::Unexpected::Other("blocks"),
+        };
+        <MetaError as de::Error>::invalid_type(unexp, exp).at(&self.path)
+    }
+
+    /// The textual content of string-like values
+    fn text(&self) -> Option<std::borrow::Cow<'de, str>> {
+        use MetaValue::*;
+        match *self.value {
+            MetaString(ref s) => Some(s[..].into()),
+            MetaInlines(_) | MetaBlocks(_) => Some(self.value.stringify().into()),
+            _ => None,
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&self, exp: &dyn de::Expected) -> Result<T, MetaError> {
+        let text = self.text().ok_or_else(|| self.error(exp))?;
+        text.trim().parse().map_err(|_| {
+            <MetaError as de::Error>::invalid_value(de::Unexpected::Str(&text), exp).at(&self.path)
+        })
+    }
+}
+
+macro_rules! deserialize_number {
+    ($($method:ident => $visit:ident: $ty:ty,)*) => {$(
+        fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+            let n: $ty = self.parse(&visitor)?;
+            visitor.$visit::<MetaError>(n).map_err(|e| e.at(&self.path))
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for MetaDeserializer<'de> {
+    type Error = MetaError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        use MetaValue::*;
+        let path = self.path.clone();
+        match *self.value {
+            MetaMap(ref map) => visitor.visit_map(MapDeserializer {
+                iter: map.iter().map(|(k, v)| (&k[..], &**v)),
+                len: map.len(),
+                value: None,
+                path: self.path,
+            }),
+            MetaList(ref list) => visitor.visit_seq(SeqDeserializer {
+                iter: list.iter().enumerate(),
+                len: list.len(),
+                path: self.path,
+            }),
+            MetaBool(b) => visitor.visit_bool(b),
+            MetaString(ref s) => visitor.visit_borrowed_str(s),
+            MetaInlines(_) | MetaBlocks(_) => visitor.visit_string(self.value.stringify()),
+        }
+        .map_err(|e| e.at(&path))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        let b = match self.value.as_bool() {
+            Some(b) => b,
+            None => self.parse(&visitor)?,
+        };
+        visitor
+            .visit_bool::<MetaError>(b)
+            .map_err(|e| e.at(&self.path))
+    }
+
+    deserialize_number! {
+        deserialize_i8 => visit_i8: i8,
+        deserialize_i16 => visit_i16: i16,
+        deserialize_i32 => visit_i32: i32,
+        deserialize_i64 => visit_i64: i64,
+        deserialize_u8 => visit_u8: u8,
+        deserialize_u16 => visit_u16: u16,
+        deserialize_u32 => visit_u32: u32,
+        deserialize_u64 => visit_u64: u64,
+        deserialize_f32 => visit_f32: f32,
+        deserialize_f64 => visit_f64: f64,
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        match self.text() {
+            Some(std::borrow::Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+            Some(std::borrow::Cow::Owned(s)) => visitor.visit_string(s),
+            None => Err(self.error(&visitor)),
+        }
+        .map_err(|e| e.at(&self.path))
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        visitor
+            .visit_unit::<MetaError>()
+            .map_err(|e| e.at(&self.path))
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        match *self.value {
+            MetaValue::MetaList(_) => self.deserialize_any(visitor),
+            _ => Err(self.error(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        match *self.value {
+            MetaValue::MetaMap(_) => self.deserialize_any(visitor),
+            _ => Err(self.error(&visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        self.deserialize_map(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        let path = self.path.clone();
+        match *self.value {
+            MetaValue::MetaMap(ref map) if map.len() == 1 => {
+                let (variant, value) = map.iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer {
+                    variant: variant.clone(),
+                    value: Some(value),
+                    path: child_path(&self.path, variant),
+                })
+            }
+            _ => match self.text() {
+                Some(variant) => visitor.visit_enum(EnumDeserializer {
+                    variant: variant.trim().to_string(),
+                    value: None,
+                    path: self.path,
+                }),
+                None => Err(self.error(&"a string or a map with a single key")),
+            },
+        }
+        .map_err(|e| e.at(&path))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, MetaError> {
+        visitor.visit_unit()
+    }
+}
+
+impl<'de> IntoDeserializer<'de, MetaError> for &'de MetaValue {
+    type Deserializer = MetaDeserializer<'de>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        MetaDeserializer::new(self)
+    }
+}
+
+struct MapDeserializer<'de, I> {
+    iter: I,
+    len: usize,
+    value: Option<(&'de str, &'de MetaValue)>,
+    path: String,
+}
+
+impl<'de, I: Iterator<Item = (&'de str, &'de MetaValue)>> de::MapAccess<'de>
+    for MapDeserializer<'de, I>
+{
+    type Error = MetaError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, MetaError> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some((key, value));
+                let key: de::value::BorrowedStrDeserializer<'de, MetaError> =
+                    de::value::BorrowedStrDeserializer::new(key);
+                seed.deserialize(key).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, MetaError> {
+        let (key, value) = self
+            .value
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        let path = child_path(&self.path, key);
+        seed.deserialize(MetaDeserializer {
+            value,
+            path: path.clone(),
+        })
+        .map_err(|e| e.at(&path))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+struct SeqDeserializer<I> {
+    iter: I,
+    len: usize,
+    path: String,
+}
+
+impl<'de, I: Iterator<Item = (usize, &'de MetaValue)>> de::SeqAccess<'de> for SeqDeserializer<I> {
+    type Error = MetaError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, MetaError> {
+        match self.iter.next() {
+            Some((index, value)) => {
+                let path = child_path(&self.path, &index.to_string());
+                seed.deserialize(MetaDeserializer {
+                    value,
+                    path: path.clone(),
+                })
+                .map(Some)
+                .map_err(|e| e.at(&path))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+struct EnumDeserializer<'de> {
+    variant: String,
+    value: Option<&'de MetaValue>,
+    path: String,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = MetaError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), MetaError> {
+        let variant = seed.deserialize(self.variant.clone().into_deserializer())?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = MetaError;
+
+    fn unit_variant(self) -> Result<(), MetaError> {
+        match self.value {
+            None => Ok(()),
+            Some(value) => de::Deserialize::deserialize(MetaDeserializer {
+                value,
+                path: self.path,
+            }),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, MetaError> {
+        match self.value {
+            Some(value) => seed.deserialize(MetaDeserializer {
+                value,
+                path: self.path,
+            }),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"newtype variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_seq(
+                MetaDeserializer {
+                    value,
+                    path: self.path,
+                },
+                visitor,
+            ),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, MetaError> {
+        match self.value {
+            Some(value) => de::Deserializer::deserialize_map(
+                MetaDeserializer {
+                    value,
+                    path: self.path,
+                },
+                visitor,
+            ),
+            None => Err(de::Error::invalid_type(
+                de::Unexpected::UnitVariant,
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+/// A `serde::Serializer` producing `MetaValue`s
+///
+/// Numbers become `MetaString`s, unit variants their name and other enum
+/// variants a single entry `MetaMap`. `None` values are left out of maps
+/// and turned into empty strings elsewhere. The serializer yields `None`
+/// for values that should be omitted.
+pub struct MetaSerializer;
+
+fn string(s: impl ToString) -> Result<Option<MetaValue>, MetaError> {
+    Ok(Some(MetaValue::MetaString(s.to_string())))
+}
+
+fn or_empty(value: Option<MetaValue>) -> MetaValue {
+    value.unwrap_or_else(|| MetaValue::MetaString(String::new()))
+}
+
+macro_rules! serialize_display {
+    ($($method:ident: $ty:ty,)*) => {$(
+        fn $method(self, v: $ty) -> Result<Option<MetaValue>, MetaError> {
+            string(v)
+        }
+    )*};
+}
+
+impl ser::Serializer for MetaSerializer {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+    type SerializeSeq = SerializeList;
+    type SerializeTuple = SerializeList;
+    type SerializeTupleStruct = SerializeList;
+    type SerializeTupleVariant = SerializeVariant<SerializeList>;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeMap;
+    type SerializeStructVariant = SerializeVariant<SerializeMap>;
+
+    fn serialize_bool(self, v: bool) -> Result<Option<MetaValue>, MetaError> {
+        Ok(Some(MetaValue::MetaBool(v)))
+    }
+
+    serialize_display! {
+        serialize_i8: i8,
+        serialize_i16: i16,
+        serialize_i32: i32,
+        serialize_i64: i64,
+        serialize_u8: u8,
+        serialize_u16: u16,
+        serialize_u32: u32,
+        serialize_u64: u64,
+        serialize_f32: f32,
+        serialize_f64: f64,
+        serialize_char: char,
+        serialize_str: &str,
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Option<MetaValue>, MetaError> {
+        let list = v.iter().map(|b| MetaValue::MetaString(b.to_string()));
+        Ok(Some(MetaValue::MetaList(list.collect())))
+    }
+
+    fn serialize_none(self) -> Result<Option<MetaValue>, MetaError> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(
+        self,
+        value: &T,
+    ) -> Result<Option<MetaValue>, MetaError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<MetaValue>, MetaError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<MetaValue>, MetaError> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Option<MetaValue>, MetaError> {
+        string(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<MetaValue>, MetaError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Option<MetaValue>, MetaError> {
+        let value = or_empty(value.serialize(self).map_err(|e| e.within(variant))?);
+        let mut map = Map::new();
+        map.insert(variant.to_string(), Box::new(value));
+        Ok(Some(MetaValue::MetaMap(map)))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeList, MetaError> {
+        Ok(SerializeList(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeList, MetaError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeList, MetaError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeList>, MetaError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_seq(Some(len))?,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, MetaError> {
+        Ok(SerializeMap {
+            map: Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<SerializeMap, MetaError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeVariant<SerializeMap>, MetaError> {
+        Ok(SerializeVariant {
+            variant,
+            inner: self.serialize_map(Some(len))?,
+        })
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeList(Vec<MetaValue>);
+
+impl SerializeList {
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        let index = self.0.len().to_string();
+        let value = value
+            .serialize(MetaSerializer)
+            .map_err(|e| e.within(&index))?;
+        self.0.push(or_empty(value));
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for SerializeList {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        Ok(Some(MetaValue::MetaList(self.0)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeList {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeList {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeMap {
+    map: Map<String, Box<MetaValue>>,
+    key: Option<String>,
+}
+
+impl SerializeMap {
+    fn insert<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), MetaError> {
+        if let Some(value) = value
+            .serialize(MetaSerializer)
+            .map_err(|e| e.within(&key))?
+        {
+            self.map.insert(key, Box::new(value));
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), MetaError> {
+        match key.serialize(MetaSerializer)? {
+            Some(MetaValue::MetaString(key)) => {
+                self.key = Some(key);
+                Ok(())
+            }
+            _ => Err(de::Error::custom("metadata map keys must be strings")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.insert(key, value)
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        Ok(Some(MetaValue::MetaMap(self.map)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), MetaError> {
+        self.insert(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+#[doc(hidden)]
+pub struct SerializeVariant<T> {
+    variant: &'static str,
+    inner: T,
+}
+
+impl<T> SerializeVariant<T> {
+    fn wrap(variant: &str, value: Option<MetaValue>) -> Result<Option<MetaValue>, MetaError> {
+        let mut map = Map::new();
+        map.insert(variant.to_string(), Box::new(or_empty(value)));
+        Ok(Some(MetaValue::MetaMap(map)))
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeVariant<SerializeList> {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), MetaError> {
+        let variant = self.variant;
+        self.inner.push(value).map_err(|e| e.within(variant))
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        Self::wrap(self.variant, ser::SerializeSeq::end(self.inner)?)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeVariant<SerializeMap> {
+    type Ok = Option<MetaValue>;
+    type Error = MetaError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), MetaError> {
+        let variant = self.variant;
+        self.inner
+            .insert(key.to_string(), value)
+            .map_err(|e| e.within(variant))
+    }
+
+    fn end(self) -> Result<Option<MetaValue>, MetaError> {
+        Self::wrap(self.variant, ser::SerializeMap::end(self.inner)?)
+    }
+}
@@ -0,0 +1,1219 @@
+use super::*;
+use std::fmt;
+
+/// An error parsing pandoc's native format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeError {
+    position: Option<(usize, usize)>,
+    message: String,
+}
+
+impl NativeError {
+    /// Line and column (both starting at 1) of the offending token, or of
+    /// the value that doesn't fit where it is
+    pub fn position(&self) -> Option<(usize, usize)> {
+        self.position
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for NativeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.position {
+            Some((line, column)) => write!(f, "{}:{}: {}", line, column, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for NativeError {}
+
+/// A parsed value that doesn't make up a document, located by
+/// `Parser::error_at` once conversion failed
+struct Unexpected {
+    offset: usize,
+    message: String,
+}
+
+fn unexpected(what: &str, value: &Value) -> Unexpected {
+    let mut found = value.flat();
+    if found.chars().count() > 40 {
+        found = found.chars().take(37).collect::<String>() + "...";
+    }
+    Unexpected {
+        offset: value.offset,
+        message: format!("expected {}, found `{}`", what, found),
+    }
+}
+
+/// A value as printed by Haskell's `show`
+#[derive(Debug, Clone, PartialEq)]
+struct Value {
+    kind: Kind,
+    /// Where the value starts in the parsed text, 0 for printed values
+    offset: usize,
+}
+
+impl From<Kind> for Value {
+    fn from(kind: Kind) -> Self {
+        Value { kind, offset: 0 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Kind {
+    /// Constructor application
+    Con(String, Vec<Value>),
+    /// Constructor with record syntax
+    Rec(String, Vec<(String, Value)>),
+    List(Vec<Value>),
+    Tuple(Vec<Value>),
+    Str(String),
+    /// A number, as written
+    Num(String),
+}
+
+/// Quotes and escapes a string like Haskell's `show`
+fn show_string(text: &str) -> String {
+    const ASCII: [&str; 32] = [
+        "NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "a", "b", "t", "n", "v", "f", "r", "SO",
+        "SI", "DLE", "DC1", "DC2", "DC3", "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC",
+        "FS", "GS", "RS", "US",
+    ];
+    let mut out = String::from("\"");
+    // the escape just written, which some following characters would extend
+    let mut numeric = false;
+    let mut shift_out = false;
+    for c in text.chars() {
+        if (numeric && c.is_ascii_digit()) || (shift_out && c == 'H') {
+            out.push_str("\\&");
+        }
+        numeric = false;
+        shift_out = false;
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\u{7f}' => out.push_str("\\DEL"),
+            c if (c as u32) < 32 => {
+                out.push('\\');
+                out.push_str(ASCII[c as usize]);
+                shift_out = c == '\u{e}';
+            }
+            c if (c as u32) > 127 => {
+                out.push_str(&format!("\\{}", c as u32));
+                numeric = true;
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats a floating point number like Haskell's `show`
+fn show_double(x: f64) -> String {
+    if x.is_nan() {
+        return "NaN".to_string();
+    }
+    if x.is_infinite() {
+        return if x > 0.0 { "Infinity" } else { "-Infinity" }.to_string();
+    }
+    if x == 0.0 || (0.1..1e7).contains(&x.abs()) {
+        return format!("{:?}", x);
+    }
+    let scientific = format!("{:e}", x);
+    match scientific.split_once('e') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{}.0e{}", mantissa, exponent)
+        }
+        _ => scientific,
+    }
+}
+
+fn block_flat(open: char, close: char, items: &[String]) -> String {
+    if items.is_empty() {
+        format!("{}{}", open, close)
+    } else {
+        format!("{} {} {}", open, items.join(" , "), close)
+    }
+}
+
+impl Value {
+    /// The value on a single line
+    fn flat(&self) -> String {
+        match self.kind {
+            Kind::Con(ref name, ref args) => {
+                let mut flat = name.clone();
+                for arg in args {
+                    flat.push(' ');
+                    flat.push_str(&arg.flat_atom());
+                }
+                flat
+            }
+            Kind::Rec(ref name, ref fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(field, value)| format!("{} = {}", field, value.flat()))
+                    .collect();
+                format!("{} {}", name, block_flat('{', '}', &fields))
+            }
+            Kind::List(ref items) => {
+                block_flat('[', ']', &items.iter().map(Value::flat).collect::<Vec<_>>())
+            }
+            Kind::Tuple(ref items) => {
+                block_flat('(', ')', &items.iter().map(Value::flat).collect::<Vec<_>>())
+            }
+            Kind::Str(ref text) => show_string(text),
+            Kind::Num(ref number) => number.clone(),
+        }
+    }
+
+    /// Whether the value needs parentheses as a constructor argument
+    fn is_compound(&self) -> bool {
+        match self.kind {
+            Kind::Con(_, ref args) => !args.is_empty(),
+            Kind::Num(ref number) => number.starts_with('-'),
+            _ => false,
+        }
+    }
+
+    fn flat_atom(&self) -> String {
+        if self.is_compound() {
+            format!("({})", self.flat())
+        } else {
+            self.flat()
+        }
+    }
+}
+
+/// Lays out values like the `pretty-show` package pandoc's native writer
+/// uses: a value goes on one line if it fits, otherwise constructor
+/// arguments, list elements and record fields get a line each.
+struct Printer {
+    out: String,
+    column: usize,
+    /// Indentation of the current line
+    indent: usize,
+}
+
+impl Printer {
+    /// The default style of the `pretty` package
+    const LINE_LENGTH: usize = 100;
+    const RIBBON_LENGTH: usize = 67;
+
+    fn text(&mut self, text: &str) {
+        self.out.push_str(text);
+        self.column += text.chars().count();
+    }
+
+    fn newline(&mut self, indent: usize) {
+        self.out.push('\n');
+        self.out.extend(std::iter::repeat_n(' ', indent));
+        self.column = indent;
+        self.indent = indent;
+    }
+
+    fn fits(&self, len: usize) -> bool {
+        self.column + len <= Self::LINE_LENGTH
+            && self.column - self.indent + len <= Self::RIBBON_LENGTH
+    }
+
+    /// Prints a value followed by `trail` closing characters on its last line
+    fn value(&mut self, value: &Value, trail: usize) {
+        let flat = value.flat();
+        let base = self.column;
+        match value.kind {
+            Kind::List(ref items) => self.block('[', ']', items, Printer::value),
+            Kind::Tuple(ref items) => self.block('(', ')', items, Printer::value),
+            Kind::Con(ref name, ref args) if !args.is_empty() => {
+                if self.fits(flat.len() + trail) {
+                    return self.text(&flat);
+                }
+                self.text(name);
+                self.newline(base + 2);
+                let args_flat: Vec<String> = args.iter().map(Value::flat_atom).collect();
+                let args_flat = args_flat.join(" ");
+                if self.fits(args_flat.len() + trail) {
+                    return self.text(&args_flat);
+                }
+                for (i, arg) in args.iter().enumerate() {
+                    if i > 0 {
+                        self.newline(base + 2);
+                    }
+                    let trail = if i + 1 == args.len() { trail } else { 0 };
+                    self.atom(arg, trail);
+                }
+            }
+            Kind::Rec(ref name, ref fields) => {
+                if self.fits(flat.len() + trail) {
+                    return self.text(&flat);
+                }
+                self.text(name);
+                self.newline(base + 2);
+                self.block('{', '}', fields, |printer, (field, value), _| {
+                    let flat = format!("{} = {}", field, value.flat());
+                    if printer.fits(flat.len()) {
+                        return printer.text(&flat);
+                    }
+                    let base = printer.column;
+                    printer.text(field);
+                    printer.text(" =");
+                    printer.newline(base + 2);
+                    printer.value(value, 0);
+                });
+            }
+            _ => self.text(&flat),
+        }
+    }
+
+    fn atom(&mut self, value: &Value, trail: usize) {
+        if value.is_compound() {
+            self.text("(");
+            self.value(value, trail + 1);
+            self.text(")");
+        } else {
+            self.value(value, trail);
+        }
+    }
+
+    /// Prints `[ a , b ]` or, if that doesn't fit, each item on a line of
+    /// its own
+    fn block<T>(&mut self, open: char, close: char, items: &[T], item: fn(&mut Self, &T, usize))
+    where
+        T: Flat,
+    {
+        let flat: Vec<String> = items.iter().map(Flat::flat).collect();
+        let flat = block_flat(open, close, &flat);
+        if items.is_empty() || self.fits(flat.len()) {
+            return self.text(&flat);
+        }
+        let base = self.column;
+        for (i, value) in items.iter().enumerate() {
+            if i > 0 {
+                self.newline(base);
+                self.text(", ");
+            } else {
+                self.text(&format!("{} ", open));
+            }
+            item(self, value, 0);
+        }
+        self.newline(base);
+        self.text(&close.to_string());
+    }
+}
+
+/// Items of a block: values and record fields
+trait Flat {
+    fn flat(&self) -> String;
+}
+
+impl Flat for Value {
+    fn flat(&self) -> String {
+        Value::flat(self)
+    }
+}
+
+impl Flat for (String, Value) {
+    fn flat(&self) -> String {
+        format!("{} = {}", self.0, self.1.flat())
+    }
+}
+
+// Conversion of documents to values
+
+fn con(name: &str, args: Vec<Value>) -> Value {
+    Kind::Con(name.to_string(), args).into()
+}
+
+fn string(text: &str) -> Value {
+    Kind::Str(text.to_string()).into()
+}
+
+fn int(n: Int) -> Value {
+    Kind::Num(n.to_string()).into()
+}
+
+fn tuple(items: Vec<Value>) -> Value {
+    Kind::Tuple(items).into()
+}
+
+fn list<T>(items: &[T], f: impl Fn(&T) -> Value) -> Value {
+    Kind::List(items.iter().map(f).collect()).into()
+}
+
+/// The name of a variant without fields, as serialized
+fn unit<T: serde::Serialize>(value: &T) -> Value {
+    let json = serde_json::to_value(value).expect("serialization failed");
+    con(json["t"].as_str().unwrap_or_default(), vec![])
+}
+
+fn attr_value(attr: &Attr) -> Value {
+    tuple(vec![
+        string(&attr.0),
+        list(&attr.1, |class| string(class)),
+        list(&attr.2, |(k, v)| tuple(vec![string(k), string(v)])),
+    ])
+}
+
+fn target_value(target: &Target) -> Value {
+    tuple(vec![string(&target.0), string(&target.1)])
+}
+
+fn format_value(format: &Format) -> Value {
+    con("Format", vec![string(&format.0)])
+}
+
+fn inlines_value(inlines: &[Inline]) -> Value {
+    list(inlines, inline_value)
+}
+
+fn blocks_value(blocks: &[Block]) -> Value {
+    list(blocks, block_value)
+}
+
+fn caption_value(caption: &Caption) -> Value {
+    let short = match caption.0 {
+        Some(ref short) => con("Just", vec![inlines_value(short)]),
+        None => con("Nothing", vec![]),
+    };
+    con("Caption", vec![short, blocks_value(&caption.1)])
+}
+
+fn rows_value(rows: &[Row]) -> Value {
+    list(rows, |(attr, cells)| {
+        let cells = list(cells, |cell| {
+            con(
+                "Cell",
+                vec![
+                    attr_value(&cell.0),
+                    unit(&cell.1),
+                    con("RowSpan", vec![int(cell.2)]),
+                    con("ColSpan", vec![int(cell.3)]),
+                    blocks_value(&cell.4),
+                ],
+            )
+        });
+        con("Row", vec![attr_value(attr), cells])
+    })
+}
+
+fn block_value(block: &Block) -> Value {
+    match *block {
+        Block::Plain(ref inlines) => con("Plain", vec![inlines_value(inlines)]),
+        Block::Para(ref inlines) => con("Para", vec![inlines_value(inlines)]),
+        Block::LineBlock(ref lines) => {
+            con("LineBlock", vec![list(lines, |line| inlines_value(line))])
+        }
+        Block::CodeBlock(ref attr, ref code) => {
+            con("CodeBlock", vec![attr_value(attr), string(code)])
+        }
+        Block::RawBlock(ref format, ref text) => {
+            con("RawBlock", vec![format_value(format), string(text)])
+        }
+        Block::BlockQuote(ref blocks) => con("BlockQuote", vec![blocks_value(blocks)]),
+        Block::OrderedList((start, style, delim), ref items) => {
+            let attrs = tuple(vec![int(start), unit(&style), unit(&delim)]);
+            let items = list(items, |item| blocks_value(item));
+            con("OrderedList", vec![attrs, items])
+        }
+        Block::BulletList(ref items) => {
+            con("BulletList", vec![list(items, |item| blocks_value(item))])
+        }
+        Block::DefinitionList(ref items) => {
+            let items = list(items, |(term, definitions)| {
+                let definitions = list(definitions, |definition| blocks_value(definition));
+                tuple(vec![inlines_value(term), definitions])
+            });
+            con("DefinitionList", vec![items])
+        }
+        Block::Figure(ref attr, ref caption, ref blocks) => con(
+            "Figure",
+            vec![
+                attr_value(attr),
+                caption_value(caption),
+                blocks_value(blocks),
+            ],
+        ),
+        Block::Header(level, ref attr, ref inlines) => con(
+            "Header",
+            vec![int(level), attr_value(attr), inlines_value(inlines)],
+        ),
+        Block::HorizontalRule => con("HorizontalRule", vec![]),
+        Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+            let colspecs = list(colspecs, |(align, width)| {
+                let width = match *width {
+                    ColWidth::ColWidth(width) => {
+                        con("ColWidth", vec![Kind::Num(show_double(width)).into()])
+                    }
+                    ColWidth::ColWidthDefault => con("ColWidthDefault", vec![]),
+                };
+                tuple(vec![unit(align), width])
+            });
+            let head = con("TableHead", vec![attr_value(&head.0), rows_value(&head.1)]);
+            let bodies = list(bodies, |body| {
+                con(
+                    "TableBody",
+                    vec![
+                        attr_value(&body.0),
+                        con("RowHeadColumns", vec![int(body.1)]),
+                        rows_value(&body.2),
+                        rows_value(&body.3),
+                    ],
+                )
+            });
+            let foot = con("TableFoot", vec![attr_value(&foot.0), rows_value(&foot.1)]);
+            con(
+                "Table",
+                vec![
+                    attr_value(attr),
+                    caption_value(caption),
+                    colspecs,
+                    head,
+                    bodies,
+                    foot,
+                ],
+            )
+        }
+        Block::Div(ref attr, ref blocks) => {
+            con("Div", vec![attr_value(attr), blocks_value(blocks)])
+        }
+        Block::Null => con("Null", vec![]),
+    }
+}
+
+fn inline_value(inline: &Inline) -> Value {
+    let wrap = |name: &str, inlines: &[Inline]| con(name, vec![inlines_value(inlines)]);
+    match *inline {
+        Inline::Str(ref text) => con("Str", vec![string(text)]),
+        Inline::Emph(ref inlines) => wrap("Emph", inlines),
+        Inline::Underline(ref inlines) => wrap("Underline", inlines),
+        Inline::Strong(ref inlines) => wrap("Strong", inlines),
+        Inline::Strikeout(ref inlines) => wrap("Strikeout", inlines),
+        Inline::Superscript(ref inlines) => wrap("Superscript", inlines),
+        Inline::Subscript(ref inlines) => wrap("Subscript", inlines),
+        Inline::SmallCaps(ref inlines) => wrap("SmallCaps", inlines),
+        Inline::Quoted(quote, ref inlines) => {
+            con("Quoted", vec![unit(&quote), inlines_value(inlines)])
+        }
+        Inline::Cite(ref citations, ref inlines) => {
+            let citations = list(citations, |c| {
+                let fields = vec![
+                    ("citationId", string(&c.citationId)),
+                    ("citationPrefix", inlines_value(&c.citationPrefix)),
+                    ("citationSuffix", inlines_value(&c.citationSuffix)),
+                    ("citationMode", unit(&c.citationMode)),
+                    ("citationNoteNum", int(c.citationNoteNum)),
+                    ("citationHash", int(c.citationHash)),
+                ];
+                let fields = fields
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value))
+                    .collect();
+                Kind::Rec("Citation".to_string(), fields).into()
+            });
+            con("Cite", vec![citations, inlines_value(inlines)])
+        }
+        Inline::Code(ref attr, ref code) => con("Code", vec![attr_value(attr), string(code)]),
+        Inline::Space => con("Space", vec![]),
+        Inline::SoftBreak => con("SoftBreak", vec![]),
+        Inline::LineBreak => con("LineBreak", vec![]),
+        Inline::Math(kind, ref tex) => con("Math", vec![unit(&kind), string(tex)]),
+        Inline::RawInline(ref format, ref text) => {
+            con("RawInline", vec![format_value(format), string(text)])
+        }
+        Inline::Link(ref attr, ref inlines, ref target) => con(
+            "Link",
+            vec![
+                attr_value(attr),
+                inlines_value(inlines),
+                target_value(target),
+            ],
+        ),
+        Inline::Image(ref attr, ref inlines, ref target) => con(
+            "Image",
+            vec![
+                attr_value(attr),
+                inlines_value(inlines),
+                target_value(target),
+            ],
+        ),
+        Inline::Note(ref blocks) => con("Note", vec![blocks_value(blocks)]),
+        Inline::Span(ref attr, ref inlines) => {
+            con("Span", vec![attr_value(attr), inlines_value(inlines)])
+        }
+    }
+}
+
+fn meta_map_value<'a>(entries: impl Iterator<Item = (&'a String, &'a MetaValue)>) -> Value {
+    let entries = entries
+        .map(|(key, value)| tuple(vec![string(key), meta_value(value)]))
+        .collect();
+    con("fromList", vec![Kind::List(entries).into()])
+}
+
+fn meta_value(value: &MetaValue) -> Value {
+    match *value {
+        MetaValue::MetaMap(ref map) => con(
+            "MetaMap",
+            vec![meta_map_value(map.iter().map(|(k, v)| (k, &**v)))],
+        ),
+        MetaValue::MetaList(ref items) => con("MetaList", vec![list(items, meta_value)]),
+        MetaValue::MetaBool(b) => con(
+            "MetaBool",
+            vec![con(if b { "True" } else { "False" }, vec![])],
+        ),
+        MetaValue::MetaString(ref text) => con("MetaString", vec![string(text)]),
+        MetaValue::MetaInlines(ref inlines) => con("MetaInlines", vec![inlines_value(inlines)]),
+        MetaValue::MetaBlocks(ref blocks) => con("MetaBlocks", vec![blocks_value(blocks)]),
+    }
+}
+
+// Parsing
+
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: impl Into<String>) -> NativeError {
+        self.error_at(self.pos, message)
+    }
+
+    /// An error at the byte `offset` of the text
+    fn error_at(&self, offset: usize, message: impl Into<String>) -> NativeError {
+        let before = &self.text[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before.rsplit('\n').next().unwrap_or("").chars().count() + 1;
+        NativeError {
+            position: Some((line, column)),
+            message: message.into(),
+        }
+    }
+
+    fn skip_space(&mut self) {
+        let rest = &self.text[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn peek(&mut self) -> Option<char> {
+        self.skip_space();
+        self.text[self.pos..].chars().next()
+    }
+
+    fn expect(&mut self, c: char) -> Result<(), NativeError> {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", c)))
+        }
+    }
+
+    fn starts_atom(&mut self) -> bool {
+        matches!(self.peek(), Some(c) if "([\"-".contains(c) || c.is_alphanumeric())
+    }
+
+    fn ident(&mut self) -> String {
+        let rest = &self.text[self.pos..];
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '\'' || c == '.'))
+            .unwrap_or(rest.len());
+        self.pos += len;
+        rest[..len].to_string()
+    }
+
+    /// A constructor application or an atom
+    fn value(&mut self) -> Result<Value, NativeError> {
+        if self.peek().is_some_and(char::is_alphabetic) {
+            let offset = self.pos;
+            let name = self.ident();
+            if self.peek() == Some('{') {
+                return self.record(offset, name);
+            }
+            let mut args = Vec::new();
+            while self.starts_atom() {
+                args.push(self.atom()?);
+            }
+            let kind = Kind::Con(name, args);
+            return Ok(Value { kind, offset });
+        }
+        self.atom()
+    }
+
+    fn atom(&mut self) -> Result<Value, NativeError> {
+        let next = self.peek();
+        let offset = self.pos;
+        let kind = match next {
+            Some('(') => {
+                let items = self.items(')')?;
+                match <[Value; 1]>::try_from(items) {
+                    Ok([value]) => return Ok(value),
+                    Err(items) => Kind::Tuple(items),
+                }
+            }
+            Some('[') => Kind::List(self.items(']')?),
+            Some('"') => Kind::Str(self.string()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => Kind::Num(self.number()?),
+            Some(c) if c.is_alphabetic() => {
+                let name = self.ident();
+                if self.peek() == Some('{') {
+                    return self.record(offset, name);
+                }
+                Kind::Con(name, vec![])
+            }
+            Some(c) => return Err(self.error(format!("unexpected `{}`", c))),
+            None => return Err(self.error("unexpected end of input")),
+        };
+        Ok(Value { kind, offset })
+    }
+
+    /// Comma separated values after an opening bracket, up to `close`
+    fn items(&mut self, close: char) -> Result<Vec<Value>, NativeError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.peek() == Some(close) {
+            self.pos += 1;
+            return Ok(items);
+        }
+        loop {
+            items.push(self.value()?);
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some(c) if c == close => {
+                    self.pos += 1;
+                    return Ok(items);
+                }
+                _ => return Err(self.error(format!("expected `,` or `{}`", close))),
+            }
+        }
+    }
+
+    fn record(&mut self, offset: usize, name: String) -> Result<Value, NativeError> {
+        self.expect('{')?;
+        let mut fields = Vec::new();
+        loop {
+            if !self.peek().is_some_and(char::is_alphabetic) {
+                return Err(self.error("expected a field name"));
+            }
+            let field = self.ident();
+            self.expect('=')?;
+            fields.push((field, self.value()?));
+            match self.peek() {
+                Some(',') => self.pos += 1,
+                Some('}') => {
+                    self.pos += 1;
+                    let kind = Kind::Rec(name, fields);
+                    return Ok(Value { kind, offset });
+                }
+                _ => return Err(self.error("expected `,` or `}`")),
+            }
+        }
+    }
+
+    fn number(&mut self) -> Result<String, NativeError> {
+        let rest = &self.text[self.pos..];
+        let mut len = usize::from(rest.starts_with('-'));
+        for c in rest[len..].chars() {
+            let exponent_sign = (c == '-' || c == '+') && rest[..len].ends_with(['e', 'E']);
+            if !(c.is_ascii_digit() || c == '.' || c == 'e' || c == 'E' || exponent_sign) {
+                break;
+            }
+            len += 1;
+        }
+        let number = &rest[..len];
+        if number.parse::<f64>().is_err() {
+            return Err(self.error(format!("invalid number `{}`", number)));
+        }
+        self.pos += len;
+        Ok(number.to_string())
+    }
+
+    /// A string literal with Haskell escapes
+    fn string(&mut self) -> Result<String, NativeError> {
+        const ASCII: [(&str, char); 34] = [
+            ("NUL", '\0'),
+            ("SOH", '\u{1}'),
+            ("STX", '\u{2}'),
+            ("ETX", '\u{3}'),
+            ("EOT", '\u{4}'),
+            ("ENQ", '\u{5}'),
+            ("ACK", '\u{6}'),
+            ("BEL", '\u{7}'),
+            ("BS", '\u{8}'),
+            ("HT", '\t'),
+            ("LF", '\n'),
+            ("VT", '\u{b}'),
+            ("FF", '\u{c}'),
+            ("CR", '\r'),
+            ("SO", '\u{e}'),
+            ("SI", '\u{f}'),
+            ("DLE", '\u{10}'),
+            ("DC1", '\u{11}'),
+            ("DC2", '\u{12}'),
+            ("DC3", '\u{13}'),
+            ("DC4", '\u{14}'),
+            ("NAK", '\u{15}'),
+            ("SYN", '\u{16}'),
+            ("ETB", '\u{17}'),
+            ("CAN", '\u{18}'),
+            ("EM", '\u{19}'),
+            ("SUB", '\u{1a}'),
+            ("ESC", '\u{1b}'),
+            ("FS", '\u{1c}'),
+            ("GS", '\u{1d}'),
+            ("RS", '\u{1e}'),
+            ("US", '\u{1f}'),
+            ("SP", ' '),
+            ("DEL", '\u{7f}'),
+        ];
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            let rest = &self.text[self.pos..];
+            let Some(c) = rest.chars().next() else {
+                return Err(self.error("unterminated string"));
+            };
+            self.pos += c.len_utf8();
+            match c {
+                '"' => return Ok(out),
+                '\\' => {}
+                c => {
+                    out.push(c);
+                    continue;
+                }
+            }
+            let rest = &self.text[self.pos..];
+            let digits = |radix: u32| {
+                let skip = usize::from(radix != 10);
+                let len = rest[skip..]
+                    .find(|c: char| !c.is_digit(radix))
+                    .unwrap_or(rest.len() - skip);
+                (
+                    skip + len,
+                    u32::from_str_radix(&rest[skip..skip + len], radix).ok(),
+                )
+            };
+            let (len, escaped) = match rest.chars().next() {
+                Some('&') => (1, None),
+                Some(c) if c.is_whitespace() => match rest.find('\\') {
+                    // a string gap
+                    Some(end) => (end + 1, None),
+                    None => return Err(self.error("unterminated string gap")),
+                },
+                Some(c @ ('"' | '\\' | '\'')) => (1, Some(c)),
+                Some('a') => (1, Some('\u{7}')),
+                Some('b') => (1, Some('\u{8}')),
+                Some('f') => (1, Some('\u{c}')),
+                Some('n') => (1, Some('\n')),
+                Some('r') => (1, Some('\r')),
+                Some('t') => (1, Some('\t')),
+                Some('v') => (1, Some('\u{b}')),
+                Some('^') => match rest[1..].chars().next() {
+                    Some(c @ '@'..='_') => (2, char::from_u32(c as u32 - 64)),
+                    _ => return Err(self.error("invalid control escape")),
+                },
+                Some(c) if c.is_ascii_digit() || c == 'x' || c == 'o' => {
+                    let radix = match c {
+                        'x' => 16,
+                        'o' => 8,
+                        _ => 10,
+                    };
+                    match digits(radix) {
+                        (len, Some(code)) => match char::from_u32(code) {
+                            Some(c) => (len, Some(c)),
+                            None => return Err(self.error("invalid character code")),
+                        },
+                        _ => return Err(self.error("invalid numeric escape")),
+                    }
+                }
+                _ => {
+                    // the longest matching name, `SOH` rather than `SO`
+                    let found = ASCII
+                        .iter()
+                        .filter(|(name, _)| rest.starts_with(name))
+                        .max_by_key(|(name, _)| name.len());
+                    match found {
+                        Some(&(name, c)) => (name.len(), Some(c)),
+                        None => return Err(self.error("invalid escape")),
+                    }
+                }
+            };
+            self.pos += len;
+            out.extend(escaped);
+        }
+    }
+}
+
+// Conversion of values to documents
+
+type Parsed<T> = Result<T, Unexpected>;
+
+fn con_args<'a>(value: &'a Value, what: &str) -> Parsed<(&'a str, &'a [Value])> {
+    match value.kind {
+        Kind::Con(ref name, ref args) => Ok((name, args)),
+        _ => Err(unexpected(what, value)),
+    }
+}
+
+fn to_list<T>(value: &Value, f: impl Fn(&Value) -> Parsed<T>) -> Parsed<Vec<T>> {
+    match value.kind {
+        Kind::List(ref items) => items.iter().map(f).collect(),
+        _ => Err(unexpected("a list", value)),
+    }
+}
+
+fn to_string(value: &Value) -> Parsed<String> {
+    match value.kind {
+        Kind::Str(ref text) => Ok(text.clone()),
+        _ => Err(unexpected("a string", value)),
+    }
+}
+
+fn to_int(value: &Value) -> Parsed<Int> {
+    match value.kind {
+        Kind::Num(ref n) => n.parse().map_err(|_| unexpected("an integer", value)),
+        _ => Err(unexpected("an integer", value)),
+    }
+}
+
+/// The argument of a newtype like `RowSpan 1`
+fn to_newtype<'a>(value: &'a Value, name: &str) -> Parsed<&'a Value> {
+    match con_args(value, name)? {
+        (found, [arg]) if found == name => Ok(arg),
+        _ => Err(unexpected(name, value)),
+    }
+}
+
+/// A variant without fields, by its serialized name
+fn to_unit<T: serde::de::DeserializeOwned>(value: &Value, what: &str) -> Parsed<T> {
+    match value.kind {
+        Kind::Con(ref name, ref args) if args.is_empty() => {
+            serde_json::from_value(serde_json::json!({ "t": name }))
+                .map_err(|_| unexpected(what, value))
+        }
+        _ => Err(unexpected(what, value)),
+    }
+}
+
+fn to_pair(value: &Value) -> Parsed<(&Value, &Value)> {
+    match value.kind {
+        Kind::Tuple(ref items) if items.len() == 2 => Ok((&items[0], &items[1])),
+        _ => Err(unexpected("a pair", value)),
+    }
+}
+
+fn to_attr(value: &Value) -> Parsed<Attr> {
+    match value.kind {
+        Kind::Tuple(ref items) if items.len() == 3 => Ok((
+            to_string(&items[0])?,
+            to_list(&items[1], to_string)?,
+            to_list(&items[2], |kv| {
+                let (k, v) = to_pair(kv)?;
+                Ok((to_string(k)?, to_string(v)?))
+            })?,
+        )),
+        _ => Err(unexpected("attributes", value)),
+    }
+}
+
+fn to_target(value: &Value) -> Parsed<Target> {
+    let (url, title) = to_pair(value)?;
+    Ok((to_string(url)?, to_string(title)?))
+}
+
+fn to_format(value: &Value) -> Parsed<Format> {
+    Ok(Format(to_string(to_newtype(value, "Format")?)?))
+}
+
+fn to_inlines(value: &Value) -> Parsed<Vec<Inline>> {
+    to_list(value, to_inline)
+}
+
+fn to_blocks(value: &Value) -> Parsed<Vec<Block>> {
+    to_list(value, to_block)
+}
+
+fn to_caption(value: &Value) -> Parsed<Caption> {
+    match con_args(value, "a caption")? {
+        ("Caption", [short, blocks]) => {
+            let short = match con_args(short, "a short caption")? {
+                ("Nothing", []) => None,
+                ("Just", [inlines]) => Some(to_inlines(inlines)?),
+                _ => return Err(unexpected("a short caption", short)),
+            };
+            Ok((short, to_blocks(blocks)?))
+        }
+        _ => Err(unexpected("a caption", value)),
+    }
+}
+
+fn to_rows(value: &Value) -> Parsed<Vec<Row>> {
+    to_list(value, |row| match con_args(row, "a row")? {
+        ("Row", [attr, cells]) => {
+            let cells = to_list(cells, |cell| match con_args(cell, "a cell")? {
+                ("Cell", [attr, align, rowspan, colspan, blocks]) => Ok((
+                    to_attr(attr)?,
+                    to_unit(align, "an alignment")?,
+                    to_int(to_newtype(rowspan, "RowSpan")?)?,
+                    to_int(to_newtype(colspan, "ColSpan")?)?,
+                    to_blocks(blocks)?,
+                )),
+                _ => Err(unexpected("a cell", cell)),
+            })?;
+            Ok((to_attr(attr)?, cells))
+        }
+        _ => Err(unexpected("a row", row)),
+    })
+}
+
+fn to_block(value: &Value) -> Parsed<Block> {
+    let (name, args) = con_args(value, "a block")?;
+    Ok(match (name, args) {
+        ("Plain", [inlines]) => Block::Plain(to_inlines(inlines)?),
+        ("Para", [inlines]) => Block::Para(to_inlines(inlines)?),
+        ("LineBlock", [lines]) => Block::LineBlock(to_list(lines, to_inlines)?),
+        ("CodeBlock", [attr, code]) => Block::CodeBlock(to_attr(attr)?, to_string(code)?),
+        ("RawBlock", [format, text]) => Block::RawBlock(to_format(format)?, to_string(text)?),
+        ("BlockQuote", [blocks]) => Block::BlockQuote(to_blocks(blocks)?),
+        (
+            "OrderedList",
+            [Value {
+                kind: Kind::Tuple(ref attrs),
+                ..
+            }, items],
+        ) if attrs.len() == 3 => {
+            let attrs = (
+                to_int(&attrs[0])?,
+                to_unit(&attrs[1], "a list number style")?,
+                to_unit(&attrs[2], "a list number delimiter")?,
+            );
+            Block::OrderedList(attrs, to_list(items, to_blocks)?)
+        }
+        ("BulletList", [items]) => Block::BulletList(to_list(items, to_blocks)?),
+        ("DefinitionList", [items]) => Block::DefinitionList(to_list(items, |item| {
+            let (term, definitions) = to_pair(item)?;
+            Ok((to_inlines(term)?, to_list(definitions, to_blocks)?))
+        })?),
+        ("Figure", [attr, caption, blocks]) => {
+            Block::Figure(to_attr(attr)?, to_caption(caption)?, to_blocks(blocks)?)
+        }
+        ("Header", [level, attr, inlines]) => {
+            Block::Header(to_int(level)?, to_attr(attr)?, to_inlines(inlines)?)
+        }
+        ("HorizontalRule", []) => Block::HorizontalRule,
+        ("Table", [attr, caption, colspecs, head, bodies, foot]) => {
+            let colspecs = to_list(colspecs, |spec| {
+                let (align, width) = to_pair(spec)?;
+                let width = match con_args(width, "a column width")? {
+                    (
+                        "ColWidth",
+                        [Value {
+                            kind: Kind::Num(ref n),
+                            ..
+                        }],
+                    ) => ColWidth::ColWidth(
+                        n.parse().map_err(|_| unexpected("a column width", width))?,
+                    ),
+                    ("ColWidthDefault", []) => ColWidth::ColWidthDefault,
+                    _ => return Err(unexpected("a column width", width)),
+                };
+                Ok((to_unit(align, "an alignment")?, width))
+            })?;
+            let head = match con_args(head, "a table head")? {
+                ("TableHead", [attr, rows]) => (to_attr(attr)?, to_rows(rows)?),
+                _ => return Err(unexpected("a table head", head)),
+            };
+            let bodies = to_list(bodies, |body| match con_args(body, "a table body")? {
+                ("TableBody", [attr, columns, head, rows]) => Ok((
+                    to_attr(attr)?,
+                    to_int(to_newtype(columns, "RowHeadColumns")?)?,
+                    to_rows(head)?,
+                    to_rows(rows)?,
+                )),
+                _ => Err(unexpected("a table body", body)),
+            })?;
+            let foot = match con_args(foot, "a table foot")? {
+                ("TableFoot", [attr, rows]) => (to_attr(attr)?, to_rows(rows)?),
+                _ => return Err(unexpected("a table foot", foot)),
+            };
+            Block::Table(
+                to_attr(attr)?,
+                to_caption(caption)?,
+                colspecs,
+                head,
+                bodies,
+                foot,
+            )
+        }
+        ("Div", [attr, blocks]) => Block::Div(to_attr(attr)?, to_blocks(blocks)?),
+        ("Null", []) => Block::Null,
+        _ => return Err(unexpected("a block", value)),
+    })
+}
+
+fn to_inline(value: &Value) -> Parsed<Inline> {
+    let (name, args) = con_args(value, "an inline")?;
+    Ok(match (name, args) {
+        ("Str", [text]) => Inline::Str(to_string(text)?),
+        ("Emph", [inlines]) => Inline::Emph(to_inlines(inlines)?),
+        ("Underline", [inlines]) => Inline::Underline(to_inlines(inlines)?),
+        ("Strong", [inlines]) => Inline::Strong(to_inlines(inlines)?),
+        ("Strikeout", [inlines]) => Inline::Strikeout(to_inlines(inlines)?),
+        ("Superscript", [inlines]) => Inline::Superscript(to_inlines(inlines)?),
+        ("Subscript", [inlines]) => Inline::Subscript(to_inlines(inlines)?),
+        ("SmallCaps", [inlines]) => Inline::SmallCaps(to_inlines(inlines)?),
+        ("Quoted", [quote, inlines]) => {
+            Inline::Quoted(to_unit(quote, "a quote type")?, to_inlines(inlines)?)
+        }
+        ("Cite", [citations, inlines]) => {
+            Inline::Cite(to_list(citations, to_citation)?, to_inlines(inlines)?)
+        }
+        ("Code", [attr, code]) => Inline::Code(to_attr(attr)?, to_string(code)?),
+        ("Space", []) => Inline::Space,
+        ("SoftBreak", []) => Inline::SoftBreak,
+        ("LineBreak", []) => Inline::LineBreak,
+        ("Math", [kind, tex]) => Inline::Math(to_unit(kind, "a math type")?, to_string(tex)?),
+        ("RawInline", [format, text]) => Inline::RawInline(to_format(format)?, to_string(text)?),
+        ("Link", [attr, inlines, target]) => {
+            Inline::Link(to_attr(attr)?, to_inlines(inlines)?, to_target(target)?)
+        }
+        ("Image", [attr, inlines, target]) => {
+            Inline::Image(to_attr(attr)?, to_inlines(inlines)?, to_target(target)?)
+        }
+        ("Note", [blocks]) => Inline::Note(to_blocks(blocks)?),
+        ("Span", [attr, inlines]) => Inline::Span(to_attr(attr)?, to_inlines(inlines)?),
+        _ => return Err(unexpected("an inline", value)),
+    })
+}
+
+fn to_citation(value: &Value) -> Parsed<Citation> {
+    let fields = match value.kind {
+        Kind::Rec(ref name, ref fields) if name == "Citation" => fields,
+        _ => return Err(unexpected("a citation", value)),
+    };
+    let field = |name: &str| {
+        fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value)
+            .ok_or_else(|| unexpected(&format!("a citation with `{}`", name), value))
+    };
+    Ok(Citation {
+        citationId: to_string(field("citationId")?)?,
+        citationPrefix: to_inlines(field("citationPrefix")?)?,
+        citationSuffix: to_inlines(field("citationSuffix")?)?,
+        citationMode: to_unit(field("citationMode")?, "a citation mode")?,
+        citationNoteNum: to_int(field("citationNoteNum")?)?,
+        citationHash: to_int(field("citationHash")?)?,
+    })
+}
+
+/// The entries of `fromList [ ( key , value ) ]`
+fn to_meta_map(value: &Value) -> Parsed<Map<String, MetaValue>> {
+    match con_args(value, "a map")? {
+        ("fromList", [entries]) => to_list(entries, |entry| {
+            let (key, value) = to_pair(entry)?;
+            Ok((to_string(key)?, to_meta_value(value)?))
+        })
+        .map(|entries| entries.into_iter().collect()),
+        _ => Err(unexpected("a map", value)),
+    }
+}
+
+fn to_meta_value(value: &Value) -> Parsed<MetaValue> {
+    let (name, args) = con_args(value, "a metadata value")?;
+    Ok(match (name, args) {
+        ("MetaMap", [map]) => MetaValue::MetaMap(
+            to_meta_map(map)?
+                .into_iter()
+                .map(|(k, v)| (k, Box::new(v)))
+                .collect(),
+        ),
+        ("MetaList", [items]) => MetaValue::MetaList(to_list(items, to_meta_value)?),
+        (
+            "MetaBool",
+            [Value {
+                kind: Kind::Con(ref b, ref args),
+                ..
+            }],
+        ) if args.is_empty() => match &b[..] {
+            "True" => MetaValue::MetaBool(true),
+            "False" => MetaValue::MetaBool(false),
+            _ => return Err(unexpected("a metadata value", value)),
+        },
+        ("MetaString", [text]) => MetaValue::MetaString(to_string(text)?),
+        ("MetaInlines", [inlines]) => MetaValue::MetaInlines(to_inlines(inlines)?),
+        ("MetaBlocks", [blocks]) => MetaValue::MetaBlocks(to_blocks(blocks)?),
+        _ => return Err(unexpected("a metadata value", value)),
+    })
+}
+
+fn to_meta(value: &Value) -> Parsed<Map<String, MetaValue>> {
+    match value.kind {
+        Kind::Rec(ref name, ref fields) if name == "Meta" => match &fields[..] {
+            [(field, map)] if field == "unMeta" => to_meta_map(map),
+            _ => Err(unexpected("metadata", value)),
+        },
+        _ => Err(unexpected("metadata", value)),
+    }
+}
+
+/// The metadata and blocks of a `Pandoc` value or a list of blocks
+fn to_document(value: &Value) -> Parsed<(Map<String, MetaValue>, Vec<Block>)> {
+    match value.kind {
+        Kind::List(_) => Ok((Map::new(), to_blocks(value)?)),
+        Kind::Con(ref name, ref args) if name == "Pandoc" && args.len() == 2 => {
+            Ok((to_meta(&args[0])?, to_blocks(&args[1])?))
+        }
+        _ => Err(unexpected("a document or a list of blocks", value)),
+    }
+}
+
+impl Pandoc {
+    /// Renders the document in pandoc's native format, the output of
+    /// `pandoc --standalone --to native`
+    pub fn to_native(&self) -> String {
+        let meta = Kind::Rec(
+            "Meta".to_string(),
+            vec![("unMeta".to_string(), meta_map_value(self.meta.iter()))],
+        )
+        .into();
+        let mut printer = Printer {
+            out: String::new(),
+            column: 0,
+            indent: 0,
+        };
+        printer.text("Pandoc");
+        printer.newline(2);
+        printer.value(&meta, 0);
+        printer.newline(2);
+        printer.value(&blocks_value(&self.blocks), 0);
+        printer.out.push('\n');
+        printer.out
+    }
+
+    /// Parses a document in pandoc's native format. Like `pandoc --from
+    /// native` this accepts a whole `Pandoc` value as well as a list of
+    /// blocks, and any layout of it.
+    pub fn from_native(native: &str) -> Result<Pandoc, NativeError> {
+        let mut parser = Parser {
+            text: native,
+            pos: 0,
+        };
+        let value = parser.value()?;
+        if parser.peek().is_some() {
+            return Err(parser.error("expected end of input"));
+        }
+        let (meta, blocks) =
+            to_document(&value).map_err(|err| parser.error_at(err.offset, err.message))?;
+        Ok(Pandoc {
+            meta,
+            blocks,
+            pandoc_api_version: vec![1, 23, 1],
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use super::*;
+use sections::is_unnumbered;
+
+fn roman(mut n: Int) -> String {
+    const NUMERALS: &[(Int, &str)] = &[
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut out = String::new();
+    for &(value, numeral) in NUMERALS {
+        while n >= value {
+            out.push_str(numeral);
+            n -= value;
+        }
+    }
+    out
+}
+
+fn alpha(mut n: Int) -> String {
+    let mut out = Vec::new();
+    while n > 0 {
+        n -= 1;
+        out.push(b'a' + (n % 26) as u8);
+        n /= 26;
+    }
+    out.reverse();
+    String::from_utf8(out).unwrap()
+}
+
+impl ListNumberStyle {
+    /// Renders `n` in this style: `3` is `iii` in `LowerRoman` and `C` in
+    /// `UpperAlpha`. `DefaultStyle` and `Example` use decimal numbers, as
+    /// do numbers that have no representation in the style (like `0`).
+    pub fn format(&self, n: Int) -> String {
+        use ListNumberStyle::*;
+        match *self {
+            _ if n <= 0 => n.to_string(),
+            LowerRoman => roman(n),
+            UpperRoman => roman(n).to_uppercase(),
+            LowerAlpha => alpha(n),
+            UpperAlpha => alpha(n).to_uppercase(),
+            DefaultStyle | Example | Decimal => n.to_string(),
+        }
+    }
+}
+
+/// Options for `number_sections`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberingOptions {
+    /// Number style per header level, starting at level 1. Levels without an
+    /// entry are numbered decimally.
+    pub formats: Vec<ListNumberStyle>,
+    /// Initial counter per header level, like pandoc's `--number-offset`:
+    /// `[5]` numbers the first level 1 header `6`
+    pub offsets: Vec<Int>,
+    /// Headers with this class and all following level 1 headers are
+    /// numbered as appendices, with their own counter and `appendix_style`
+    pub appendix_class: String,
+    pub appendix_style: ListNumberStyle,
+}
+
+impl Default for NumberingOptions {
+    fn default() -> Self {
+        NumberingOptions {
+            formats: Vec::new(),
+            offsets: Vec::new(),
+            appendix_class: "appendix".to_string(),
+            appendix_style: ListNumberStyle::UpperAlpha,
+        }
+    }
+}
+
+impl NumberingOptions {
+    /// Default options with the offsets taken from the `number-offset`
+    /// metadata field, either a list or a comma separated string like `1,4`
+    pub fn from_meta(meta: &Map<String, MetaValue>) -> Self {
+        let offsets = match meta.get("number-offset") {
+            Some(MetaValue::MetaList(list)) => list
+                .iter()
+                .filter_map(|v| v.stringify().trim().parse().ok())
+                .collect(),
+            Some(value) => value
+                .stringify()
+                .split(',')
+                .filter_map(|n| n.trim().parse().ok())
+                .collect(),
+            None => Vec::new(),
+        };
+        NumberingOptions {
+            offsets,
+            ..NumberingOptions::default()
+        }
+    }
+}
+
+/// Prefixes a header's text with a `header-section-number` span
+pub(crate) fn add_section_number(title: &mut Vec<Inline>, number: &str) {
+    let span_attr = (
+        String::new(),
+        vec!["header-section-number".to_string()],
+        vec![],
+    );
+    title.splice(
+        0..0,
+        vec![
+            Inline::Span(span_attr, vec![Inline::Str(number.to_string())]),
+            Inline::Space,
+        ],
+    );
+}
+
+/// Removes a section number added by `add_section_number`
+pub(crate) fn strip_section_number(title: &mut Vec<Inline>) {
+    if let Some(Inline::Span((_, ref classes, _), _)) = title.first() {
+        if classes.iter().any(|c| c == "header-section-number") {
+            title.remove(0);
+            if title.first() == Some(&Inline::Space) {
+                title.remove(0);
+            }
+        }
+    }
+}
+
+struct Numberer<'a> {
+    options: &'a NumberingOptions,
+    counters: Vec<Int>,
+    appendix: bool,
+}
+
+impl Numberer<'_> {
+    fn style(&self, level: usize) -> ListNumberStyle {
+        if level == 0 && self.appendix {
+            return self.options.appendix_style;
+        }
+        self.options
+            .formats
+            .get(level)
+            .copied()
+            .unwrap_or(ListNumberStyle::Decimal)
+    }
+
+    fn visit(&mut self, blocks: &mut [Block]) {
+        for block in blocks {
+            match *block {
+                Block::Header(level, (_, ref classes, ref mut kvs), ref mut title) => {
+                    kvs.retain(|(k, _)| k != "number");
+                    strip_section_number(title);
+                    if !self.appendix && classes.contains(&self.options.appendix_class) {
+                        self.appendix = true;
+                        self.counters = vec![0];
+                    }
+                    if level < 1 || is_unnumbered(classes) {
+                        continue;
+                    }
+                    let depth = level as usize;
+                    if self.counters.len() < depth {
+                        self.counters.resize(depth, 0);
+                    }
+                    self.counters.truncate(depth);
+                    self.counters[depth - 1] += 1;
+                    let number = self
+                        .counters
+                        .iter()
+                        .enumerate()
+                        .map(|(level, &n)| self.style(level).format(n))
+                        .collect::<Vec<_>>()
+                        .join(".");
+                    add_section_number(title, &number);
+                    kvs.push(("number".to_string(), number));
+                }
+                Block::Div(_, ref mut inner) => self.visit(inner),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Numbers the headers at the top level and inside `Div`s.
+///
+/// Each numbered header gets a `number` attribute like `2.1` and its text
+/// is prefixed with a `Span` of class `header-section-number` holding the
+/// same number, like pandoc does with `--number-sections`. Headers with the
+/// class `unnumbered` or `-` are skipped. Numbers from a previous run are
+/// replaced.
+pub fn number_sections(blocks: &mut [Block], options: &NumberingOptions) {
+    Numberer {
+        options,
+        counters: options.offsets.clone(),
+        appendix: false,
+    }
+    .visit(blocks)
+}
+
+impl Pandoc {
+    /// Applies `number_sections` to the document's blocks
+    pub fn number_sections(&mut self, options: &NumberingOptions) {
+        number_sections(&mut self.blocks, options)
+    }
+}
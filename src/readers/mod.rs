@@ -0,0 +1,5 @@
+//! Readers parsing other formats into documents without running pandoc
+
+pub mod org;
+
+use super::*;
@@ -0,0 +1,699 @@
+//! A subset of Emacs Org mode
+//!
+//! Reads headlines with their properties drawers, paragraphs, plain and
+//! description lists, source, example, quote and other blocks, tables,
+//! footnotes, links and emphasis. Headers without a `CUSTOM_ID` get
+//! identifiers the way pandoc's org reader generates them.
+
+use super::*;
+
+/// Parses Org text into a document. Anything this reader doesn't know is
+/// read as paragraph text, so parsing never fails.
+pub fn read(text: &str) -> Pandoc {
+    let mut lines: Vec<&str> = text.lines().collect();
+    let mut reader = Reader::default();
+    reader.take_notes(&mut lines);
+    let blocks = reader.blocks(&lines);
+    Pandoc {
+        meta: reader.meta,
+        blocks,
+        pandoc_api_version: vec![1, 23, 1],
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+/// The level of a headline like `** Title`
+fn headline(line: &str) -> Option<usize> {
+    let stars = line.chars().take_while(|&c| c == '*').count();
+    let rest = &line[stars..];
+    (stars > 0 && (rest.is_empty() || rest.starts_with(' '))).then_some(stars)
+}
+
+/// The label and text of a footnote definition like `[fn:1] Text`
+fn footnote_definition(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix("[fn:")?;
+    let (label, text) = rest.split_once(']')?;
+    (!label.is_empty() && !label.contains(':')).then_some((label, text.trim_start()))
+}
+
+/// The lowercased name and the value of a keyword like `#+TITLE: Text`
+fn keyword(line: &str) -> Option<(String, &str)> {
+    let rest = line.trim_start().strip_prefix("#+")?;
+    let (key, value) = rest.split_once(':')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key.to_lowercase(), value.trim()))
+}
+
+/// Removes the comma Org puts in front of lines that would otherwise be
+/// read as headlines or keywords
+fn unescape_code(line: &str) -> String {
+    let content = line.trim_start();
+    let indentation = &line[..line.len() - content.len()];
+    match content.strip_prefix(',') {
+        Some(rest) => {
+            let unescaped = rest.trim_start_matches(',');
+            if unescaped.starts_with('*') || unescaped.starts_with("#+") {
+                return format!("{}{}", indentation, rest);
+            }
+            line.to_string()
+        }
+        None => line.to_string(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListKind {
+    Bullet,
+    Ordered(ListNumberDelim),
+}
+
+/// A list item's indentation, kind, the column its content starts at and
+/// its first line of text
+struct Item<'a> {
+    indent: usize,
+    kind: ListKind,
+    column: usize,
+    text: &'a str,
+}
+
+fn list_item(line: &str) -> Option<Item<'_>> {
+    let content = line.trim_start_matches([' ', '\t']);
+    let indent = line.len() - content.len();
+    let marker = content.split(' ').next().unwrap_or("");
+    let kind = match marker {
+        "-" | "+" => ListKind::Bullet,
+        // at the start of the line `*` is a headline
+        "*" if indent > 0 => ListKind::Bullet,
+        _ => {
+            let number = marker.strip_suffix(['.', ')'])?;
+            if number.is_empty() || !number.chars().all(|c| c.is_ascii_digit()) {
+                return None;
+            }
+            if marker.ends_with(')') {
+                ListKind::Ordered(ListNumberDelim::OneParen)
+            } else {
+                ListKind::Ordered(ListNumberDelim::Period)
+            }
+        }
+    };
+    let text = content[marker.len()..].strip_prefix(' ').unwrap_or("");
+    Some(Item {
+        indent,
+        kind,
+        column: indent + marker.len() + 1,
+        text,
+    })
+}
+
+/// Whether the line starts something other than a paragraph
+fn interrupts(line: &str) -> bool {
+    let content = line.trim_start();
+    headline(line).is_some()
+        || content.starts_with("#+")
+        || content.starts_with('|')
+        || list_item(line).is_some()
+        || footnote_definition(line).is_some()
+}
+
+fn is_rule(line: &str) -> bool {
+    let line = line.trim();
+    line.len() >= 5 && line.chars().all(|c| c == '-')
+}
+
+/// Turns the paragraphs of a tight list item into plain blocks
+fn tighten(blocks: Vec<Block>) -> Vec<Block> {
+    blocks
+        .into_iter()
+        .map(|block| match block {
+            Block::Para(inlines) => Block::Plain(inlines),
+            block => block,
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Reader {
+    meta: Map<String, MetaValue>,
+    /// The text of footnote definitions by label
+    notes: Map<String, String>,
+    /// Labels of the footnotes being read, references to them from their
+    /// own text stay plain text
+    expanding: Vec<String>,
+    ids: IdGenerator,
+}
+
+impl Reader {
+    /// Collects footnote definitions outside of blocks and blanks their
+    /// lines
+    fn take_notes(&mut self, lines: &mut [&str]) {
+        let mut in_block = false;
+        let mut i = 0;
+        while i < lines.len() {
+            let lower = lines[i].trim_start().to_lowercase();
+            if lower.starts_with("#+begin_") {
+                in_block = true;
+            } else if lower.starts_with("#+end_") {
+                in_block = false;
+            }
+            let (label, text) = match footnote_definition(lines[i]) {
+                Some(definition) if !in_block => definition,
+                _ => {
+                    i += 1;
+                    continue;
+                }
+            };
+            let mut text = text.to_string();
+            lines[i] = "";
+            i += 1;
+            while i < lines.len() && !is_blank(lines[i]) && !interrupts(lines[i]) {
+                text.push('\n');
+                text.push_str(lines[i]);
+                lines[i] = "";
+                i += 1;
+            }
+            self.notes.insert(label.to_string(), text);
+        }
+    }
+
+    fn blocks(&mut self, lines: &[&str]) -> Vec<Block> {
+        let mut blocks = Vec::new();
+        // `#+caption:` and `#+name:` of the next table or figure
+        let mut caption: Option<Vec<Inline>> = None;
+        let mut name = String::new();
+        let mut i = 0;
+        while i < lines.len() {
+            let line = lines[i];
+            let content = line.trim();
+            if content.is_empty() {
+                i += 1;
+                continue;
+            }
+            if let Some(level) = headline(line) {
+                i += 1;
+                blocks.push(self.header(level, &line[level..], lines, &mut i));
+                continue;
+            }
+            if content.to_lowercase().starts_with("#+begin_") {
+                blocks.push(self.greater_block(lines, &mut i));
+                continue;
+            }
+            if let Some((key, value)) = keyword(line) {
+                match &key[..] {
+                    "title" | "subtitle" | "date" => {
+                        let value = MetaValue::MetaInlines(self.inlines(value));
+                        self.meta.insert(key, value);
+                    }
+                    "author" => {
+                        let author = MetaValue::MetaInlines(self.inlines(value));
+                        let authors = match self.meta.remove("author") {
+                            Some(MetaValue::MetaList(mut authors)) => {
+                                authors.push(author);
+                                MetaValue::MetaList(authors)
+                            }
+                            Some(first) => MetaValue::MetaList(vec![first, author]),
+                            None => author,
+                        };
+                        self.meta.insert(key, authors);
+                    }
+                    "caption" => caption = Some(self.inlines(value)),
+                    "name" => name = value.to_string(),
+                    _ => {}
+                }
+                i += 1;
+                continue;
+            }
+            if content == "#" || content.starts_with("# ") {
+                i += 1;
+                continue;
+            }
+            let block = if content.starts_with('|') {
+                let start = i;
+                while i < lines.len() && lines[i].trim_start().starts_with('|') {
+                    i += 1;
+                }
+                self.table(&lines[start..i], caption.take(), &name)
+            } else if is_rule(line) {
+                i += 1;
+                Block::HorizontalRule
+            } else if let Some(item) = list_item(line) {
+                self.list(item, lines, &mut i)
+            } else {
+                let start = i;
+                i += 1;
+                while i < lines.len() && !is_blank(lines[i]) && !interrupts(lines[i]) {
+                    i += 1;
+                }
+                let inlines = self.inlines(&lines[start..i].join("\n"));
+                match (caption.take(), &inlines[..]) {
+                    (Some(caption), [Inline::Image(..)]) => Block::Figure(
+                        (std::mem::take(&mut name), vec![], vec![]),
+                        (None, vec![Block::Plain(caption)]),
+                        vec![Block::Plain(inlines)],
+                    ),
+                    _ => Block::Para(inlines),
+                }
+            };
+            caption = None;
+            name.clear();
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    /// A headline with its properties drawer, tags become classes
+    fn header(&mut self, level: usize, title: &str, lines: &[&str], i: &mut usize) -> Block {
+        let mut title = title.trim();
+        let mut classes = Vec::new();
+        if let Some((rest, tags)) = title.rsplit_once(char::is_whitespace) {
+            let is_tags = tags.len() > 2
+                && tags.starts_with(':')
+                && tags.ends_with(':')
+                && !tags.contains("::");
+            if is_tags {
+                classes.extend(tags.split(':').filter(|t| !t.is_empty()).map(String::from));
+                title = rest.trim_end();
+            }
+        }
+        let mut id = String::new();
+        let mut kvs = Vec::new();
+        if lines
+            .get(*i)
+            .is_some_and(|l| l.trim().eq_ignore_ascii_case(":PROPERTIES:"))
+        {
+            *i += 1;
+            while *i < lines.len() && !lines[*i].trim().eq_ignore_ascii_case(":END:") {
+                let property = lines[*i].trim().strip_prefix(':');
+                if let Some((key, value)) = property.and_then(|p| p.split_once(':')) {
+                    let value = value.trim();
+                    match key {
+                        "CUSTOM_ID" => id = value.to_string(),
+                        "UNNUMBERED" if value == "t" => classes.push("unnumbered".to_string()),
+                        _ => kvs.push((key.to_string(), value.to_string())),
+                    }
+                }
+                *i += 1;
+            }
+            *i += 1;
+        }
+        let inlines = self.inlines(title);
+        if id.is_empty() {
+            id = self.ids.generate(&inlines);
+        } else {
+            self.ids.reserve(id.clone());
+        }
+        Block::Header(level as Int, (id, classes, kvs), inlines)
+    }
+
+    /// A `#+begin_name` ... `#+end_name` block
+    fn greater_block(&mut self, lines: &[&str], i: &mut usize) -> Block {
+        let begin = lines[*i].trim();
+        let (name, args) = begin["#+begin_".len()..]
+            .split_once(char::is_whitespace)
+            .unwrap_or((&begin["#+begin_".len()..], ""));
+        let name = name.to_lowercase();
+        let end = format!("#+end_{}", name);
+        let start = *i + 1;
+        let mut stop = start;
+        while stop < lines.len() && !lines[stop].trim().eq_ignore_ascii_case(&end) {
+            stop += 1;
+        }
+        *i = stop + 1;
+        let content = &lines[start..stop];
+        let code = || {
+            let lines: Vec<String> = content.iter().map(|l| unescape_code(l)).collect();
+            lines.join("\n")
+        };
+        match &name[..] {
+            "src" => {
+                let classes = args.split_whitespace().take(1).map(String::from).collect();
+                Block::CodeBlock((String::new(), classes, vec![]), code())
+            }
+            "example" => Block::CodeBlock(Attr::default(), code()),
+            "export" => Block::RawBlock(Format::new(args.trim()), content.join("\n")),
+            "quote" => Block::BlockQuote(self.blocks(content)),
+            "verse" => Block::LineBlock(content.iter().map(|l| self.inlines(l.trim())).collect()),
+            _ => Block::Div((String::new(), vec![name], vec![]), self.blocks(content)),
+        }
+    }
+
+    fn list(&mut self, first: Item, lines: &[&str], i: &mut usize) -> Block {
+        let mut start = 1;
+        let mut items: Vec<(Vec<String>, bool)> = Vec::new();
+        let mut text = first.text;
+        let mut column = first.column;
+        loop {
+            if items.is_empty() {
+                if let Some(rest) = text.strip_prefix("[@") {
+                    if let Some((n, rest)) = rest.split_once(']') {
+                        start = n.parse().unwrap_or(1);
+                        text = rest.trim_start();
+                    }
+                }
+            }
+            let first_line = match text {
+                _ if text.starts_with("[ ] ") => format!("\u{2610} {}", &text[4..]),
+                _ if text.starts_with("[X] ") || text.starts_with("[x] ") => {
+                    format!("\u{2612} {}", &text[4..])
+                }
+                _ => text.to_string(),
+            };
+            let mut item = vec![first_line];
+            *i += 1;
+            // continuation lines are indented deeper than the marker, two
+            // blank lines end the list
+            while *i < lines.len() {
+                let line = lines[*i];
+                // only ASCII indentation, so `column` is a char boundary
+                let indent = line.len() - line.trim_start_matches([' ', '\t']).len();
+                if is_blank(line) {
+                    if lines.get(*i + 1).is_none_or(|l| is_blank(l)) {
+                        break;
+                    }
+                    item.push(String::new());
+                } else if indent > first.indent {
+                    item.push(line[indent.min(column)..].to_string());
+                } else {
+                    break;
+                }
+                *i += 1;
+            }
+            let blank = item.last().is_some_and(|l| l.is_empty());
+            while item.last().is_some_and(|l| l.is_empty()) {
+                item.pop();
+            }
+            let loose = blank || item.iter().any(|l| l.is_empty());
+            items.push((item, loose));
+            match lines.get(*i).and_then(|l| list_item(l)) {
+                Some(next) if next.indent == first.indent && next.kind == first.kind => {
+                    text = next.text;
+                    column = next.column;
+                }
+                _ => break,
+            }
+        }
+        // a blank line after the last item doesn't make the list loose
+        let last = items.len() - 1;
+        let loose =
+            items[..last].iter().any(|item| item.1) || items[last].0.iter().any(|l| l.is_empty());
+        let mut blocks: Vec<Vec<Block>> = Vec::new();
+        let description = first.kind == ListKind::Bullet && first.text.contains(" ::");
+        let mut definitions = Vec::new();
+        for (item, _) in items {
+            let lines: Vec<&str> = item.iter().map(|l| &l[..]).collect();
+            if description {
+                let (term, definition) = lines[0].split_once(" ::").unwrap_or((lines[0], ""));
+                let mut rest = vec![definition.trim_start()];
+                rest.extend(&lines[1..]);
+                let mut definition = self.blocks(&rest);
+                if !loose {
+                    definition = tighten(definition);
+                }
+                definitions.push((self.inlines(term.trim()), vec![definition]));
+                continue;
+            }
+            let item = self.blocks(&lines);
+            blocks.push(if loose { item } else { tighten(item) });
+        }
+        match first.kind {
+            _ if description => Block::DefinitionList(definitions),
+            ListKind::Bullet => Block::BulletList(blocks),
+            ListKind::Ordered(delim) => {
+                Block::OrderedList((start, ListNumberStyle::Decimal, delim), blocks)
+            }
+        }
+    }
+
+    /// A table, rows above the first rule are its head
+    fn table(&mut self, lines: &[&str], caption: Option<Vec<Inline>>, name: &str) -> Block {
+        let mut head_rows = None;
+        let mut rows: Vec<Vec<String>> = Vec::new();
+        let mut aligns: Vec<Alignment> = Vec::new();
+        for line in lines {
+            let line = line.trim();
+            if line.starts_with("|-") {
+                if head_rows.is_none() && !rows.is_empty() {
+                    head_rows = Some(rows.len());
+                }
+                continue;
+            }
+            let inner = line.trim_start_matches('|');
+            let inner = inner.strip_suffix('|').unwrap_or(inner);
+            let cells: Vec<String> = inner.split('|').map(|c| c.trim().to_string()).collect();
+            // a row of cookies like `<l>` or `<r10>` sets the alignments
+            let cookie = |c: &String| {
+                c.starts_with('<')
+                    && c.ends_with('>')
+                    && c[1..c.len() - 1]
+                        .trim_start_matches(['l', 'r', 'c'])
+                        .chars()
+                        .all(|c| c.is_ascii_digit())
+            };
+            if cells.iter().all(|c| c.is_empty() || cookie(c)) && cells.iter().any(cookie) {
+                aligns = cells
+                    .iter()
+                    .map(|c| match c.get(1..2) {
+                        Some("l") => Alignment::AlignLeft,
+                        Some("r") => Alignment::AlignRight,
+                        Some("c") => Alignment::AlignCenter,
+                        _ => Alignment::AlignDefault,
+                    })
+                    .collect();
+                continue;
+            }
+            rows.push(cells);
+        }
+        let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let colspecs = (0..cols)
+            .map(|c| {
+                let align = aligns.get(c).copied().unwrap_or(Alignment::AlignDefault);
+                (align, ColWidth::ColWidthDefault)
+            })
+            .collect();
+        let mut rows: Vec<Row> = rows
+            .into_iter()
+            .map(|mut cells| {
+                cells.resize(cols, String::new());
+                let cells = cells
+                    .into_iter()
+                    .map(|text| {
+                        let blocks = if text.is_empty() {
+                            vec![]
+                        } else {
+                            vec![Block::Plain(self.inlines(&text.replace("\\vert{}", "|")))]
+                        };
+                        (Attr::default(), Alignment::AlignDefault, 1, 1, blocks)
+                    })
+                    .collect();
+                (Attr::default(), cells)
+            })
+            .collect();
+        let body = rows.split_off(head_rows.unwrap_or(0));
+        Block::Table(
+            (name.to_string(), vec![], vec![]),
+            (None, caption.map(Block::Plain).into_iter().collect()),
+            colspecs,
+            (Attr::default(), rows),
+            vec![(Attr::default(), 0, vec![], body)],
+            (Attr::default(), vec![]),
+        )
+    }
+
+    fn inlines(&mut self, text: &str) -> Vec<Inline> {
+        let chars: Vec<char> = text.chars().collect();
+        let mut out = Vec::new();
+        let mut word = String::new();
+        let mut i = 0;
+        let flush = |word: &mut String, out: &mut Vec<Inline>| {
+            if !word.is_empty() {
+                out.push(Inline::Str(std::mem::take(word)));
+            }
+        };
+        let find = |from: usize, pattern: &str| {
+            let pattern: Vec<char> = pattern.chars().collect();
+            (from..chars.len()).find(|&j| chars[j..].starts_with(&pattern))
+        };
+        let slice = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+        while i < chars.len() {
+            let c = chars[i];
+            let rest = &chars[i..];
+            if c.is_whitespace() {
+                flush(&mut word, &mut out);
+                let mut newline = false;
+                while i < chars.len() && chars[i].is_whitespace() {
+                    newline |= chars[i] == '\n';
+                    i += 1;
+                }
+                if i < chars.len() {
+                    out.push(if newline {
+                        Inline::SoftBreak
+                    } else {
+                        Inline::Space
+                    });
+                }
+                continue;
+            }
+            // `\\` at the end of a line
+            if rest.starts_with(&['\\', '\\']) {
+                let next = (i + 2..chars.len()).find(|&j| chars[j] != ' ' && chars[j] != '\t');
+                if next.is_none_or(|j| chars[j] == '\n') {
+                    flush(&mut word, &mut out);
+                    out.push(Inline::LineBreak);
+                    i = next.map_or(chars.len(), |j| j + 1);
+                    continue;
+                }
+            }
+            if rest.starts_with(&['[', '[']) {
+                if let Some(end) = find(i + 2, "]]") {
+                    flush(&mut word, &mut out);
+                    out.push(self.link(&slice(i + 2, end)));
+                    i = end + 2;
+                    continue;
+                }
+            }
+            if rest.starts_with(&['[', 'f', 'n', ':']) {
+                if let Some(end) = find(i + 4, "]") {
+                    let inside = slice(i + 4, end);
+                    let note = match inside.split_once(':') {
+                        Some((_, text)) => Some((None, text.to_string())),
+                        None if self.expanding.contains(&inside) => None,
+                        None => self
+                            .notes
+                            .get(&inside)
+                            .map(|note| (Some(inside), note.clone())),
+                    };
+                    if let Some((label, note)) = note {
+                        flush(&mut word, &mut out);
+                        let note: Vec<&str> = note.lines().collect();
+                        let depth = self.expanding.len();
+                        self.expanding.extend(label);
+                        out.push(Inline::Note(self.blocks(&note)));
+                        self.expanding.truncate(depth);
+                        i = end + 1;
+                        continue;
+                    }
+                }
+            }
+            if rest.starts_with(&['<', '<']) {
+                if let Some(end) = find(i + 2, ">>") {
+                    flush(&mut word, &mut out);
+                    out.push(Inline::Span((slice(i + 2, end), vec![], vec![]), vec![]));
+                    i = end + 2;
+                    continue;
+                }
+            }
+            let math = match rest {
+                ['\\', '(', ..] => Some((MathType::InlineMath, "\\)")),
+                ['\\', '[', ..] => Some((MathType::DisplayMath, "\\]")),
+                _ => None,
+            };
+            if let Some((kind, close)) = math {
+                if let Some(end) = find(i + 2, close) {
+                    flush(&mut word, &mut out);
+                    out.push(Inline::Math(kind, slice(i + 2, end)));
+                    i = end + 2;
+                    continue;
+                }
+            }
+            if rest.starts_with(&['@', '@']) {
+                let end = find(i + 2, "@@");
+                let raw = end.map(|end| slice(i + 2, end));
+                if let (Some(end), Some((format, text))) =
+                    (end, raw.as_ref().and_then(|r| r.split_once(':')))
+                {
+                    flush(&mut word, &mut out);
+                    out.push(Inline::RawInline(Format::new(format), text.to_string()));
+                    i = end + 2;
+                    continue;
+                }
+            }
+            // `x^{2}` and `x_{i}` directly after a word
+            if matches!(rest, ['^' | '_', '{', ..]) && !word.is_empty() {
+                if let Some(end) = find(i + 2, "}") {
+                    flush(&mut word, &mut out);
+                    let inner = self.inlines(&slice(i + 2, end));
+                    out.push(if c == '^' {
+                        Inline::Superscript(inner)
+                    } else {
+                        Inline::Subscript(inner)
+                    });
+                    i = end + 1;
+                    continue;
+                }
+            }
+            if let Some(end) = self.emphasis_end(&chars, i) {
+                flush(&mut word, &mut out);
+                let inner = slice(i + 1, end);
+                out.push(match c {
+                    '=' | '~' => Inline::Code(Attr::default(), inner),
+                    '/' => Inline::Emph(self.inlines(&inner)),
+                    '*' => Inline::Strong(self.inlines(&inner)),
+                    '_' => Inline::Underline(self.inlines(&inner)),
+                    _ => Inline::Strikeout(self.inlines(&inner)),
+                });
+                i = end + 1;
+                continue;
+            }
+            word.push(c);
+            i += 1;
+        }
+        flush(&mut word, &mut out);
+        out
+    }
+
+    /// The position of the marker closing emphasis opened at `i`, following
+    /// Org's rules for the characters around the markers
+    fn emphasis_end(&self, chars: &[char], i: usize) -> Option<usize> {
+        let marker = chars[i];
+        if !"*/_+=~".contains(marker) {
+            return None;
+        }
+        let before = i.checked_sub(1).map(|j| chars[j]);
+        if !before.is_none_or(|c| c.is_whitespace() || "-({'\"".contains(c)) {
+            return None;
+        }
+        if chars.get(i + 1).is_none_or(|c| c.is_whitespace()) {
+            return None;
+        }
+        (i + 2..chars.len()).find(|&j| {
+            chars[j] == marker
+                && !chars[j - 1].is_whitespace()
+                && chars
+                    .get(j + 1)
+                    .is_none_or(|&c| c.is_whitespace() || "-.,:!?;'\")}[\\".contains(c))
+        })
+    }
+
+    /// A link like `[[url][description]]` or `[[file:image.png]]`
+    fn link(&mut self, inside: &str) -> Inline {
+        let (target, description) = match inside.split_once("][") {
+            Some((target, description)) => (target, Some(description)),
+            None => (inside, None),
+        };
+        let extension = target.rsplit('.').next().unwrap_or("").to_lowercase();
+        let image = matches!(
+            &extension[..],
+            "png" | "jpg" | "jpeg" | "gif" | "svg" | "webp" | "bmp" | "tif" | "tiff"
+        );
+        match description {
+            None if image => {
+                let url = target.strip_prefix("file:").unwrap_or(target);
+                Inline::Image(Attr::default(), vec![], (url.to_string(), String::new()))
+            }
+            None => Inline::Link(
+                Attr::default(),
+                vec![Inline::Str(target.to_string())],
+                (target.to_string(), String::new()),
+            ),
+            Some(description) => Inline::Link(
+                Attr::default(),
+                self.inlines(description),
+                (target.to_string(), String::new()),
+            ),
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use super::*;
+use numbering::{add_section_number, strip_section_number};
+use std::ops::Range;
+
+/// Options for `make_sections`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct MakeSectionsOptions {
+    /// Add a `number` attribute and a `header-section-number` span to
+    /// numbered headers
+    pub number_sections: bool,
+    /// Shift the headers so the outermost ones end up at this level
+    pub base_level: Option<Int>,
+}
+
+pub(crate) fn is_unnumbered(classes: &[String]) -> bool {
+    classes.iter().any(|c| c == "unnumbered" || c == "-")
+}
+
+/// The level of a header or of a section `Div` starting with a header
+pub(crate) fn section_level(block: &Block) -> Option<Int> {
+    match *block {
+        Block::Header(level, ..) => Some(level),
+        Block::Div(_, ref blocks) => match blocks.first() {
+            Some(&Block::Header(level, ..)) => Some(level),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn min_header_level(blocks: &[Block]) -> Option<Int> {
+    blocks
+        .iter()
+        .filter_map(|block| match *block {
+            Block::Header(level, ..) => Some(level),
+            Block::Div(_, ref blocks) => min_header_level(blocks),
+            _ => None,
+        })
+        .min()
+}
+
+/// Merges two attributes, preferring the identifier and key-value pairs
+/// of `first`
+fn combine_attr(first: Attr, second: Attr) -> Attr {
+    let id = if first.0.is_empty() {
+        second.0
+    } else {
+        first.0
+    };
+    let mut classes = first.1;
+    for class in second.1 {
+        if !classes.contains(&class) {
+            classes.push(class);
+        }
+    }
+    let mut kvs = first.2;
+    for (k, v) in second.2 {
+        if !kvs.iter().any(|(key, _)| *key == k) {
+            kvs.push((k, v));
+        }
+    }
+    (id, classes, kvs)
+}
+
+struct Sectioner {
+    numbering: bool,
+    shift: Int,
+    last: Vec<Int>,
+}
+
+impl Sectioner {
+    fn go(&mut self, blocks: &[Block]) -> Vec<Block> {
+        let mut out = Vec::new();
+        let mut rest = blocks;
+        while let Some((block, tail)) = rest.split_first() {
+            rest = tail;
+            match *block {
+                Block::Header(level, (ref id, ref classes, ref kvs), ref title) => {
+                    let end = rest
+                        .iter()
+                        .position(|b| section_level(b).is_some_and(|l| l <= level))
+                        .unwrap_or(rest.len());
+                    let (contents, tail) = rest.split_at(end);
+                    rest = tail;
+                    out.push(self.section(level, id, classes, kvs, title, contents));
+                }
+                Block::Div(ref attr, ref inner) => {
+                    let nested = match inner.split_first() {
+                        Some((&Block::Header(level, ..), ys))
+                            if !attr.1.iter().any(|c| c == "column" || c == "columns")
+                                && ys.iter().all(|b| match *b {
+                                    Block::Header(l, ..) => l > level,
+                                    _ => true,
+                                }) =>
+                        {
+                            Some(self.go(inner))
+                        }
+                        _ => None,
+                    };
+                    out.push(match nested {
+                        Some(mut sections) => match sections.pop() {
+                            Some(Block::Div(inner_attr, zs))
+                                if sections.is_empty()
+                                    && (attr.0.is_empty()
+                                        || inner_attr.0.is_empty()
+                                        || attr.0 == inner_attr.0) =>
+                            {
+                                Block::Div(combine_attr(inner_attr, attr.clone()), zs)
+                            }
+                            last => {
+                                sections.extend(last);
+                                Block::Div(attr.clone(), sections)
+                            }
+                        },
+                        None => Block::Div(attr.clone(), self.go(inner)),
+                    });
+                }
+                ref other => out.push(other.clone()),
+            }
+        }
+        out
+    }
+
+    fn section(
+        &mut self,
+        level: Int,
+        id: &str,
+        classes: &[String],
+        kvs: &[(String, String)],
+        title: &[Inline],
+        contents: &[Block],
+    ) -> Block {
+        let level = level + self.shift;
+        let depth = level.max(0) as usize;
+        let number = if depth == 0 || is_unnumbered(classes) {
+            Vec::new()
+        } else if self.last.len() >= depth {
+            let mut number = self.last[..depth].to_vec();
+            *number.last_mut().unwrap() += 1;
+            number
+        } else {
+            let mut number = self.last.clone();
+            number.resize(depth - 1, 0);
+            number.push(1);
+            number
+        };
+        if !number.is_empty() {
+            self.last = number.clone();
+        }
+        let mut kvs = kvs.to_vec();
+        let mut title = title.to_vec();
+        if self.numbering && !number.is_empty() {
+            let number = number
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(".");
+            add_section_number(&mut title, &number);
+            kvs.push(("number".to_string(), number));
+        }
+        let mut section_classes = vec!["section".to_string()];
+        section_classes.extend(classes.iter().cloned());
+        let header = Block::Header(level, (String::new(), classes.to_vec(), kvs), title);
+        let mut blocks = vec![header];
+        blocks.extend(self.go(contents));
+        Block::Div((id.to_string(), section_classes, vec![]), blocks)
+    }
+}
+
+/// Wraps every header and the blocks following it (up to the next header
+/// of the same or a higher level) in a `Div` with class `section`, like
+/// pandoc's `makeSections`.
+///
+/// The header's identifier moves to the `Div`. With `number_sections`,
+/// headers get a `number` attribute like `2.1` and their text is prefixed
+/// with a `Span` of class `header-section-number`. Headers with the class
+/// `unnumbered` are not counted.
+pub fn make_sections(blocks: &[Block], options: MakeSectionsOptions) -> Vec<Block> {
+    let shift = match (options.base_level, min_header_level(blocks)) {
+        (Some(base), Some(min)) => base - min,
+        _ => 0,
+    };
+    Sectioner {
+        numbering: options.number_sections,
+        shift,
+        last: Vec::new(),
+    }
+    .go(blocks)
+}
+
+/// Reverts `make_sections`: section `Div`s are replaced by their contents,
+/// with the identifier moved back to the header and the section number
+/// removed. Header levels are not restored.
+pub fn flatten_sections(blocks: &[Block]) -> Vec<Block> {
+    let mut out = Vec::new();
+    for block in blocks {
+        match *block {
+            Block::Div((ref id, ref classes, ref kvs), ref inner)
+                if classes.iter().any(|c| c == "section")
+                    && matches!(inner.first(), Some(Block::Header(..))) =>
+            {
+                let inner = flatten_sections(inner);
+                let mut inner = inner.into_iter();
+                if let Some(Block::Header(level, (hid, hclasses, hkvs), mut title)) = inner.next() {
+                    strip_section_number(&mut title);
+                    let id = if hid.is_empty() { id.clone() } else { hid };
+                    let hkvs = hkvs.into_iter().filter(|(k, _)| k != "number").collect();
+                    let classes = classes.iter().filter(|c| *c != "section").cloned();
+                    let attr = combine_attr(
+                        (id, hclasses, hkvs),
+                        (String::new(), classes.collect(), kvs.clone()),
+                    );
+                    out.push(Block::Header(level, attr, title));
+                }
+                out.extend(inner);
+            }
+            Block::Div(ref attr, ref inner) => {
+                out.push(Block::Div(attr.clone(), flatten_sections(inner)))
+            }
+            ref other => out.push(other.clone()),
+        }
+    }
+    out
+}
+
+/// Finds the section with the identifier `id`: the path of `Div` indices
+/// leading to the list of blocks holding it and its range in that list
+fn find_section(blocks: &[Block], id: &str) -> Option<(Vec<usize>, Range<usize>)> {
+    for (i, block) in blocks.iter().enumerate() {
+        match *block {
+            Block::Header(level, (ref hid, _, _), _) if hid == id => {
+                let end = blocks[i + 1..]
+                    .iter()
+                    .position(|b| section_level(b).is_some_and(|l| l <= level))
+                    .map_or(blocks.len(), |n| i + 1 + n);
+                return Some((Vec::new(), i..end));
+            }
+            Block::Div((ref did, ref classes, _), _)
+                if did == id && classes.iter().any(|c| c == "section") =>
+            {
+                return Some((Vec::new(), i..i + 1));
+            }
+            Block::Div(_, ref inner) => {
+                if let Some((mut path, range)) = find_section(inner, id) {
+                    path.insert(0, i);
+                    return Some((path, range));
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+impl Pandoc {
+    /// The section with the identifier `id`: the header and the blocks
+    /// following it up to the next header of the same or a higher level,
+    /// or the section `Div` made by `make_sections`. Sections nested in
+    /// `Div`s are found too.
+    pub fn section(&self, id: &str) -> Option<Vec<Block>> {
+        let (path, range) = find_section(&self.blocks, id)?;
+        let mut blocks = &self.blocks;
+        for i in path {
+            match blocks[i] {
+                Block::Div(_, ref inner) => blocks = inner,
+                _ => unreachable!(),
+            }
+        }
+        Some(blocks[range].to_vec())
+    }
+
+    /// Replaces the section with the identifier `id` (see `section`) by
+    /// `replacement` and returns the old section
+    pub fn replace_section(&mut self, id: &str, replacement: Vec<Block>) -> Option<Vec<Block>> {
+        let (path, range) = find_section(&self.blocks, id)?;
+        let mut blocks = &mut self.blocks;
+        for i in path {
+            match blocks[i] {
+                Block::Div(_, ref mut inner) => blocks = inner,
+                _ => unreachable!(),
+            }
+        }
+        Some(blocks.splice(range, replacement).collect())
+    }
+
+    /// Removes the section with the identifier `id` (see `section`) and
+    /// returns it
+    pub fn remove_section(&mut self, id: &str) -> Option<Vec<Block>> {
+        self.replace_section(id, Vec::new())
+    }
+
+    /// Applies `make_sections` to the document's blocks
+    pub fn make_sections(&mut self, options: MakeSectionsOptions) {
+        self.blocks = make_sections(&self.blocks, options);
+    }
+
+    /// Applies `flatten_sections` to the document's blocks
+    pub fn flatten_sections(&mut self) {
+        self.blocks = flatten_sections(&self.blocks);
+    }
+}
@@ -0,0 +1,145 @@
+use super::*;
+use sections::section_level;
+
+/// Options for `Pandoc::split`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SplitOptions {
+    /// Headers up to this level start a new chunk
+    pub level: Int,
+    /// Blocks with this class start a new chunk, whatever their level
+    pub by_class: Option<String>,
+    /// Appended to the slug to get the file name links point to, after a
+    /// dot unless it is empty
+    pub extension: String,
+}
+
+impl Default for SplitOptions {
+    fn default() -> Self {
+        SplitOptions {
+            level: 1,
+            by_class: None,
+            extension: "html".to_string(),
+        }
+    }
+}
+
+/// The result of `Pandoc::split`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Split {
+    /// The chunks in document order, with their slugs
+    pub chunks: Vec<(String, Pandoc)>,
+    /// The slug of the chunk defining each identifier
+    pub ids: Map<String, String>,
+}
+
+impl SplitOptions {
+    fn starts_chunk(&self, block: &Block) -> bool {
+        let class = match *block {
+            Block::Header(_, (_, ref classes, _), _) | Block::Div((_, ref classes, _), _) => {
+                classes.iter().any(|c| Some(c) == self.by_class.as_ref())
+            }
+            _ => false,
+        };
+        class || section_level(block).is_some_and(|level| level <= self.level)
+    }
+}
+
+/// The identifier and text naming a chunk starting with `block`
+fn chunk_name(block: &Block) -> Option<(&str, &[Inline])> {
+    match *block {
+        Block::Header(_, (ref id, _, _), ref title) => Some((id, title)),
+        Block::Div((ref id, _, _), ref inner) => match inner.first() {
+            Some(Block::Header(_, (hid, _, _), title)) if id.is_empty() => Some((hid, title)),
+            Some(Block::Header(_, _, title)) => Some((id, title)),
+            _ if !id.is_empty() => Some((id, &[])),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Pandoc {
+    /// Splits the document into chunks at top level headers (or section
+    /// `Div`s as made by `make_sections`) up to `options.level` and at
+    /// blocks with the class `options.by_class`.
+    ///
+    /// Every chunk gets the document's metadata. Its slug is the identifier
+    /// of the header starting it, or one generated from the header's text;
+    /// blocks before the first header form a chunk named `index`. Links to
+    /// `#id` in another chunk are rewritten to `slug.html#id` (with the
+    /// default `extension`). Footnotes
+    /// are inline and so stay with the text referencing them.
+    pub fn split(&self, options: &SplitOptions) -> Split {
+        let mut slugs = IdGenerator::default();
+        let mut parts: Vec<(String, Vec<Block>)> = Vec::new();
+        for block in &self.blocks {
+            if parts.is_empty() || options.starts_chunk(block) {
+                let slug = if !options.starts_chunk(block) {
+                    slugs.unique("index")
+                } else {
+                    match chunk_name(block) {
+                        Some((id, _)) if !id.is_empty() && slugs.reserve(id) => id.to_string(),
+                        Some((_, title)) => slugs.generate(title),
+                        None => slugs.unique("section"),
+                    }
+                };
+                parts.push((slug, Vec::new()));
+            }
+            parts.last_mut().unwrap().1.push(block.clone());
+        }
+
+        struct Collect<'a>(&'a mut Map<String, String>, &'a str);
+        impl Visitor for Collect<'_> {
+            fn visit_attr(&mut self, attr: &Attr) {
+                if !attr.0.is_empty() {
+                    self.0
+                        .entry(attr.0.clone())
+                        .or_insert_with(|| self.1.to_string());
+                }
+            }
+        }
+        let mut ids = Map::new();
+        for (slug, blocks) in &parts {
+            Collect(&mut ids, slug).visit_vec_block(blocks);
+        }
+
+        struct Relink<'a> {
+            ids: &'a Map<String, String>,
+            own: &'a str,
+            extension: &'a str,
+        }
+        impl MutVisitor for Relink<'_> {
+            fn visit_inline(&mut self, inline: &mut Inline) {
+                if let Inline::Link(_, _, (ref mut url, _)) = *inline {
+                    let chunk = url.strip_prefix('#').and_then(|id| self.ids.get(id));
+                    if let Some(chunk) = chunk.filter(|c| *c != self.own) {
+                        *url = if self.extension.is_empty() {
+                            format!("{}{}", chunk, url)
+                        } else {
+                            format!("{}.{}{}", chunk, self.extension, url)
+                        };
+                    }
+                }
+                self.walk_inline(inline)
+            }
+        }
+        let chunks = parts
+            .into_iter()
+            .map(|(slug, mut blocks)| {
+                Relink {
+                    ids: &ids,
+                    own: &slug,
+                    extension: &options.extension,
+                }
+                .visit_vec_block(&mut blocks);
+                let chunk = Pandoc {
+                    meta: self.meta.clone(),
+                    blocks,
+                    pandoc_api_version: self.pandoc_api_version.clone(),
+                };
+                (slug, chunk)
+            })
+            .collect();
+        Split { chunks, ids }
+    }
+}
@@ -0,0 +1,153 @@
+use super::*;
+
+/// Plain text of an AST node, following the rules of pandoc's
+/// `pandoc.utils.stringify`
+///
+/// All formatting is dropped: `Space` and `SoftBreak` become a space,
+/// `LineBreak` a newline, `Quoted` text is wrapped in curly quotes, `Code`
+/// and `Math` contribute their text and footnotes are left out entirely.
+/// Blocks are concatenated without separators, raw content and code blocks
+/// are skipped.
+pub trait Stringify {
+    /// Appends the plain text to `out`
+    fn write_text(&self, out: &mut String);
+
+    fn stringify(&self) -> String {
+        let mut out = String::new();
+        self.write_text(&mut out);
+        out
+    }
+}
+
+impl<T: Stringify> Stringify for [T] {
+    fn write_text(&self, out: &mut String) {
+        for item in self {
+            item.write_text(out);
+        }
+    }
+}
+
+impl<T: Stringify> Stringify for Vec<T> {
+    fn write_text(&self, out: &mut String) {
+        self[..].write_text(out)
+    }
+}
+
+impl Stringify for Inline {
+    fn write_text(&self, out: &mut String) {
+        use Inline::*;
+        match *self {
+            Str(ref s) | Code(_, ref s) | Math(_, ref s) => out.push_str(s),
+            Space | SoftBreak => out.push(' '),
+            LineBreak => out.push('\n'),
+            RawInline(ref format, ref s) if format.0 == "html" && s.starts_with("<br") => {
+                out.push('\n')
+            }
+            RawInline(..) | Note(_) => {}
+            Quoted(QuoteType::SingleQuote, ref c) => {
+                out.push('\u{2018}');
+                c.write_text(out);
+                out.push('\u{2019}');
+            }
+            Quoted(QuoteType::DoubleQuote, ref c) => {
+                out.push('\u{201C}');
+                c.write_text(out);
+                out.push('\u{201D}');
+            }
+            Emph(ref c)
+            | Underline(ref c)
+            | Strong(ref c)
+            | Strikeout(ref c)
+            | Superscript(ref c)
+            | Subscript(ref c)
+            | SmallCaps(ref c)
+            | Link(_, ref c, _)
+            | Image(_, ref c, _)
+            | Span(_, ref c) => c.write_text(out),
+            Cite(ref citations, ref c) => {
+                for citation in citations {
+                    citation.citationPrefix.write_text(out);
+                    citation.citationSuffix.write_text(out);
+                }
+                c.write_text(out);
+            }
+        }
+    }
+}
+
+impl Stringify for Block {
+    fn write_text(&self, out: &mut String) {
+        use Block::*;
+        match *self {
+            Plain(ref c) | Para(ref c) | Header(_, _, ref c) => c.write_text(out),
+            LineBlock(ref lines) => lines.write_text(out),
+            CodeBlock(..) | RawBlock(..) | HorizontalRule | Null => {}
+            BlockQuote(ref c) | Div(_, ref c) => c.write_text(out),
+            OrderedList(_, ref items) | BulletList(ref items) => items.write_text(out),
+            DefinitionList(ref items) => {
+                for (term, definitions) in items {
+                    term.write_text(out);
+                    definitions.write_text(out);
+                }
+            }
+            Figure(_, ref caption, ref c) => {
+                caption.write_text(out);
+                c.write_text(out);
+            }
+            Table(_, ref caption, _, ref head, ref bodies, ref foot) => {
+                caption.write_text(out);
+                for (_, cells) in &head.1 {
+                    cells.write_text(out);
+                }
+                for (_, _, head, body) in bodies {
+                    for (_, cells) in head.iter().chain(body) {
+                        cells.write_text(out);
+                    }
+                }
+                for (_, cells) in &foot.1 {
+                    cells.write_text(out);
+                }
+            }
+        }
+    }
+}
+
+impl Stringify for Caption {
+    fn write_text(&self, out: &mut String) {
+        let (ref short, ref long) = *self;
+        if let Some(short) = short {
+            short.write_text(out);
+        }
+        long.write_text(out);
+    }
+}
+
+impl Stringify for Cell {
+    fn write_text(&self, out: &mut String) {
+        self.4.write_text(out)
+    }
+}
+
+impl Stringify for MetaValue {
+    fn write_text(&self, out: &mut String) {
+        use MetaValue::*;
+        match *self {
+            MetaMap(ref map) => {
+                for value in map.values() {
+                    value.write_text(out);
+                }
+            }
+            MetaList(ref list) => list.write_text(out),
+            MetaBool(true) => out.push_str("true"),
+            MetaBool(false) => out.push_str("false"),
+            MetaString(ref s) => out.push_str(s),
+            MetaInlines(ref c) => c.write_text(out),
+            MetaBlocks(ref c) => c.write_text(out),
+        }
+    }
+}
+
+/// Plain text of an AST node, see `Stringify`
+pub fn stringify<T: Stringify + ?Sized>(node: &T) -> String {
+    node.stringify()
+}
@@ -0,0 +1,243 @@
+use super::*;
+use numbering::strip_section_number;
+
+/// Options for `toc`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TocOptions {
+    /// Headers up to this level are listed
+    pub depth: Int,
+    /// Produce an `OrderedList` instead of a `BulletList`
+    pub ordered: bool,
+    /// List headers with the class `unnumbered`
+    pub include_unnumbered: bool,
+}
+
+impl Default for TocOptions {
+    fn default() -> Self {
+        TocOptions {
+            depth: 3,
+            ordered: false,
+            include_unnumbered: true,
+        }
+    }
+}
+
+struct Entry {
+    level: Int,
+    item: Vec<Inline>,
+}
+
+/// Header text as shown in the table of contents: links are unwrapped,
+/// footnotes dropped and the section number (if any) is prefixed
+fn toc_text(id: &str, kvs: &[(String, String)], title: &[Inline]) -> Vec<Inline> {
+    struct Clean;
+    impl MutVisitor for Clean {
+        fn visit_vec_inline(&mut self, vec_inline: &mut Vec<Inline>) {
+            vec_inline.retain(|inline| !matches!(inline, Inline::Note(_)));
+            let mut i = 0;
+            while i < vec_inline.len() {
+                if let Inline::Link(_, ref mut content, _) = vec_inline[i] {
+                    let content = std::mem::take(content);
+                    let len = content.len();
+                    vec_inline.splice(i..=i, content);
+                    i += len;
+                } else {
+                    i += 1;
+                }
+            }
+            self.walk_vec_inline(vec_inline)
+        }
+    }
+    let mut text = title.to_vec();
+    // `make_sections` already put the number into the text
+    strip_section_number(&mut text);
+    Clean.visit_vec_inline(&mut text);
+    if let Some((_, number)) = kvs.iter().find(|(k, _)| k == "number") {
+        let span_attr = (
+            String::new(),
+            vec!["toc-section-number".to_string()],
+            vec![],
+        );
+        text.splice(
+            0..0,
+            vec![
+                Inline::Span(span_attr, vec![Inline::Str(number.clone())]),
+                Inline::Space,
+            ],
+        );
+    }
+    if id.is_empty() {
+        return text;
+    }
+    let attr = (format!("toc-{}", id), vec![], vec![]);
+    vec![Inline::Link(
+        attr,
+        text,
+        (format!("#{}", id), String::new()),
+    )]
+}
+
+/// Collects headers from the top level and from `Div`s, which is where
+/// `make_sections` puts them
+fn collect(blocks: &[Block], options: &TocOptions, entries: &mut Vec<Entry>) {
+    for block in blocks {
+        match *block {
+            Block::Header(level, (ref id, ref classes, ref kvs), ref title) => {
+                if level > options.depth
+                    || classes.iter().any(|c| c == "unlisted")
+                    || (!options.include_unnumbered && classes.iter().any(|c| c == "unnumbered"))
+                {
+                    continue;
+                }
+                entries.push(Entry {
+                    level,
+                    item: toc_text(id, kvs, title),
+                });
+            }
+            Block::Div((ref id, ref classes, _), ref inner)
+                if classes.iter().any(|c| c == "section") =>
+            {
+                // the header of a section has its identifier on the `Div`
+                match inner.split_first() {
+                    Some((Block::Header(level, (hid, hclasses, kvs), title), rest))
+                        if hid.is_empty() =>
+                    {
+                        let header = Block::Header(
+                            *level,
+                            (id.clone(), hclasses.clone(), kvs.clone()),
+                            title.clone(),
+                        );
+                        collect(std::slice::from_ref(&header), options, entries);
+                        collect(rest, options, entries);
+                    }
+                    _ => collect(inner, options, entries),
+                }
+            }
+            Block::Div(_, ref inner) => collect(inner, options, entries),
+            _ => {}
+        }
+    }
+}
+
+fn build(entries: &[Entry], ordered: bool) -> Block {
+    let mut items = Vec::new();
+    let mut rest = entries;
+    while let Some((entry, tail)) = rest.split_first() {
+        let end = tail
+            .iter()
+            .position(|e| e.level <= entry.level)
+            .unwrap_or(tail.len());
+        let (children, tail) = tail.split_at(end);
+        rest = tail;
+        let mut item = Vec::new();
+        if !entry.item.is_empty() {
+            item.push(Block::Plain(entry.item.clone()));
+        }
+        if !children.is_empty() {
+            item.push(build(children, ordered));
+        }
+        items.push(item);
+    }
+    if ordered {
+        let attrs = (
+            1,
+            ListNumberStyle::DefaultStyle,
+            ListNumberDelim::DefaultDelim,
+        );
+        Block::OrderedList(attrs, items)
+    } else {
+        Block::BulletList(items)
+    }
+}
+
+/// Builds a table of contents as a nested list of links to the headers.
+///
+/// Headers without an identifier get the one pandoc would generate, see
+/// `Pandoc::assign_header_ids`. Headers with the class `unlisted` are
+/// never listed.
+pub fn toc(pandoc: &Pandoc, options: TocOptions) -> Block {
+    let mut pandoc = pandoc.clone();
+    let mut ids = IdGenerator::default();
+    ids.seed(&pandoc);
+    pandoc.assign_header_ids(&mut ids);
+    let mut entries = Vec::new();
+    collect(&pandoc.blocks, &options, &mut entries);
+    build(&entries, options.ordered)
+}
+
+fn is_placeholder(block: &Block) -> bool {
+    match *block {
+        Block::Div((_, ref classes, _), _) => classes.iter().any(|c| c == "toc"),
+        Block::Para(ref inlines) => stringify(inlines).trim() == "[[TOC]]",
+        _ => false,
+    }
+}
+
+impl Pandoc {
+    /// Gives every header without an identifier a fresh one generated by
+    /// `ids`, in document order. Seed `ids` with the document first.
+    pub fn assign_header_ids(&mut self, ids: &mut IdGenerator) {
+        struct Assign<'a>(&'a mut IdGenerator);
+        impl MutVisitor for Assign<'_> {
+            fn visit_block(&mut self, block: &mut Block) {
+                match *block {
+                    Block::Header(_, (ref mut id, _, _), ref title) if id.is_empty() => {
+                        *id = self.0.generate(title);
+                    }
+                    // the header of a section has its identifier on the `Div`
+                    Block::Div((ref id, ref classes, _), ref mut inner)
+                        if !id.is_empty() && classes.iter().any(|c| c == "section") =>
+                    {
+                        if let Some((Block::Header(..), rest)) = inner.split_first_mut() {
+                            rest.iter_mut().for_each(|block| self.visit_block(block));
+                            return;
+                        }
+                    }
+                    _ => {}
+                }
+                self.walk_block(block)
+            }
+        }
+        Assign(ids).visit_vec_block(&mut self.blocks);
+    }
+
+    /// Replaces the contents of `Div`s with the class `toc` and paragraphs
+    /// consisting of `[[TOC]]` with a table of contents. Headers without an
+    /// identifier get one so the links resolve.
+    ///
+    /// Returns whether a placeholder was found.
+    pub fn insert_toc(&mut self, options: TocOptions) -> bool {
+        struct Find(bool);
+        impl Visitor for Find {
+            fn visit_block(&mut self, block: &Block) {
+                self.0 |= is_placeholder(block);
+                self.walk_block(block)
+            }
+        }
+        let mut find = Find(false);
+        find.visit_vec_block(&self.blocks);
+        if !find.0 {
+            return false;
+        }
+        let mut ids = IdGenerator::default();
+        ids.seed(self);
+        self.assign_header_ids(&mut ids);
+        let list = toc(self, options);
+
+        struct Replace(Block);
+        impl MutVisitor for Replace {
+            fn visit_block(&mut self, block: &mut Block) {
+                if is_placeholder(block) {
+                    match *block {
+                        Block::Div(_, ref mut content) => *content = vec![self.0.clone()],
+                        _ => *block = self.0.clone(),
+                    }
+                    return;
+                }
+                self.walk_block(block)
+            }
+        }
+        Replace(list).visit_vec_block(&mut self.blocks);
+        true
+    }
+}
@@ -0,0 +1,488 @@
+use super::*;
+use std::fmt;
+use std::num::ParseIntError;
+use std::str::FromStr;
+
+/// The position of a node in the tree view: the indices of the children
+/// leading to it, starting with the index into the document's blocks
+///
+/// Written like `2.0.1`, the second child of the first child of the third
+/// block. The children of a node are the ones the tree view lists below it,
+/// so list items, table rows and cells, captions and definition list terms
+/// count as nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct NodePath(pub Vec<usize>);
+
+impl FromStr for NodePath {
+    type Err = ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Ok(NodePath::default());
+        }
+        s.split('.')
+            .map(str::parse)
+            .collect::<Result<_, _>>()
+            .map(NodePath)
+    }
+}
+
+impl fmt::Display for NodePath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indices: Vec<String> = self.0.iter().map(usize::to_string).collect();
+        write!(f, "{}", indices.join("."))
+    }
+}
+
+impl From<Vec<usize>> for NodePath {
+    fn from(indices: Vec<usize>) -> Self {
+        NodePath(indices)
+    }
+}
+
+/// Options for `Pandoc::tree` and `Pandoc::to_dot`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TreeOptions {
+    /// Levels shown below the root, deeper nodes are replaced by `…`
+    pub depth: Option<usize>,
+    /// Show only this node and its descendants instead of the document
+    pub focus: Option<NodePath>,
+    /// Strings longer than this many characters are truncated
+    pub max_string: usize,
+    /// Highlight names, strings and attributes with ANSI colors (tree view
+    /// only)
+    pub color: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        TreeOptions {
+            depth: None,
+            focus: None,
+            max_string: 40,
+            color: false,
+        }
+    }
+}
+
+/// A part of a node's label
+enum Part {
+    /// Something like a list style or header level
+    Plain(String),
+    /// Text from the document, quoted
+    Str(String),
+    /// Attributes like `#id .class key="value"`
+    Attr(String),
+}
+
+/// A node of the tree view
+struct Node {
+    name: &'static str,
+    parts: Vec<Part>,
+    children: Vec<Node>,
+}
+
+impl Node {
+    fn new(name: &'static str) -> Self {
+        Node {
+            name,
+            parts: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn plain(mut self, text: impl ToString) -> Self {
+        self.parts.push(Part::Plain(text.to_string()));
+        self
+    }
+
+    fn str(mut self, text: &str) -> Self {
+        self.parts.push(Part::Str(text.to_string()));
+        self
+    }
+
+    fn attr(mut self, attr: &Attr) -> Self {
+        let (ref id, ref classes, ref kvs) = *attr;
+        let mut parts = Vec::new();
+        if !id.is_empty() {
+            parts.push(format!("#{}", id));
+        }
+        parts.extend(classes.iter().map(|class| format!(".{}", class)));
+        parts.extend(
+            kvs.iter()
+                .map(|(key, value)| format!("{}={:?}", key, value)),
+        );
+        if !parts.is_empty() {
+            self.parts.push(Part::Attr(parts.join(" ")));
+        }
+        self
+    }
+
+    fn blocks(mut self, blocks: &[Block]) -> Self {
+        self.children.extend(blocks.iter().map(block));
+        self
+    }
+
+    fn inlines(mut self, inlines: &[Inline]) -> Self {
+        self.children.extend(inlines.iter().map(inline));
+        self
+    }
+
+    fn child(mut self, child: Node) -> Self {
+        self.children.push(child);
+        self
+    }
+
+    fn find(&self, path: &[usize]) -> Option<&Node> {
+        match path.split_first() {
+            None => Some(self),
+            Some((&i, rest)) => self.children.get(i)?.find(rest),
+        }
+    }
+
+    /// The label, with strings truncated and optionally colored
+    fn label(&self, options: &TreeOptions, color: bool) -> String {
+        let paint = |code: &str, text: String| {
+            if color {
+                format!("\x1b[{}m{}\x1b[0m", code, text)
+            } else {
+                text
+            }
+        };
+        let mut label = paint("1", self.name.to_string());
+        for part in &self.parts {
+            label.push(' ');
+            label.push_str(&match *part {
+                Part::Plain(ref text) => text.clone(),
+                Part::Str(ref text) => paint("32", truncate(text, options.max_string)),
+                Part::Attr(ref text) => paint("33", text.clone()),
+            });
+        }
+        label
+    }
+}
+
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return format!("{:?}", text);
+    }
+    let text: String = text.chars().take(max).collect();
+    format!("{:?}…", text)
+}
+
+fn caption(caption: &Caption) -> Node {
+    let node = Node::new("Caption");
+    let node = match caption.0 {
+        Some(ref short) => node.child(Node::new("ShortCaption").inlines(short)),
+        None => node,
+    };
+    node.blocks(&caption.1)
+}
+
+fn rows(mut node: Node, rows: &[Row]) -> Node {
+    for row in rows {
+        let mut row_node = Node::new("Row").attr(&row.0);
+        for &(ref attr, align, rowspan, colspan, ref blocks) in &row.1 {
+            let mut cell = Node::new("Cell").attr(attr);
+            if align != Alignment::AlignDefault {
+                cell = cell.plain(format!("{:?}", align));
+            }
+            if rowspan > 1 || colspan > 1 {
+                cell = cell.plain(format!("{}x{}", rowspan, colspan));
+            }
+            row_node = row_node.child(cell.blocks(blocks));
+        }
+        node = node.child(row_node);
+    }
+    node
+}
+
+fn block(block: &Block) -> Node {
+    match *block {
+        Block::Plain(ref inlines) => Node::new("Plain").inlines(inlines),
+        Block::Para(ref inlines) => Node::new("Para").inlines(inlines),
+        Block::LineBlock(ref lines) => {
+            let mut node = Node::new("LineBlock");
+            for line in lines {
+                node = node.child(Node::new("Line").inlines(line));
+            }
+            node
+        }
+        Block::CodeBlock(ref attr, ref code) => Node::new("CodeBlock").attr(attr).str(code),
+        Block::RawBlock(ref format, ref text) => Node::new("RawBlock").plain(&format.0).str(text),
+        Block::BlockQuote(ref blocks) => Node::new("BlockQuote").blocks(blocks),
+        Block::OrderedList((start, style, delim), ref items) => {
+            let mut node = Node::new("OrderedList")
+                .plain(start)
+                .plain(format!("{:?}", style))
+                .plain(format!("{:?}", delim));
+            for item in items {
+                node = node.child(Node::new("Item").blocks(item));
+            }
+            node
+        }
+        Block::BulletList(ref items) => {
+            let mut node = Node::new("BulletList");
+            for item in items {
+                node = node.child(Node::new("Item").blocks(item));
+            }
+            node
+        }
+        Block::DefinitionList(ref items) => {
+            let mut node = Node::new("DefinitionList");
+            for (term, definitions) in items {
+                let mut item = Node::new("Item").child(Node::new("Term").inlines(term));
+                for definition in definitions {
+                    item = item.child(Node::new("Definition").blocks(definition));
+                }
+                node = node.child(item);
+            }
+            node
+        }
+        Block::Header(level, ref attr, ref inlines) => {
+            Node::new("Header").plain(level).attr(attr).inlines(inlines)
+        }
+        Block::HorizontalRule => Node::new("HorizontalRule"),
+        Block::Table(ref attr, ref caption_, ref colspecs, ref head, ref bodies, ref foot) => {
+            let columns: Vec<String> = colspecs
+                .iter()
+                .map(|&(align, width)| match width {
+                    ColWidth::ColWidth(width) => format!("{:?} {}", align, width),
+                    ColWidth::ColWidthDefault => format!("{:?}", align),
+                })
+                .collect();
+            let mut node = Node::new("Table")
+                .attr(attr)
+                .plain(format!("[{}]", columns.join(", ")))
+                .child(caption(caption_))
+                .child(rows(Node::new("TableHead").attr(&head.0), &head.1));
+            for &(ref attr, row_head_columns, ref head, ref body) in bodies {
+                let body_node = Node::new("TableBody")
+                    .attr(attr)
+                    .plain(row_head_columns)
+                    .child(rows(Node::new("Head"), head))
+                    .child(rows(Node::new("Body"), body));
+                node = node.child(body_node);
+            }
+            node.child(rows(Node::new("TableFoot").attr(&foot.0), &foot.1))
+        }
+        Block::Figure(ref attr, ref caption_, ref blocks) => Node::new("Figure")
+            .attr(attr)
+            .child(caption(caption_))
+            .blocks(blocks),
+        Block::Div(ref attr, ref blocks) => Node::new("Div").attr(attr).blocks(blocks),
+        Block::Null => Node::new("Null"),
+    }
+}
+
+fn inline(inline: &Inline) -> Node {
+    match *inline {
+        Inline::Str(ref text) => Node::new("Str").str(text),
+        Inline::Emph(ref inlines) => Node::new("Emph").inlines(inlines),
+        Inline::Underline(ref inlines) => Node::new("Underline").inlines(inlines),
+        Inline::Strong(ref inlines) => Node::new("Strong").inlines(inlines),
+        Inline::Strikeout(ref inlines) => Node::new("Strikeout").inlines(inlines),
+        Inline::Superscript(ref inlines) => Node::new("Superscript").inlines(inlines),
+        Inline::Subscript(ref inlines) => Node::new("Subscript").inlines(inlines),
+        Inline::SmallCaps(ref inlines) => Node::new("SmallCaps").inlines(inlines),
+        Inline::Quoted(quote, ref inlines) => Node::new("Quoted")
+            .plain(format!("{:?}", quote))
+            .inlines(inlines),
+        Inline::Cite(ref citations, ref inlines) => {
+            let ids: Vec<String> = citations
+                .iter()
+                .map(|c| format!("@{}", c.citationId))
+                .collect();
+            Node::new("Cite").plain(ids.join(" ")).inlines(inlines)
+        }
+        Inline::Code(ref attr, ref code) => Node::new("Code").attr(attr).str(code),
+        Inline::Space => Node::new("Space"),
+        Inline::SoftBreak => Node::new("SoftBreak"),
+        Inline::LineBreak => Node::new("LineBreak"),
+        Inline::Math(kind, ref tex) => Node::new("Math").plain(format!("{:?}", kind)).str(tex),
+        Inline::RawInline(ref format, ref text) => {
+            Node::new("RawInline").plain(&format.0).str(text)
+        }
+        Inline::Link(ref attr, ref inlines, (ref url, ref title)) => {
+            let node = Node::new("Link").attr(attr).str(url);
+            let node = if title.is_empty() {
+                node
+            } else {
+                node.str(title)
+            };
+            node.inlines(inlines)
+        }
+        Inline::Image(ref attr, ref inlines, (ref url, ref title)) => {
+            let node = Node::new("Image").attr(attr).str(url);
+            let node = if title.is_empty() {
+                node
+            } else {
+                node.str(title)
+            };
+            node.inlines(inlines)
+        }
+        Inline::Note(ref blocks) => Node::new("Note").blocks(blocks),
+        Inline::Span(ref attr, ref inlines) => Node::new("Span").attr(attr).inlines(inlines),
+    }
+}
+
+fn meta_entry(key: &str, value: &MetaValue) -> Node {
+    let mut node = meta_value("Entry", value);
+    node.parts.insert(0, Part::Str(key.to_string()));
+    node
+}
+
+fn meta_value(name: &'static str, value: &MetaValue) -> Node {
+    match *value {
+        MetaValue::MetaMap(ref map) => {
+            let mut node = Node::new(name).plain("MetaMap");
+            for (key, value) in map {
+                node = node.child(meta_entry(key, value));
+            }
+            node
+        }
+        MetaValue::MetaList(ref list) => {
+            let mut node = Node::new(name).plain("MetaList");
+            for value in list {
+                node = node.child(meta_value("Item", value));
+            }
+            node
+        }
+        MetaValue::MetaBool(b) => Node::new(name).plain("MetaBool").plain(b),
+        MetaValue::MetaString(ref s) => Node::new(name).plain("MetaString").str(s),
+        MetaValue::MetaInlines(ref inlines) => {
+            Node::new(name).plain("MetaInlines").inlines(inlines)
+        }
+        MetaValue::MetaBlocks(ref blocks) => Node::new(name).plain("MetaBlocks").blocks(blocks),
+    }
+}
+
+impl Pandoc {
+    /// The tree of the document, with its metadata unless the options focus
+    /// on a node, and the node to show
+    fn tree_node(&self, options: &TreeOptions) -> Node {
+        let mut root = Node::new("Pandoc");
+        if options.focus.is_none() && !self.meta.is_empty() {
+            let mut meta = Node::new("Meta");
+            for (key, value) in &self.meta {
+                meta = meta.child(meta_entry(key, value));
+            }
+            root = root.child(meta);
+        }
+        root.blocks(&self.blocks)
+    }
+
+    /// An indented tree view of the document for debugging, like
+    ///
+    /// ```text
+    /// Pandoc
+    /// └─ Para
+    ///    ├─ Str "Hello"
+    ///    ├─ Space
+    ///    └─ Emph
+    ///       └─ Str "world"
+    /// ```
+    ///
+    /// Empty if the focused node doesn't exist.
+    pub fn tree(&self, options: &TreeOptions) -> String {
+        let root = self.tree_node(options);
+        let root = match options.focus {
+            Some(ref path) => match root.find(&path.0) {
+                Some(node) => node,
+                None => return String::new(),
+            },
+            None => &root,
+        };
+        let mut out = String::new();
+        draw(root, options, "", "", 0, &mut out);
+        out
+    }
+
+    /// A Graphviz `digraph` of the document's nodes, empty if the focused
+    /// node doesn't exist
+    pub fn to_dot(&self, options: &TreeOptions) -> String {
+        let root = self.tree_node(options);
+        let root = match options.focus {
+            Some(ref path) => match root.find(&path.0) {
+                Some(node) => node,
+                None => return String::new(),
+            },
+            None => &root,
+        };
+        let mut out =
+            String::from("digraph pandoc {\n  node [shape=box, fontname=\"monospace\"];\n");
+        let mut count = 0;
+        dot(root, options, 0, &mut count, &mut out);
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Appends the node's line and the lines of its children, `first` and
+/// `rest` are the prefixes of the node's first and following lines
+fn draw(
+    node: &Node,
+    options: &TreeOptions,
+    first: &str,
+    rest: &str,
+    depth: usize,
+    out: &mut String,
+) {
+    let branch = |text: &str| {
+        if options.color && !text.is_empty() {
+            format!("\x1b[2m{}\x1b[0m", text)
+        } else {
+            text.to_string()
+        }
+    };
+    out.push_str(&branch(first));
+    out.push_str(&node.label(options, options.color));
+    let hidden = options.depth.is_some_and(|max| depth >= max);
+    if hidden && !node.children.is_empty() {
+        out.push_str(" …\n");
+        return;
+    }
+    out.push('\n');
+    for (i, child) in node.children.iter().enumerate() {
+        let last = i + 1 == node.children.len();
+        let (first, next) = if last {
+            ("└─ ", "   ")
+        } else {
+            ("├─ ", "│  ")
+        };
+        draw(
+            child,
+            options,
+            &format!("{}{}", rest, first),
+            &format!("{}{}", rest, next),
+            depth + 1,
+            out,
+        );
+    }
+}
+
+/// Appends the node and the edges to its children, returns its name
+fn dot(
+    node: &Node,
+    options: &TreeOptions,
+    depth: usize,
+    count: &mut usize,
+    out: &mut String,
+) -> String {
+    let name = format!("n{}", count);
+    *count += 1;
+    let mut label = node.label(options, false);
+    let hidden = options.depth.is_some_and(|max| depth >= max);
+    if hidden && !node.children.is_empty() {
+        label.push_str(" …");
+    }
+    let label = label.replace('\\', "\\\\").replace('"', "\\\"");
+    out.push_str(&format!("  {} [label=\"{}\"];\n", name, label));
+    if !hidden {
+        for child in &node.children {
+            let child = dot(child, options, depth + 1, count, out);
+            out.push_str(&format!("  {} -> {};\n", name, child));
+        }
+    }
+    name
+}
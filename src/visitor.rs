@@ -190,3 +190,194 @@ pub trait MutVisitor {
         }
     }
 }
+
+pub trait Visitor {
+    fn visit_block(&mut self, block: &Block) {
+        self.walk_block(block)
+    }
+    fn visit_attr(&mut self, attr: &Attr) {
+        self.walk_attr(attr)
+    }
+    fn visit_inline(&mut self, inline: &Inline) {
+        self.walk_inline(inline)
+    }
+    fn visit_meta(&mut self, _key: &str, meta: &MetaValue) {
+        self.walk_meta(meta)
+    }
+    fn visit_vec_block(&mut self, vec_block: &Vec<Block>) {
+        self.walk_vec_block(vec_block)
+    }
+    fn visit_vec_inline(&mut self, vec_inline: &Vec<Inline>) {
+        self.walk_vec_inline(vec_inline)
+    }
+    fn visit_rows(&mut self, rows: &Vec<Row>) {
+        self.walk_rows(rows)
+    }
+    fn walk_meta(&mut self, meta: &MetaValue) {
+        use MetaValue::*;
+        match *meta {
+            MetaMap(ref c) => {
+                for (key, meta) in c {
+                    self.visit_meta(key, meta);
+                }
+            }
+            MetaList(ref c) => {
+                for meta in c {
+                    self.walk_meta(meta);
+                }
+            }
+            MetaBool(_) => {}
+            MetaString(_) => {}
+            MetaInlines(ref v_inline) => {
+                self.visit_vec_inline(v_inline);
+            }
+            MetaBlocks(ref v_block) => {
+                self.visit_vec_block(v_block);
+            }
+        }
+    }
+    fn walk_pandoc(&mut self, pandoc: &Pandoc) {
+        for (key, meta) in &pandoc.meta {
+            self.visit_meta(key, meta);
+        }
+        self.visit_vec_block(&pandoc.blocks);
+    }
+    fn walk_block(&mut self, block: &Block) {
+        use Block::*;
+        match *block {
+            Plain(ref vec_inline) | Para(ref vec_inline) => {
+                self.visit_vec_inline(vec_inline);
+            }
+            LineBlock(ref vec_vec_inline) => {
+                for vec_inline in vec_vec_inline {
+                    self.visit_vec_inline(vec_inline);
+                }
+            }
+            CodeBlock(ref attr, _) => self.visit_attr(attr),
+            RawBlock { .. } => {}
+            BlockQuote(ref vec_block) => {
+                self.visit_vec_block(vec_block);
+            }
+            OrderedList(_, ref vec_vec_block) | BulletList(ref vec_vec_block) => {
+                for vec_block in vec_vec_block {
+                    self.visit_vec_block(vec_block);
+                }
+            }
+            DefinitionList(ref c) => {
+                for def in c {
+                    self.visit_vec_inline(&def.0);
+                    for vec_block in &def.1 {
+                        self.visit_vec_block(vec_block);
+                    }
+                }
+            }
+            Figure(ref attr, ref caption, ref vec_block) => {
+                self.visit_attr(attr);
+                {
+                    let (short, caption) = caption;
+                    if let Some(shortcaption) = short {
+                        self.visit_vec_inline(shortcaption);
+                    }
+
+                    self.visit_vec_block(caption);
+                }
+                self.visit_vec_block(vec_block);
+            }
+            Header(_, ref attr, ref vec_inline) => {
+                self.visit_attr(attr);
+                self.visit_vec_inline(vec_inline);
+            }
+            HorizontalRule => {}
+            Table(ref attr, ref caption, _, ref head, ref bodies, ref foot) => {
+                self.visit_attr(attr);
+                {
+                    let (short, caption) = caption;
+                    if let Some(shortcaption) = short {
+                        self.visit_vec_inline(shortcaption);
+                    }
+
+                    self.visit_vec_block(caption);
+                }
+                {
+                    let (attr, rows) = head;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows);
+                }
+                for body in bodies {
+                    let (attr, _, rows_h, rows) = body;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows_h);
+                    self.visit_rows(rows);
+                }
+                {
+                    let (attr, rows) = foot;
+                    self.visit_attr(attr);
+                    self.visit_rows(rows);
+                }
+            }
+            Div(ref attr, ref vec_block) => {
+                self.visit_attr(attr);
+                self.visit_vec_block(vec_block);
+            }
+            Null => {}
+        }
+    }
+    fn walk_attr(&mut self, _attr: &Attr) {}
+    fn walk_inline(&mut self, inline: &Inline) {
+        use Inline::*;
+        match *inline {
+            Str { .. } => {}
+            Emph(ref c)
+            | Strong(ref c)
+            | Underline(ref c)
+            | Strikeout(ref c)
+            | Superscript(ref c)
+            | Subscript(ref c)
+            | SmallCaps(ref c)
+            | Quoted(_, ref c) => {
+                self.visit_vec_inline(c);
+            }
+            Cite(ref v_cite, ref v_inl) => {
+                for cite in v_cite {
+                    self.visit_vec_inline(&cite.citationPrefix);
+                    self.visit_vec_inline(&cite.citationSuffix);
+                }
+                self.visit_vec_inline(v_inl);
+            }
+            Code(ref attr, _) => self.visit_attr(attr),
+            Space => {}
+            SoftBreak => {}
+            LineBreak => {}
+            Math { .. } => {}
+            RawInline { .. } => {}
+            Link(ref attr, ref v_inline, _)
+            | Image(ref attr, ref v_inline, _)
+            | Span(ref attr, ref v_inline) => {
+                self.visit_attr(attr);
+                self.visit_vec_inline(v_inline);
+            }
+            Note(ref c) => {
+                self.visit_vec_block(c);
+            }
+        }
+    }
+    fn walk_rows(&mut self, rows: &Vec<Row>) {
+        for (attr, cells) in rows {
+            self.visit_attr(attr);
+            for (cell_attr, _, _, _, content) in cells {
+                self.visit_attr(cell_attr);
+                self.visit_vec_block(content);
+            }
+        }
+    }
+    fn walk_vec_block(&mut self, vec_block: &Vec<Block>) {
+        for block in vec_block {
+            self.visit_block(block);
+        }
+    }
+    fn walk_vec_inline(&mut self, vec_inline: &Vec<Inline>) {
+        for inline in vec_inline {
+            self.visit_inline(inline);
+        }
+    }
+}
@@ -0,0 +1,431 @@
+//! DocBook 5 XML, like pandoc's `docbook5` format
+//!
+//! Headers and the blocks following them become nested `<section>`s,
+//! tables become CALS tables.
+
+use super::*;
+
+/// Renders the document as an `<article>` with the title, subtitle,
+/// authors and date from the metadata in its `<info>`
+pub fn write(pandoc: &Pandoc) -> String {
+    let mut writer = Writer;
+    let mut xml = String::from(
+        "<?xml version=\"1.0\" encoding=\"utf-8\" ?>\n\
+         <article xmlns=\"http://docbook.org/ns/docbook\" \
+         xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"5.0\">\n",
+    );
+    let mut info = String::new();
+    for (key, element) in [("title", "title"), ("subtitle", "subtitle")] {
+        if let Some(value) = pandoc.meta.get(key) {
+            info.push_str(&format!(
+                "<{0}>{1}</{0}>\n",
+                element,
+                writer.meta_inlines(value)
+            ));
+        }
+    }
+    let authors = match pandoc.meta.get("author") {
+        Some(MetaValue::MetaList(authors)) => authors.iter().collect(),
+        Some(author) => vec![author],
+        None => vec![],
+    };
+    for author in authors {
+        info.push_str(&format!(
+            "<author>\n<personname>{}</personname>\n</author>\n",
+            writer.meta_inlines(author)
+        ));
+    }
+    if let Some(date) = pandoc.meta.get("date") {
+        info.push_str(&format!("<date>{}</date>\n", writer.meta_inlines(date)));
+    }
+    if !info.is_empty() {
+        xml.push_str(&format!("<info>\n{}</info>\n", info));
+    }
+    xml.push_str(&write_blocks(&pandoc.blocks));
+    xml.push_str("</article>\n");
+    xml
+}
+
+/// Renders blocks as DocBook elements
+pub fn write_blocks(blocks: &[Block]) -> String {
+    let blocks = make_sections(blocks, MakeSectionsOptions::default());
+    let mut xml = Writer.blocks(&blocks);
+    if !xml.is_empty() {
+        xml.push('\n');
+    }
+    xml
+}
+
+/// Escapes text for XML, dropping the control characters it can't hold
+fn escape(text: &str) -> String {
+    let text: String = text
+        .chars()
+        .filter(|c| !c.is_control() || matches!(c, '\t' | '\n' | '\r'))
+        .collect();
+    escape_html(&text)
+}
+
+/// An `xml:id` attribute with a leading space, or nothing
+fn id_attr(id: &str) -> String {
+    if id.is_empty() {
+        String::new()
+    } else {
+        format!(" xml:id=\"{}\"", escape(id))
+    }
+}
+
+fn is_docbook(format: &Format) -> bool {
+    format.base_name().starts_with("docbook")
+}
+
+fn image_data(url: &str) -> String {
+    format!(
+        "<imageobject><imagedata fileref=\"{}\" /></imageobject>",
+        escape(url)
+    )
+}
+
+struct Writer;
+
+impl Writer {
+    fn blocks(&mut self, blocks: &[Block]) -> String {
+        let blocks: Vec<String> = blocks
+            .iter()
+            .map(|block| self.block(block))
+            .filter(|block| !block.is_empty())
+            .collect();
+        blocks.join("\n")
+    }
+
+    fn block(&mut self, block: &Block) -> String {
+        match *block {
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => {
+                format!("<para>{}</para>", self.inlines(inlines))
+            }
+            Block::LineBlock(ref lines) => {
+                let lines: Vec<String> = lines.iter().map(|line| self.inlines(line)).collect();
+                format!("<literallayout>{}</literallayout>", lines.join("\n"))
+            }
+            Block::CodeBlock((ref id, ref classes, _), ref code) => {
+                let language = match classes.first() {
+                    Some(language) => format!(" language=\"{}\"", escape(language)),
+                    None => String::new(),
+                };
+                format!(
+                    "<programlisting{}{}>{}</programlisting>",
+                    id_attr(id),
+                    language,
+                    escape(code)
+                )
+            }
+            Block::RawBlock(ref format, ref text) if is_docbook(format) => text.clone(),
+            Block::RawBlock(..) | Block::Null | Block::HorizontalRule => String::new(),
+            Block::BlockQuote(ref blocks) => {
+                format!("<blockquote>\n{}\n</blockquote>", self.blocks(blocks))
+            }
+            Block::OrderedList((start, style, _), ref items) => {
+                let mut open = String::from("<orderedlist");
+                let numeration = match style {
+                    ListNumberStyle::LowerRoman => "lowerroman",
+                    ListNumberStyle::UpperRoman => "upperroman",
+                    ListNumberStyle::LowerAlpha => "loweralpha",
+                    ListNumberStyle::UpperAlpha => "upperalpha",
+                    _ => "arabic",
+                };
+                open.push_str(&format!(" numeration=\"{}\"", numeration));
+                if start != 1 {
+                    open.push_str(&format!(" startingnumber=\"{}\"", start));
+                }
+                format!("{}{}", open, self.items(items, "orderedlist"))
+            }
+            Block::BulletList(ref items) => {
+                format!("<itemizedlist{}", self.items(items, "itemizedlist"))
+            }
+            Block::DefinitionList(ref items) => {
+                let mut xml = String::from("<variablelist>\n");
+                for (term, definitions) in items {
+                    xml.push_str("<varlistentry>\n");
+                    xml.push_str(&format!("<term>{}</term>\n", self.inlines(term)));
+                    let definitions: Vec<String> =
+                        definitions.iter().map(|d| self.blocks(d)).collect();
+                    xml.push_str(&format!(
+                        "<listitem>\n{}\n</listitem>\n",
+                        definitions.join("\n")
+                    ));
+                    xml.push_str("</varlistentry>\n");
+                }
+                xml.push_str("</variablelist>");
+                xml
+            }
+            Block::Figure((ref id, _, _), ref caption, ref blocks) => {
+                let content: Vec<String> = blocks
+                    .iter()
+                    .map(|block| match *block {
+                        Block::Plain(ref inlines) | Block::Para(ref inlines)
+                            if matches!(inlines[..], [Inline::Image(..)]) =>
+                        {
+                            let Inline::Image(_, ref alt, (ref url, _)) = inlines[0] else {
+                                unreachable!()
+                            };
+                            let mut xml = format!("<mediaobject>\n{}\n", image_data(url));
+                            if !alt.is_empty() {
+                                xml.push_str(&format!(
+                                    "<textobject><phrase>{}</phrase></textobject>\n",
+                                    escape(&stringify(alt))
+                                ));
+                            }
+                            xml.push_str("</mediaobject>");
+                            xml
+                        }
+                        ref block => self.block(block),
+                    })
+                    .collect();
+                if caption.1.is_empty() {
+                    format!(
+                        "<informalfigure{}>\n{}\n</informalfigure>",
+                        id_attr(id),
+                        content.join("\n")
+                    )
+                } else {
+                    format!(
+                        "<figure{}>\n<title>{}</title>\n{}\n</figure>",
+                        id_attr(id),
+                        self.title(&caption.1),
+                        content.join("\n")
+                    )
+                }
+            }
+            Block::Header(level, (ref id, _, _), ref inlines) => {
+                // headers outside of sections, e.g. in lists
+                format!(
+                    "<bridgehead{} renderas=\"sect{}\">{}</bridgehead>",
+                    id_attr(id),
+                    level.clamp(1, 5),
+                    self.inlines(inlines)
+                )
+            }
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                self.table(&attr.0, caption, colspecs, head, bodies, foot)
+            }
+            Block::Div((ref id, ref classes, _), ref blocks) => match blocks.split_first() {
+                Some((Block::Header(_, _, title), rest))
+                    if classes.iter().any(|c| c == "section") =>
+                {
+                    let mut xml = format!(
+                        "<section{}>\n<title>{}</title>",
+                        id_attr(id),
+                        self.inlines(title)
+                    );
+                    let rest = self.blocks(rest);
+                    if !rest.is_empty() {
+                        xml.push('\n');
+                        xml.push_str(&rest);
+                    }
+                    xml.push_str("\n</section>");
+                    xml
+                }
+                _ if id.is_empty() => self.blocks(blocks),
+                _ => format!("<anchor{} />\n{}", id_attr(id), self.blocks(blocks)),
+            },
+        }
+    }
+
+    /// The rest of a list's opening tag, its items and the closing tag
+    fn items(&mut self, items: &[Vec<Block>], element: &str) -> String {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let mut xml = String::from(if tight {
+            " spacing=\"compact\">\n"
+        } else {
+            ">\n"
+        });
+        for item in items {
+            xml.push_str(&format!("<listitem>\n{}\n</listitem>\n", self.blocks(item)));
+        }
+        xml.push_str(&format!("</{}>", element));
+        xml
+    }
+
+    /// Blocks as the content of a `<title>`
+    fn title(&mut self, blocks: &[Block]) -> String {
+        let inlines: Vec<String> = blocks
+            .iter()
+            .filter_map(|block| match *block {
+                Block::Plain(ref inlines) | Block::Para(ref inlines) => Some(self.inlines(inlines)),
+                _ => None,
+            })
+            .collect();
+        inlines.join(" ")
+    }
+
+    fn table(
+        &mut self,
+        id: &str,
+        caption: &Caption,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> String {
+        let grid = Grid::new(colspecs, head, bodies, foot);
+        let mut xml = if caption.1.is_empty() {
+            format!("<informaltable{}>\n", id_attr(id))
+        } else {
+            format!(
+                "<table{}>\n<title>{}</title>\n",
+                id_attr(id),
+                self.title(&caption.1)
+            )
+        };
+        xml.push_str(&format!("<tgroup cols=\"{}\">\n", grid.cols));
+        for c in 0..grid.cols {
+            let mut colspec = format!("<colspec colname=\"c{}\"", c + 1);
+            match colspecs.get(c).map_or(Alignment::AlignDefault, |s| s.0) {
+                Alignment::AlignLeft => colspec.push_str(" align=\"left\""),
+                Alignment::AlignRight => colspec.push_str(" align=\"right\""),
+                Alignment::AlignCenter => colspec.push_str(" align=\"center\""),
+                Alignment::AlignDefault => {}
+            }
+            if let Some(&ColWidth::ColWidth(width)) = grid.widths.get(c) {
+                colspec.push_str(&format!(" colwidth=\"{}*\"", (width * 100.0).round()));
+            }
+            colspec.push_str(" />\n");
+            xml.push_str(&colspec);
+        }
+        let foot_start = grid.rows - grid.foot;
+        let parts = [
+            ("thead", 0..grid.head),
+            ("tfoot", foot_start..grid.rows),
+            ("tbody", grid.head..foot_start),
+        ];
+        for (element, rows) in parts {
+            if rows.is_empty() {
+                continue;
+            }
+            xml.push_str(&format!("<{}>\n", element));
+            for r in rows {
+                let mut cells: Vec<&GridCell> =
+                    grid.cells.iter().filter(|cell| cell.row == r).collect();
+                cells.sort_by_key(|cell| cell.col);
+                xml.push_str("<row>\n");
+                for cell in cells {
+                    let mut entry = String::from("<entry");
+                    if cell.cols > 1 {
+                        entry.push_str(&format!(
+                            " namest=\"c{}\" nameend=\"c{}\"",
+                            cell.col + 1,
+                            cell.col + cell.cols
+                        ));
+                    }
+                    if cell.rows > 1 {
+                        entry.push_str(&format!(" morerows=\"{}\"", cell.rows - 1));
+                    }
+                    let content = match cell.blocks {
+                        [Block::Plain(inlines)] => self.inlines(inlines),
+                        blocks => self.blocks(blocks),
+                    };
+                    xml.push_str(&format!("{}>{}</entry>\n", entry, content));
+                }
+                xml.push_str("</row>\n");
+            }
+            xml.push_str(&format!("</{}>\n", element));
+        }
+        xml.push_str("</tgroup>\n");
+        xml.push_str(if caption.1.is_empty() {
+            "</informaltable>"
+        } else {
+            "</table>"
+        });
+        xml
+    }
+
+    /// A metadata value as inline content
+    fn meta_inlines(&mut self, value: &MetaValue) -> String {
+        match *value {
+            MetaValue::MetaInlines(ref inlines) => self.inlines(inlines),
+            MetaValue::MetaBlocks(ref blocks) => self.title(blocks),
+            ref other => escape(&other.stringify()),
+        }
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) -> String {
+        inlines.iter().map(|inline| self.inline(inline)).collect()
+    }
+
+    fn element(&mut self, open: &str, close: &str, inlines: &[Inline]) -> String {
+        format!("<{}>{}</{}>", open, self.inlines(inlines), close)
+    }
+
+    fn inline(&mut self, inline: &Inline) -> String {
+        match *inline {
+            Inline::Str(ref text) => escape(text),
+            Inline::Emph(ref inlines) => self.element("emphasis", "emphasis", inlines),
+            Inline::Strong(ref inlines) => {
+                self.element("emphasis role=\"strong\"", "emphasis", inlines)
+            }
+            Inline::Underline(ref inlines) => {
+                self.element("emphasis role=\"underline\"", "emphasis", inlines)
+            }
+            Inline::Strikeout(ref inlines) => {
+                self.element("emphasis role=\"strikethrough\"", "emphasis", inlines)
+            }
+            Inline::SmallCaps(ref inlines) => {
+                self.element("emphasis role=\"smallcaps\"", "emphasis", inlines)
+            }
+            Inline::Superscript(ref inlines) => self.element("superscript", "superscript", inlines),
+            Inline::Subscript(ref inlines) => self.element("subscript", "subscript", inlines),
+            Inline::Quoted(_, ref inlines) => self.element("quote", "quote", inlines),
+            Inline::Cite(ref citations, ref inlines) if citations.is_empty() => {
+                self.inlines(inlines)
+            }
+            Inline::Cite(ref citations, _) => {
+                let citations: Vec<String> = citations
+                    .iter()
+                    .map(|c| {
+                        let mut cite = self.inlines(&c.citationPrefix);
+                        if !cite.is_empty() {
+                            cite.push(' ');
+                        }
+                        cite.push_str(&format!("<citation>{}</citation>", escape(&c.citationId)));
+                        cite.push_str(&self.inlines(&c.citationSuffix));
+                        cite
+                    })
+                    .collect();
+                citations.join("; ")
+            }
+            Inline::Code(_, ref code) => format!("<literal>{}</literal>", escape(code)),
+            Inline::Space | Inline::SoftBreak => " ".to_string(),
+            Inline::LineBreak => "\n".to_string(),
+            Inline::Math(MathType::InlineMath, ref tex) => format!(
+                "<inlineequation><mathphrase>{}</mathphrase></inlineequation>",
+                escape(tex)
+            ),
+            Inline::Math(MathType::DisplayMath, ref tex) => format!(
+                "<informalequation><mathphrase>{}</mathphrase></informalequation>",
+                escape(tex)
+            ),
+            Inline::RawInline(ref format, ref text) if is_docbook(format) => text.clone(),
+            Inline::RawInline(..) => String::new(),
+            Inline::Link(_, ref inlines, (ref url, _)) => {
+                let target = match url.strip_prefix('#') {
+                    Some(id) => format!("linkend=\"{}\"", escape(id)),
+                    None => format!("xlink:href=\"{}\"", escape(url)),
+                };
+                format!("<link {}>{}</link>", target, self.inlines(inlines))
+            }
+            Inline::Image(_, _, (ref url, _)) => {
+                format!("<inlinemediaobject>{}</inlinemediaobject>", image_data(url))
+            }
+            Inline::Note(ref blocks) => format!("<footnote>\n{}\n</footnote>", self.blocks(blocks)),
+            Inline::Span((ref id, ref classes, _), ref inlines) => {
+                if id.is_empty() && classes.is_empty() {
+                    return self.inlines(inlines);
+                }
+                let mut open = format!("phrase{}", id_attr(id));
+                if !classes.is_empty() {
+                    open.push_str(&format!(" role=\"{}\"", escape(&classes.join(" "))));
+                }
+                self.element(&open, "phrase", inlines)
+            }
+        }
+    }
+}
@@ -0,0 +1,481 @@
+//! HTML5, like pandoc's `html` format
+//!
+//! Rendering of individual nodes can be replaced through `HtmlOverrides`:
+//!
+//! ```
+//! use pandoc_ast::writers::html::{HtmlOverrides, HtmlWriter};
+//! use pandoc_ast::Inline;
+//!
+//! struct ExternalLinks;
+//!
+//! impl HtmlOverrides for ExternalLinks {
+//!     fn inline(writer: &mut HtmlWriter<Self>, inline: &Inline) -> Option<String> {
+//!         match *inline {
+//!             Inline::Link(_, ref text, (ref url, _)) if url.starts_with("http") => {
+//!                 let text = writer.inlines(text);
+//!                 let url = writer.url(url);
+//!                 Some(format!("<a href=\"{}\" rel=\"external\">{}</a>", url, text))
+//!             }
+//!             _ => None,
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! Overrides that render attributes or text themselves can escape them
+//! with `html_attr` and `escape_html`.
+
+use super::*;
+pub use super::{escape_html, html_attr};
+
+/// Options for the HTML writer
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct HtmlOptions {
+    /// Prepended to all identifiers and to the targets of internal links,
+    /// so several documents can be put on one page
+    pub id_prefix: String,
+}
+
+/// Replaces the rendering of particular nodes. The methods return `None`
+/// to keep the default rendering; they get the writer to render children
+/// and to reach the overrides' own state through `writer.overrides`.
+pub trait HtmlOverrides: Sized {
+    fn block(writer: &mut HtmlWriter<Self>, block: &Block) -> Option<String> {
+        let _ = (writer, block);
+        None
+    }
+
+    fn inline(writer: &mut HtmlWriter<Self>, inline: &Inline) -> Option<String> {
+        let _ = (writer, inline);
+        None
+    }
+}
+
+/// Keeps the default rendering of all nodes
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoOverrides;
+
+impl HtmlOverrides for NoOverrides {}
+
+/// Renders a document's blocks and its notes as HTML
+pub fn write(pandoc: &Pandoc, options: &HtmlOptions) -> String {
+    HtmlWriter::new(options.clone(), NoOverrides).write(pandoc)
+}
+
+/// Renders blocks and the notes in them as HTML
+pub fn write_blocks(blocks: &[Block], options: &HtmlOptions) -> String {
+    let mut writer = HtmlWriter::new(options.clone(), NoOverrides);
+    let mut html = writer.blocks(blocks);
+    html.push_str(&writer.notes());
+    html
+}
+
+/// Renders nodes as HTML, asking the overrides `O` first. Notes are
+/// collected while rendering and written out by `notes`.
+pub struct HtmlWriter<O = NoOverrides> {
+    pub options: HtmlOptions,
+    pub overrides: O,
+    /// The rendered notes found so far
+    notes: Vec<String>,
+}
+
+fn is_html(format: &Format) -> bool {
+    format.matches(&Format::new("html"))
+}
+
+/// The attributes with `css` appended to their `style`, if any
+fn with_style(attr: &Attr, css: &str) -> Attr {
+    let mut attr = attr.clone();
+    if css.is_empty() {
+        return attr;
+    }
+    match attr.2.iter_mut().find(|(key, _)| key == "style") {
+        Some((_, style)) => {
+            let existing = style.trim_end().trim_end_matches(';');
+            *style = if existing.is_empty() {
+                css.to_string()
+            } else {
+                format!("{}; {}", existing, css)
+            };
+        }
+        None => attr.2.push(("style".to_string(), css.to_string())),
+    }
+    attr
+}
+
+impl<O: HtmlOverrides> HtmlWriter<O> {
+    pub fn new(options: HtmlOptions, overrides: O) -> Self {
+        HtmlWriter {
+            options,
+            overrides,
+            notes: Vec::new(),
+        }
+    }
+
+    /// Renders the blocks of a document followed by its notes
+    pub fn write(&mut self, pandoc: &Pandoc) -> String {
+        let mut html = self.blocks(&pandoc.blocks);
+        html.push_str(&self.notes());
+        html
+    }
+
+    /// The section listing the notes rendered so far, which are then
+    /// forgotten. Empty if there were none.
+    pub fn notes(&mut self) -> String {
+        if self.notes.is_empty() {
+            return String::new();
+        }
+        let prefix = escape_html(&self.options.id_prefix);
+        let mut html = format!(
+            "<section id=\"{}footnotes\" class=\"footnotes footnotes-end-of-document\" \
+             role=\"doc-endnotes\">\n<hr />\n<ol>\n",
+            prefix
+        );
+        for note in self.notes.drain(..) {
+            html.push_str(&note);
+            html.push('\n');
+        }
+        html.push_str("</ol>\n</section>\n");
+        html
+    }
+
+    /// Renders blocks, each followed by a newline
+    pub fn blocks(&mut self, blocks: &[Block]) -> String {
+        let mut html = String::new();
+        for block in blocks {
+            let block = self.block(block);
+            if !block.is_empty() {
+                html.push_str(&block);
+                html.push('\n');
+            }
+        }
+        html
+    }
+
+    pub fn block(&mut self, block: &Block) -> String {
+        match O::block(self, block) {
+            Some(html) => html,
+            None => self.default_block(block),
+        }
+    }
+
+    pub fn inlines(&mut self, inlines: &[Inline]) -> String {
+        inlines.iter().map(|inline| self.inline(inline)).collect()
+    }
+
+    pub fn inline(&mut self, inline: &Inline) -> String {
+        match O::inline(self, inline) {
+            Some(html) => html,
+            None => self.default_inline(inline),
+        }
+    }
+
+    /// Attributes rendered by `html_attr`, with the identifier prefixed
+    pub fn attr(&self, attr: &Attr) -> String {
+        if attr.0.is_empty() || self.options.id_prefix.is_empty() {
+            return html_attr(attr);
+        }
+        let id = format!("{}{}", self.options.id_prefix, attr.0);
+        html_attr(&(id, attr.1.clone(), attr.2.clone()))
+    }
+
+    /// A link target escaped for an attribute value, with the identifier
+    /// prefixed for internal links
+    pub fn url(&self, url: &str) -> String {
+        match url.strip_prefix('#') {
+            Some(id) => escape_html(&format!("#{}{}", self.options.id_prefix, id)),
+            None => escape_html(url),
+        }
+    }
+
+    /// The contents of a list item or cell, without `<p>` for a single
+    /// `Plain` block
+    fn item(&mut self, blocks: &[Block]) -> String {
+        match blocks {
+            [Block::Plain(inlines)] => self.inlines(inlines),
+            blocks => format!("\n{}", self.blocks(blocks)),
+        }
+    }
+
+    /// The default rendering of a block, without a trailing newline
+    pub fn default_block(&mut self, block: &Block) -> String {
+        match *block {
+            Block::Plain(ref inlines) => self.inlines(inlines),
+            Block::Para(ref inlines) => format!("<p>{}</p>", self.inlines(inlines)),
+            Block::LineBlock(ref lines) => {
+                let lines: Vec<String> = lines.iter().map(|line| self.inlines(line)).collect();
+                format!("<div class=\"line-block\">{}</div>", lines.join("<br />\n"))
+            }
+            Block::CodeBlock(ref attr, ref code) => {
+                format!(
+                    "<pre{}><code>{}</code></pre>",
+                    self.attr(attr),
+                    escape_html(code)
+                )
+            }
+            Block::RawBlock(ref format, ref text) if is_html(format) => text.clone(),
+            Block::RawBlock(..) | Block::Null => String::new(),
+            Block::BlockQuote(ref blocks) => {
+                format!("<blockquote>\n{}</blockquote>", self.blocks(blocks))
+            }
+            Block::OrderedList((start, style, _), ref items) => {
+                let mut open = String::from("<ol");
+                if start != 1 {
+                    open.push_str(&format!(" start=\"{}\"", start));
+                }
+                let kind = match style {
+                    ListNumberStyle::LowerRoman => "i",
+                    ListNumberStyle::UpperRoman => "I",
+                    ListNumberStyle::LowerAlpha => "a",
+                    ListNumberStyle::UpperAlpha => "A",
+                    _ => "",
+                };
+                if !kind.is_empty() {
+                    open.push_str(&format!(" type=\"{}\"", kind));
+                }
+                format!("{}>\n{}</ol>", open, self.items(items))
+            }
+            Block::BulletList(ref items) => format!("<ul>\n{}</ul>", self.items(items)),
+            Block::DefinitionList(ref items) => {
+                let mut html = String::from("<dl>\n");
+                for (term, definitions) in items {
+                    html.push_str(&format!("<dt>{}</dt>\n", self.inlines(term)));
+                    for definition in definitions {
+                        html.push_str(&format!("<dd>{}</dd>\n", self.item(definition)));
+                    }
+                }
+                html.push_str("</dl>");
+                html
+            }
+            Block::Figure(ref attr, ref caption, ref content) => {
+                let mut html = format!("<figure{}>\n{}", self.attr(attr), self.blocks(content));
+                if !caption.1.is_empty() {
+                    let caption = self.item(&caption.1);
+                    html.push_str(&format!("<figcaption>{}</figcaption>\n", caption));
+                }
+                html.push_str("</figure>");
+                html
+            }
+            Block::Header(level, ref attr, ref inlines) => {
+                let level = level.clamp(1, 6);
+                let attr = self.attr(attr);
+                format!("<h{0}{1}>{2}</h{0}>", level, attr, self.inlines(inlines))
+            }
+            Block::HorizontalRule => "<hr />".to_string(),
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                self.table(attr, caption, colspecs, head, bodies, foot)
+            }
+            Block::Div(ref attr, ref blocks) => {
+                format!("<div{}>\n{}</div>", self.attr(attr), self.blocks(blocks))
+            }
+        }
+    }
+
+    fn items(&mut self, items: &[Vec<Block>]) -> String {
+        items
+            .iter()
+            .map(|item| format!("<li>{}</li>\n", self.item(item)))
+            .collect()
+    }
+
+    fn table(
+        &mut self,
+        attr: &Attr,
+        caption: &Caption,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> String {
+        let mut html = format!("<table{}>\n", self.attr(attr));
+        if !caption.1.is_empty() {
+            html.push_str(&format!("<caption>{}</caption>\n", self.item(&caption.1)));
+        }
+        if colspecs
+            .iter()
+            .any(|spec| matches!(spec.1, ColWidth::ColWidth(_)))
+        {
+            html.push_str("<colgroup>\n");
+            for spec in colspecs {
+                match spec.1 {
+                    ColWidth::ColWidth(width) => html.push_str(&format!(
+                        "<col style=\"width: {}%\" />\n",
+                        (width * 100.0).round()
+                    )),
+                    ColWidth::ColWidthDefault => html.push_str("<col />\n"),
+                }
+            }
+            html.push_str("</colgroup>\n");
+        }
+        html.push_str(&self.rows("thead", &head.0, &head.1, colspecs, usize::MAX));
+        for body in bodies {
+            let heads = body.1.max(0) as usize;
+            let mut rows = self.rows("", &body.0, &body.2, colspecs, usize::MAX);
+            rows.push_str(&self.rows("", &body.0, &body.3, colspecs, heads));
+            html.push_str(&format!(
+                "<tbody{}>\n{}</tbody>\n",
+                self.attr(&body.0),
+                rows
+            ));
+        }
+        html.push_str(&self.rows("tfoot", &foot.0, &foot.1, colspecs, 0));
+        html.push_str("</table>");
+        html
+    }
+
+    /// Renders rows, wrapped in `tag` unless it is empty. The first `heads`
+    /// cells of each row are header cells.
+    fn rows(
+        &mut self,
+        tag: &str,
+        attr: &Attr,
+        rows: &[Row],
+        colspecs: &[ColSpec],
+        heads: usize,
+    ) -> String {
+        if rows.is_empty() {
+            return String::new();
+        }
+        let mut html = String::new();
+        for (row_attr, cells) in rows {
+            html.push_str(&format!("<tr{}>\n", self.attr(row_attr)));
+            let mut col = 0;
+            for (i, cell) in cells.iter().enumerate() {
+                let (ref cell_attr, align, rowspan, colspan, ref blocks) = *cell;
+                let name = if i < heads { "th" } else { "td" };
+                let align = match align {
+                    Alignment::AlignDefault => colspecs.get(col).map_or(align, |spec| spec.0),
+                    _ => align,
+                };
+                let text_align = match align {
+                    Alignment::AlignLeft => "text-align: left;",
+                    Alignment::AlignRight => "text-align: right;",
+                    Alignment::AlignCenter => "text-align: center;",
+                    Alignment::AlignDefault => "",
+                };
+                let mut open =
+                    format!("<{}{}", name, self.attr(&with_style(cell_attr, text_align)));
+                if rowspan > 1 {
+                    open.push_str(&format!(" rowspan=\"{}\"", rowspan));
+                }
+                if colspan > 1 {
+                    open.push_str(&format!(" colspan=\"{}\"", colspan));
+                }
+                let content = self.item(blocks);
+                html.push_str(&format!("{}>{}</{}>\n", open, content, name));
+                col += colspan.max(1) as usize;
+            }
+            html.push_str("</tr>\n");
+        }
+        if tag.is_empty() {
+            html
+        } else {
+            format!("<{0}{1}>\n{2}</{0}>\n", tag, self.attr(attr), html)
+        }
+    }
+
+    /// The default rendering of an inline
+    pub fn default_inline(&mut self, inline: &Inline) -> String {
+        let tag = |this: &mut Self, tag: &str, inlines: &[Inline]| {
+            format!("<{0}>{1}</{0}>", tag, this.inlines(inlines))
+        };
+        match *inline {
+            Inline::Str(ref text) => escape_html(text),
+            Inline::Emph(ref inlines) => tag(self, "em", inlines),
+            Inline::Strong(ref inlines) => tag(self, "strong", inlines),
+            Inline::Underline(ref inlines) => tag(self, "u", inlines),
+            Inline::Strikeout(ref inlines) => tag(self, "del", inlines),
+            Inline::Superscript(ref inlines) => tag(self, "sup", inlines),
+            Inline::Subscript(ref inlines) => tag(self, "sub", inlines),
+            Inline::SmallCaps(ref inlines) => {
+                format!("<span class=\"smallcaps\">{}</span>", self.inlines(inlines))
+            }
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                format!("\u{2018}{}\u{2019}", self.inlines(inlines))
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                format!("\u{201c}{}\u{201d}", self.inlines(inlines))
+            }
+            Inline::Cite(ref citations, ref inlines) => {
+                let ids: Vec<&str> = citations.iter().map(|c| &c.citationId[..]).collect();
+                format!(
+                    "<span class=\"citation\" data-cites=\"{}\">{}</span>",
+                    escape_html(&ids.join(" ")),
+                    self.inlines(inlines)
+                )
+            }
+            Inline::Code(ref attr, ref code) => {
+                format!("<code{}>{}</code>", self.attr(attr), escape_html(code))
+            }
+            Inline::Space => " ".to_string(),
+            Inline::SoftBreak => "\n".to_string(),
+            Inline::LineBreak => "<br />\n".to_string(),
+            Inline::Math(MathType::InlineMath, ref tex) => {
+                format!(
+                    "<span class=\"math inline\">\\({}\\)</span>",
+                    escape_html(tex)
+                )
+            }
+            Inline::Math(MathType::DisplayMath, ref tex) => {
+                format!(
+                    "<span class=\"math display\">\\[{}\\]</span>",
+                    escape_html(tex)
+                )
+            }
+            Inline::RawInline(ref format, ref text) if is_html(format) => text.clone(),
+            Inline::RawInline(..) => String::new(),
+            Inline::Link(ref attr, ref inlines, (ref url, ref title)) => {
+                let mut open = format!("<a href=\"{}\"", self.url(url));
+                if !title.is_empty() {
+                    open.push_str(&format!(" title=\"{}\"", escape_html(title)));
+                }
+                format!("{}{}>{}</a>", open, self.attr(attr), self.inlines(inlines))
+            }
+            Inline::Image(ref attr, ref inlines, (ref url, ref title)) => {
+                let mut html = format!(
+                    "<img src=\"{}\" alt=\"{}\"",
+                    escape_html(url),
+                    escape_html(&stringify(inlines))
+                );
+                if !title.is_empty() {
+                    html.push_str(&format!(" title=\"{}\"", escape_html(title)));
+                }
+                format!("{}{} />", html, self.attr(attr))
+            }
+            Inline::Note(ref blocks) => {
+                // reserve the number before rendering, notes can be nested
+                let n = self.notes.len() + 1;
+                self.notes.push(String::new());
+                let prefix = escape_html(&self.options.id_prefix);
+                let back = format!(
+                    "<a href=\"#{}fnref{}\" class=\"footnote-back\" role=\"doc-backlink\">\
+                     \u{21a9}\u{fe0e}</a>",
+                    prefix, n
+                );
+                let content = match blocks.split_last() {
+                    Some((Block::Para(inlines), rest)) => {
+                        format!(
+                            "{}<p>{}{}</p>",
+                            self.blocks(rest),
+                            self.inlines(inlines),
+                            back
+                        )
+                    }
+                    Some((Block::Plain(inlines), rest)) => {
+                        format!("{}{}{}", self.blocks(rest), self.inlines(inlines), back)
+                    }
+                    _ => format!("{}{}", self.blocks(blocks), back),
+                };
+                self.notes[n - 1] = format!("<li id=\"{}fn{}\">{}</li>", prefix, n, content);
+                format!(
+                    "<a href=\"#{0}fn{1}\" class=\"footnote-ref\" id=\"{0}fnref{1}\" \
+                     role=\"doc-noteref\"><sup>{1}</sup></a>",
+                    prefix, n
+                )
+            }
+            Inline::Span(ref attr, ref inlines) => {
+                format!("<span{}>{}</span>", self.attr(attr), self.inlines(inlines))
+            }
+        }
+    }
+}
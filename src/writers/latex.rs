@@ -0,0 +1,515 @@
+//! LaTeX fragments for inclusion into a template, like the body of
+//! pandoc's `latex` output
+//!
+//! Tables need the `longtable` and `booktabs` packages (and `multirow` for
+//! cells spanning rows), strikeout needs `ulem` and links `hyperref`.
+
+use super::*;
+
+/// The commands citations are rendered with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum CiteCommands {
+    /// `\citet`, `\citep` and `\citeyearpar`
+    #[default]
+    Natbib,
+    /// `\textcite`, `\autocite` and `\autocite*`
+    Biblatex,
+}
+
+/// Options for the LaTeX writer
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LatexOptions {
+    pub cite_commands: CiteCommands,
+}
+
+/// Renders the blocks of a document as LaTeX
+pub fn write(pandoc: &Pandoc, options: &LatexOptions) -> String {
+    write_blocks(&pandoc.blocks, options)
+}
+
+/// Renders blocks as LaTeX
+pub fn write_blocks(blocks: &[Block], options: &LatexOptions) -> String {
+    let mut latex = Writer::new(options).blocks(blocks);
+    if !latex.is_empty() {
+        latex.push('\n');
+    }
+    latex
+}
+
+/// Renders inlines as LaTeX
+pub fn write_inlines(inlines: &[Inline], options: &LatexOptions) -> String {
+    Writer::new(options).inlines(inlines)
+}
+
+/// Escapes the characters LaTeX treats specially in running text
+pub fn escape_latex(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '{' | '}' | '$' | '%' | '&' | '#' | '_' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\\' => out.push_str("\\textbackslash{}"),
+            '^' => out.push_str("\\^{}"),
+            '~' => out.push_str("\\textasciitilde{}"),
+            '<' => out.push_str("\\textless{}"),
+            '>' => out.push_str("\\textgreater{}"),
+            '|' => out.push_str("\\textbar{}"),
+            '[' => out.push_str("{[}"),
+            ']' => out.push_str("{]}"),
+            '\u{a0}' => out.push('~'),
+            '\u{2013}' => out.push_str("--"),
+            '\u{2014}' => out.push_str("---"),
+            '\u{2026}' => out.push_str("\\ldots{}"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes a URL or path for `\href`, `\url` and `\includegraphics`
+fn escape_url(url: &str) -> String {
+    url.replace('\\', "/")
+        .replace('%', "\\%")
+        .replace('#', "\\#")
+        .replace('{', "\\{")
+        .replace('}', "\\}")
+}
+
+/// A LaTeX length for an image's `width` or `height` attribute, with
+/// percentages relative to `relative_to`
+fn dimension(value: &str, relative_to: &str) -> String {
+    match value.strip_suffix('%').and_then(|v| v.parse::<f64>().ok()) {
+        Some(percent) => format!("{}{}", percent / 100.0, relative_to),
+        None => value.to_string(),
+    }
+}
+
+fn is_latex(format: &Format) -> bool {
+    format.matches(&Format::new("latex"))
+}
+
+fn label(id: &str) -> String {
+    if id.is_empty() {
+        String::new()
+    } else {
+        format!("\\label{{{}}}", id)
+    }
+}
+
+struct Writer<'a> {
+    options: &'a LatexOptions,
+    /// Number of enclosing ordered lists
+    enumerate_depth: usize,
+}
+
+impl<'a> Writer<'a> {
+    fn new(options: &'a LatexOptions) -> Self {
+        Writer {
+            options,
+            enumerate_depth: 0,
+        }
+    }
+
+    fn blocks(&mut self, blocks: &[Block]) -> String {
+        let blocks: Vec<String> = blocks
+            .iter()
+            .map(|block| self.block(block))
+            .filter(|block| !block.is_empty())
+            .collect();
+        blocks.join("\n\n")
+    }
+
+    /// The caption's blocks on one line, `\caption` takes no paragraphs
+    fn caption(&mut self, blocks: &[Block]) -> String {
+        let blocks: Vec<String> = blocks
+            .iter()
+            .map(|block| self.block(block))
+            .filter(|block| !block.is_empty())
+            .collect();
+        blocks.join(" ")
+    }
+
+    fn block(&mut self, block: &Block) -> String {
+        match *block {
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => self.inlines(inlines),
+            Block::LineBlock(ref lines) => {
+                let lines: Vec<String> = lines
+                    .iter()
+                    .map(|line| match self.inlines(line) {
+                        line if line.is_empty() => "~".to_string(),
+                        line => line,
+                    })
+                    .collect();
+                lines.join("\\\\\n")
+            }
+            Block::CodeBlock(_, ref code) => {
+                format!("\\begin{{verbatim}}\n{}\n\\end{{verbatim}}", code)
+            }
+            Block::RawBlock(ref format, ref text) if is_latex(format) => text.clone(),
+            Block::RawBlock(..) | Block::Null => String::new(),
+            Block::BlockQuote(ref blocks) => {
+                format!("\\begin{{quote}}\n{}\n\\end{{quote}}", self.blocks(blocks))
+            }
+            Block::OrderedList((start, style, delim), ref items) => {
+                self.enumerate_depth += 1;
+                let counter = match self.enumerate_depth {
+                    1 => "enumi",
+                    2 => "enumii",
+                    3 => "enumiii",
+                    _ => "enumiv",
+                };
+                let mut latex = String::from("\\begin{enumerate}\n");
+                let number = match style {
+                    ListNumberStyle::LowerRoman => "\\roman",
+                    ListNumberStyle::UpperRoman => "\\Roman",
+                    ListNumberStyle::LowerAlpha => "\\alph",
+                    ListNumberStyle::UpperAlpha => "\\Alph",
+                    _ => "\\arabic",
+                };
+                let plain = number == "\\arabic"
+                    && matches!(
+                        delim,
+                        ListNumberDelim::DefaultDelim | ListNumberDelim::Period
+                    );
+                if !plain {
+                    let number = format!("{}{{{}}}", number, counter);
+                    let label = match delim {
+                        ListNumberDelim::OneParen => format!("{})", number),
+                        ListNumberDelim::TwoParens => format!("({})", number),
+                        _ => format!("{}.", number),
+                    };
+                    latex.push_str(&format!("\\def\\label{}{{{}}}\n", counter, label));
+                }
+                if start != 1 {
+                    latex.push_str(&format!("\\setcounter{{{}}}{{{}}}\n", counter, start - 1));
+                }
+                latex.push_str(&self.items(items));
+                latex.push_str("\\end{enumerate}");
+                self.enumerate_depth -= 1;
+                latex
+            }
+            Block::BulletList(ref items) => {
+                format!("\\begin{{itemize}}\n{}\\end{{itemize}}", self.items(items))
+            }
+            Block::DefinitionList(ref items) => {
+                let mut latex = String::from("\\begin{description}\n");
+                for (term, definitions) in items {
+                    latex.push_str(&format!("\\item[{}]", self.inlines(term)));
+                    for definition in definitions {
+                        latex.push('\n');
+                        latex.push_str(&self.blocks(definition));
+                    }
+                    latex.push('\n');
+                }
+                latex.push_str("\\end{description}");
+                latex
+            }
+            Block::Figure((ref id, _, _), ref caption, ref blocks) => {
+                let mut latex =
+                    format!("\\begin{{figure}}\n\\centering\n{}\n", self.blocks(blocks));
+                if !caption.1.is_empty() || !id.is_empty() {
+                    let caption = self.caption(&caption.1);
+                    latex.push_str(&format!("\\caption{{{}}}{}\n", caption, label(id)));
+                }
+                latex.push_str("\\end{figure}");
+                latex
+            }
+            Block::Header(level, (ref id, ref classes, _), ref inlines) => {
+                let command = match level {
+                    ..=1 => "section",
+                    2 => "subsection",
+                    3 => "subsubsection",
+                    4 => "paragraph",
+                    _ => "subparagraph",
+                };
+                let star = if classes.iter().any(|c| c == "unnumbered") {
+                    "*"
+                } else {
+                    ""
+                };
+                let title = self.inlines(inlines);
+                format!("\\{}{}{{{}}}{}", command, star, title, label(id))
+            }
+            Block::HorizontalRule => {
+                "\\begin{center}\\rule{0.5\\linewidth}{0.5pt}\\end{center}".to_string()
+            }
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                self.table(&attr.0, caption, colspecs, head, bodies, foot)
+            }
+            Block::Div((ref id, _, _), ref blocks) => {
+                let content = self.blocks(blocks);
+                if id.is_empty() {
+                    content
+                } else {
+                    format!("\\phantomsection{}\n{}", label(id), content)
+                }
+            }
+        }
+    }
+
+    fn items(&mut self, items: &[Vec<Block>]) -> String {
+        let mut latex = String::new();
+        for item in items {
+            latex.push_str("\\item");
+            let content = self.blocks(item);
+            if !content.is_empty() {
+                latex.push(' ');
+                latex.push_str(&content);
+            }
+            latex.push('\n');
+        }
+        latex
+    }
+
+    /// A `longtable`. Columns with a relative width become paragraph
+    /// columns; in the others the blocks of a cell are put on one line.
+    fn table(
+        &mut self,
+        id: &str,
+        caption: &Caption,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> String {
+        let grid = Grid::new(colspecs, head, bodies, foot);
+        let paragraphs: Vec<bool> = (0..grid.cols)
+            .map(|c| matches!(grid.widths.get(c), Some(ColWidth::ColWidth(_))))
+            .collect();
+        let mut spec = String::from("@{}");
+        for c in 0..grid.cols {
+            let align = colspecs.get(c).map_or(Alignment::AlignDefault, |s| s.0);
+            match grid.widths.get(c) {
+                Some(&ColWidth::ColWidth(width)) => {
+                    let ragged = match align {
+                        Alignment::AlignRight => "\\raggedleft",
+                        Alignment::AlignCenter => "\\centering",
+                        _ => "\\raggedright",
+                    };
+                    spec.push_str(&format!(
+                        ">{{{}\\arraybackslash}}p{{{:.2}\\linewidth}}",
+                        ragged, width
+                    ));
+                }
+                _ => spec.push(column_letter(align)),
+            }
+        }
+        spec.push_str("@{}");
+
+        let mut latex = format!("\\begin{{longtable}}[]{{{}}}\n", spec);
+        if !caption.1.is_empty() || !id.is_empty() {
+            let caption = self.caption(&caption.1);
+            latex.push_str(&format!(
+                "\\caption{{{}}}{}\\tabularnewline\n",
+                caption,
+                label(id)
+            ));
+        }
+        latex.push_str("\\toprule\\noalign{}\n");
+        for r in 0..grid.rows {
+            if r == grid.head && grid.head > 0 {
+                latex.push_str("\\midrule\\noalign{}\n\\endhead\n");
+            }
+            if r == grid.rows - grid.foot && grid.foot > 0 {
+                latex.push_str("\\midrule\\noalign{}\n");
+            }
+            let mut cells = Vec::new();
+            let mut c = 0;
+            while c < grid.cols {
+                let cell = grid
+                    .cells
+                    .iter()
+                    .find(|cell| cell.col == c && cell.row <= r && r < cell.row + cell.rows);
+                let Some(cell) = cell else {
+                    cells.push(String::new());
+                    c += 1;
+                    continue;
+                };
+                let mut content = if cell.row < r {
+                    // covered by a cell spanning rows above
+                    String::new()
+                } else {
+                    let separator = if paragraphs[c] { "\n\n" } else { " " };
+                    let blocks: Vec<String> = cell
+                        .blocks
+                        .iter()
+                        .map(|block| self.block(block))
+                        .filter(|block| !block.is_empty())
+                        .collect();
+                    blocks.join(separator)
+                };
+                if cell.row == r && cell.rows > 1 {
+                    content = format!("\\multirow{{{}}}{{*}}{{{}}}", cell.rows, content);
+                }
+                if cell.cols > 1 {
+                    let letter = column_letter(cell.align);
+                    content = format!(
+                        "\\multicolumn{{{}}}{{{}}}{{{}}}",
+                        cell.cols, letter, content
+                    );
+                }
+                cells.push(content);
+                c += cell.cols;
+            }
+            latex.push_str(&cells.join(" & "));
+            latex.push_str(" \\\\\n");
+        }
+        latex.push_str("\\bottomrule\\noalign{}\n\\end{longtable}");
+        latex
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) -> String {
+        inlines.iter().map(|inline| self.inline(inline)).collect()
+    }
+
+    fn command(&mut self, name: &str, inlines: &[Inline]) -> String {
+        format!("\\{}{{{}}}", name, self.inlines(inlines))
+    }
+
+    fn inline(&mut self, inline: &Inline) -> String {
+        match *inline {
+            Inline::Str(ref text) => escape_latex(text),
+            Inline::Emph(ref inlines) => self.command("emph", inlines),
+            Inline::Strong(ref inlines) => self.command("textbf", inlines),
+            Inline::Underline(ref inlines) => self.command("underline", inlines),
+            Inline::Strikeout(ref inlines) => self.command("sout", inlines),
+            Inline::Superscript(ref inlines) => self.command("textsuperscript", inlines),
+            Inline::Subscript(ref inlines) => self.command("textsubscript", inlines),
+            Inline::SmallCaps(ref inlines) => self.command("textsc", inlines),
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                format!("`{}'", self.inlines(inlines))
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                format!("``{}''", self.inlines(inlines))
+            }
+            Inline::Cite(ref citations, _) => self.cite(citations),
+            Inline::Code(_, ref code) => format!("\\texttt{{{}}}", escape_latex(code)),
+            Inline::Space => " ".to_string(),
+            Inline::SoftBreak => "\n".to_string(),
+            Inline::LineBreak => "\\\\\n".to_string(),
+            Inline::Math(MathType::InlineMath, ref tex) => format!("\\({}\\)", tex),
+            Inline::Math(MathType::DisplayMath, ref tex) => format!("\\[{}\\]", tex),
+            Inline::RawInline(ref format, ref text) if is_latex(format) => text.clone(),
+            Inline::RawInline(..) => String::new(),
+            Inline::Link(_, ref inlines, (ref url, _)) => {
+                if let Some(id) = url.strip_prefix('#') {
+                    format!("\\hyperref[{}]{{{}}}", id, self.inlines(inlines))
+                } else {
+                    let text = stringify(inlines);
+                    if text == *url || format!("mailto:{}", text) == *url {
+                        format!("\\url{{{}}}", escape_url(url))
+                    } else {
+                        let text = self.inlines(inlines);
+                        format!("\\href{{{}}}{{{}}}", escape_url(url), text)
+                    }
+                }
+            }
+            Inline::Image((_, _, ref kvs), _, (ref url, _)) => {
+                let mut options = Vec::new();
+                for (key, value) in kvs {
+                    match &key[..] {
+                        "width" => {
+                            options.push(format!("width={}", dimension(value, "\\linewidth")))
+                        }
+                        "height" => {
+                            options.push(format!("height={}", dimension(value, "\\textheight")))
+                        }
+                        _ => {}
+                    }
+                }
+                if options.is_empty() {
+                    format!("\\includegraphics{{{}}}", escape_url(url))
+                } else {
+                    format!(
+                        "\\includegraphics[{}]{{{}}}",
+                        options.join(","),
+                        escape_url(url)
+                    )
+                }
+            }
+            Inline::Note(ref blocks) => format!("\\footnote{{{}}}", self.blocks(blocks)),
+            Inline::Span((ref id, _, _), ref inlines) => {
+                let content = self.inlines(inlines);
+                if id.is_empty() {
+                    content
+                } else {
+                    format!("\\phantomsection{}{}", label(id), content)
+                }
+            }
+        }
+    }
+
+    /// Renders citations with the commands for their mode. Consecutive
+    /// citations with the same mode share a command unless they have a
+    /// prefix or suffix, which biblatex puts into its multi-cite commands.
+    fn cite(&mut self, citations: &[Citation]) -> String {
+        let mut groups: Vec<&[Citation]> = Vec::new();
+        let mut start = 0;
+        for i in 1..=citations.len() {
+            let split = i == citations.len()
+                || citations[i].citationMode != citations[start].citationMode
+                || (self.options.cite_commands == CiteCommands::Natbib
+                    && (has_affixes(&citations[i]) || has_affixes(&citations[i - 1])));
+            if split {
+                groups.push(&citations[start..i]);
+                start = i;
+            }
+        }
+        let groups: Vec<String> = groups.into_iter().map(|g| self.cite_group(g)).collect();
+        groups.join("; ")
+    }
+
+    fn cite_group(&mut self, citations: &[Citation]) -> String {
+        let command = match (self.options.cite_commands, citations[0].citationMode) {
+            (CiteCommands::Natbib, CitationMode::AuthorInText) => "citet",
+            (CiteCommands::Natbib, CitationMode::NormalCitation) => "citep",
+            (CiteCommands::Natbib, CitationMode::SuppressAuthor) => "citeyearpar",
+            (CiteCommands::Biblatex, CitationMode::AuthorInText) => "textcite",
+            (CiteCommands::Biblatex, CitationMode::NormalCitation) => "autocite",
+            (CiteCommands::Biblatex, CitationMode::SuppressAuthor) => "autocite*",
+        };
+        if citations.len() > 1 && citations.iter().any(has_affixes) {
+            // only biblatex gets here
+            let command = command.replace("cite", "cites");
+            let mut latex = format!("\\{}", command.trim_end_matches('*'));
+            for citation in citations {
+                latex.push_str(&self.affixes(citation));
+                latex.push_str(&format!("{{{}}}", citation.citationId));
+            }
+            return latex;
+        }
+        let ids: Vec<&str> = citations.iter().map(|c| &c.citationId[..]).collect();
+        format!(
+            "\\{}{}{{{}}}",
+            command,
+            self.affixes(&citations[0]),
+            ids.join(",")
+        )
+    }
+
+    /// The optional arguments holding a citation's prefix and suffix
+    fn affixes(&mut self, citation: &Citation) -> String {
+        let prefix = self.inlines(&citation.citationPrefix);
+        let suffix = self.inlines(&citation.citationSuffix);
+        let suffix = suffix.trim_start_matches(',').trim_start();
+        match (prefix.is_empty(), suffix.is_empty()) {
+            (true, true) => String::new(),
+            (true, false) => format!("[{}]", suffix),
+            (false, _) => format!("[{}][{}]", prefix, suffix),
+        }
+    }
+}
+
+fn has_affixes(citation: &Citation) -> bool {
+    !citation.citationPrefix.is_empty() || !citation.citationSuffix.is_empty()
+}
+
+fn column_letter(align: Alignment) -> char {
+    match align {
+        Alignment::AlignRight => 'r',
+        Alignment::AlignCenter => 'c',
+        _ => 'l',
+    }
+}
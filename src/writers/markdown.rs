@@ -0,0 +1,802 @@
+//! CommonMark, optionally with the GitHub flavored extensions
+//!
+//! Constructs CommonMark has no syntax for, like underlines, attributes or
+//! tables with spanning cells, are written as raw HTML. Metadata becomes a
+//! YAML front matter block.
+
+use super::*;
+
+/// Options for the Markdown writer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MarkdownOptions {
+    /// Wrap lines at this many characters, or not at all
+    pub width: Option<usize>,
+    /// Write simple tables as pipe tables instead of HTML
+    pub pipe_tables: bool,
+    /// Write `Strikeout` as `~~text~~` instead of HTML
+    pub strikeout: bool,
+    /// Write list items starting with `☐` or `☒` as `[ ]` or `[x]`
+    pub task_lists: bool,
+    /// Write notes as `[^1]` with their text at the end of the document,
+    /// instead of HTML superscripts
+    pub footnotes: bool,
+    /// Start with the metadata as YAML front matter
+    pub front_matter: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        MarkdownOptions {
+            width: Some(72),
+            pipe_tables: false,
+            strikeout: false,
+            task_lists: false,
+            footnotes: false,
+            front_matter: true,
+        }
+    }
+}
+
+impl MarkdownOptions {
+    /// Default options with all GitHub flavored extensions enabled
+    pub fn gfm() -> Self {
+        MarkdownOptions {
+            pipe_tables: true,
+            strikeout: true,
+            task_lists: true,
+            footnotes: true,
+            ..MarkdownOptions::default()
+        }
+    }
+}
+
+/// Renders a document as Markdown
+pub fn write(pandoc: &Pandoc, options: &MarkdownOptions) -> String {
+    let mut writer = Writer {
+        options,
+        notes: Vec::new(),
+    };
+    let mut lines = Vec::new();
+    if options.front_matter && !pandoc.meta.is_empty() {
+        lines.push("---".to_string());
+        for (key, value) in &pandoc.meta {
+            lines.extend(writer.yaml_entry(key, value));
+        }
+        lines.push("---".to_string());
+        lines.push(String::new());
+    }
+    lines.extend(writer.blocks(&pandoc.blocks, options.width));
+    lines.extend(writer.notes(options.width));
+    finish(lines)
+}
+
+/// Renders blocks as Markdown
+pub fn write_blocks(blocks: &[Block], options: &MarkdownOptions) -> String {
+    let mut writer = Writer {
+        options,
+        notes: Vec::new(),
+    };
+    let mut lines = writer.blocks(blocks, options.width);
+    lines.extend(writer.notes(options.width));
+    finish(lines)
+}
+
+fn finish(lines: Vec<String>) -> String {
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn is_markdown(format: &Format) -> bool {
+    matches!(
+        &format.base_name()[..],
+        "markdown" | "commonmark" | "commonmark_x" | "gfm" | "html"
+    )
+}
+
+/// Escapes characters that would otherwise be read as markup
+fn escape(text: &str, strikeout: bool) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let alnum = |j: Option<usize>| {
+            j.and_then(|j| chars.get(j))
+                .is_some_and(|c| c.is_alphanumeric())
+        };
+        let escape = match c {
+            '\\' | '`' | '*' | '[' | ']' | '<' => true,
+            // intraword underscores are never emphasis
+            '_' => !alnum(i.checked_sub(1)) || !alnum(Some(i + 1)),
+            '~' => strikeout,
+            '!' => chars.get(i + 1) == Some(&'['),
+            '&' => chars
+                .get(i + 1)
+                .is_some_and(|c| c.is_alphanumeric() || *c == '#'),
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escapes the start of a line of text that would otherwise be read as a
+/// header, list item, block quote, fence or thematic break
+fn escape_line_start(line: String) -> String {
+    let mut chars = line.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return line,
+    };
+    let second = chars.next();
+    // setext underlines need a single `=` or `-`, thematic breaks three
+    let rule = "-*_=".contains(first)
+        && line.chars().all(|c| c == first || c == ' ')
+        && ("=-".contains(first) || line.chars().filter(|&c| c == first).count() >= 3);
+    let escape = match first {
+        '#' | '>' => true,
+        '-' | '+' | '*' => second.is_none_or(|c| c == ' ') || rule,
+        '=' | '_' => rule,
+        '`' | '~' => line.starts_with("```") || line.starts_with("~~~"),
+        _ => false,
+    };
+    if escape {
+        return format!("\\{}", line);
+    }
+    // ordered list markers like `1.` or `1)`
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if (1..=9).contains(&digits) {
+        let rest = &line[digits..];
+        let mut rest_chars = rest.chars();
+        if matches!(rest_chars.next(), Some('.') | Some(')'))
+            && rest_chars.next().is_none_or(|c| c == ' ')
+        {
+            return format!("{}\\{}", &line[..digits], rest);
+        }
+    }
+    line
+}
+
+/// A fence of backticks longer than any run of backticks in `text`
+fn fence(text: &str, min: usize) -> String {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in text.chars() {
+        run = if c == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    "`".repeat((longest + 1).max(min))
+}
+
+/// Whether a header's attributes are just the identifier pandoc derives
+/// from its text, possibly made unique with a numeric suffix
+fn implicit_attr(attr: &Attr, inlines: &[Inline]) -> bool {
+    let (ref id, ref classes, ref kvs) = *attr;
+    let implicit = |style| {
+        let base = IdGenerator::new(style).identifier(inlines);
+        let suffix = id
+            .strip_prefix(&base[..])
+            .and_then(|rest| rest.strip_prefix('-'));
+        *id == base
+            || suffix.is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+    };
+    classes.is_empty()
+        && kvs.is_empty()
+        && (id.is_empty() || implicit(IdentifierStyle::Pandoc) || implicit(IdentifierStyle::Gfm))
+}
+
+fn is_task(item: &[Block]) -> Option<bool> {
+    match item.first() {
+        Some(Block::Plain(inlines)) | Some(Block::Para(inlines)) => match inlines[..] {
+            [Inline::Str(ref s), Inline::Space, ..] if s == "\u{2610}" => Some(false),
+            [Inline::Str(ref s), Inline::Space, ..] if s == "\u{2612}" => Some(true),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The text of a cell that fits into a pipe table cell
+fn simple_cell(blocks: &[Block]) -> Option<&[Inline]> {
+    match blocks {
+        [] => Some(&[]),
+        [Block::Plain(inlines)] | [Block::Para(inlines)] => Some(inlines),
+        _ => None,
+    }
+}
+
+/// The value of a metadata field in YAML
+enum Yaml {
+    /// A scalar following the key on the same line
+    Scalar(String),
+    /// Nested lines of a list or map
+    Nested(Vec<String>),
+    /// Lines of a literal block scalar
+    Literal(Vec<String>),
+}
+
+fn yaml_string(text: &str) -> String {
+    let reserved = ["true", "false", "yes", "no", "on", "off", "null", "~"];
+    let plain = text.starts_with(|c: char| c.is_alphabetic())
+        && !text.ends_with(' ')
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || " .,'()/-_!?".contains(c))
+        && !reserved.contains(&&text.to_lowercase()[..]);
+    if plain {
+        return text.to_string();
+    }
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Writer<'a> {
+    options: &'a MarkdownOptions,
+    notes: Vec<Vec<Block>>,
+}
+
+impl Writer<'_> {
+    fn yaml(&mut self, value: &MetaValue) -> Yaml {
+        match *value {
+            MetaValue::MetaBool(b) => Yaml::Scalar(b.to_string()),
+            MetaValue::MetaString(ref s) => Yaml::Scalar(yaml_string(s)),
+            MetaValue::MetaInlines(ref inlines) => {
+                Yaml::Scalar(yaml_string(&self.text(inlines, None).join("\n")))
+            }
+            MetaValue::MetaBlocks(ref blocks) => Yaml::Literal(self.blocks(blocks, None)),
+            MetaValue::MetaList(ref list) if list.is_empty() => Yaml::Scalar("[]".to_string()),
+            MetaValue::MetaMap(ref map) if map.is_empty() => Yaml::Scalar("{}".to_string()),
+            MetaValue::MetaList(ref list) => {
+                let mut lines = Vec::new();
+                for item in list {
+                    match self.yaml(item) {
+                        Yaml::Scalar(s) => lines.push(format!("- {}", s)),
+                        Yaml::Nested(nested) => lines.extend(indent(nested, "- ", "  ")),
+                        Yaml::Literal(literal) => {
+                            lines.push("- |".to_string());
+                            lines.extend(indent(literal, "    ", "    "));
+                        }
+                    }
+                }
+                Yaml::Nested(lines)
+            }
+            MetaValue::MetaMap(ref map) => {
+                let mut lines = Vec::new();
+                for (key, value) in map {
+                    lines.extend(self.yaml_entry(key, value));
+                }
+                Yaml::Nested(lines)
+            }
+        }
+    }
+
+    fn yaml_entry(&mut self, key: &str, value: &MetaValue) -> Vec<String> {
+        let key = yaml_string(key);
+        match self.yaml(value) {
+            Yaml::Scalar(s) => vec![format!("{}: {}", key, s)],
+            Yaml::Nested(nested) => {
+                let mut lines = vec![format!("{}:", key)];
+                lines.extend(indent(nested, "  ", "  "));
+                lines
+            }
+            Yaml::Literal(literal) => {
+                let mut lines = vec![format!("{}: |", key)];
+                lines.extend(indent(literal, "  ", "  "));
+                lines
+            }
+        }
+    }
+
+    fn notes(&mut self, width: Option<usize>) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut i = 0;
+        while i < self.notes.len() {
+            let note = self.notes[i].clone();
+            let note = self.blocks(&note, narrow(width, 4));
+            lines.push(String::new());
+            if self.options.footnotes {
+                lines.extend(indent(note, &format!("[^{}]: ", i + 1), "    "));
+            } else {
+                let marker = format!("<sup>{}</sup> ", i + 1);
+                lines.extend(indent(note, &marker, ""));
+            }
+            i += 1;
+        }
+        lines
+    }
+
+    fn blocks(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut previous: Option<&Block> = None;
+        for block in blocks {
+            let lines = self.block(block, width);
+            if lines.is_empty() {
+                continue;
+            }
+            let tight = matches!(previous, Some(Block::Plain(_)));
+            // consecutive lists of the same kind would be merged
+            let same_list = matches!(
+                (previous, block),
+                (Some(Block::BulletList(_)), Block::BulletList(_))
+                    | (Some(Block::OrderedList(..)), Block::OrderedList(..))
+            );
+            if same_list {
+                out.extend([String::new(), "<!-- -->".to_string()]);
+            }
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            previous = Some(block);
+            out.extend(lines);
+        }
+        out
+    }
+
+    fn block(&mut self, block: &Block, width: Option<usize>) -> Vec<String> {
+        match *block {
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => self.text(inlines, width),
+            Block::LineBlock(ref lines) => {
+                let mut out: Vec<String> = Vec::new();
+                for line in lines {
+                    if let Some(last) = out.last_mut() {
+                        last.push('\\');
+                    }
+                    out.push(self.text(line, None).join(" "));
+                }
+                out
+            }
+            Block::Header(level, ref attr, ref inlines) if !implicit_attr(attr, inlines) => {
+                let level = level.clamp(1, 6);
+                let mut out = vec![format!("<h{}{}>", level, html_attr(attr)), String::new()];
+                out.extend(self.text(inlines, width));
+                out.extend([String::new(), format!("</h{}>", level)]);
+                out
+            }
+            Block::Header(level, _, ref inlines) => {
+                let text = self.line(inlines);
+                vec![format!(
+                    "{} {}",
+                    "#".repeat(level.clamp(1, 6) as usize),
+                    text
+                )]
+            }
+            Block::CodeBlock(ref attr, ref code)
+                if !attr.0.is_empty() || attr.1.len() > 1 || !attr.2.is_empty() =>
+            {
+                let html = format!(
+                    "<pre{}><code>{}</code></pre>",
+                    html_attr(attr),
+                    escape_html(code)
+                );
+                html.lines().map(String::from).collect()
+            }
+            Block::CodeBlock((_, ref classes, _), ref code) => {
+                let fence = fence(code, 3);
+                let info = classes.first().map_or("", |c| &c[..]);
+                let mut out = vec![format!("{}{}", fence, info)];
+                out.extend(code.lines().map(String::from));
+                out.push(fence);
+                out
+            }
+            Block::RawBlock(ref format, ref text) if is_markdown(format) => {
+                text.lines().map(String::from).collect()
+            }
+            Block::RawBlock(..) | Block::Null => Vec::new(),
+            Block::BlockQuote(ref blocks) => {
+                indent(self.blocks(blocks, narrow(width, 2)), "> ", "> ")
+            }
+            Block::BulletList(ref items) => {
+                self.list(items, vec!["-".to_string(); items.len()], width)
+            }
+            Block::OrderedList((start, _, delim), ref items) => {
+                let delim = if delim == ListNumberDelim::OneParen {
+                    ')'
+                } else {
+                    '.'
+                };
+                let markers = (0..items.len() as Int)
+                    .map(|i| format!("{}{}", start + i, delim))
+                    .collect();
+                self.list(items, markers, width)
+            }
+            Block::DefinitionList(ref items) => {
+                // CommonMark has no definition lists, use a bullet list with
+                // the terms in bold
+                let mut out = Vec::new();
+                for (term, definitions) in items {
+                    if !out.is_empty() {
+                        out.push(String::new());
+                    }
+                    let mut item = self.text(&[Inline::Strong(term.clone())], narrow(width, 2));
+                    for definition in definitions {
+                        item.push(String::new());
+                        item.extend(self.blocks(definition, narrow(width, 2)));
+                    }
+                    out.extend(indent(item, "- ", "  "));
+                }
+                out
+            }
+            Block::Figure(_, ref caption, ref content) => {
+                if let [Block::Plain(ref inlines)] | [Block::Para(ref inlines)] = content[..] {
+                    if let [Inline::Image(ref attr, _, ref target)] = inlines[..] {
+                        let alt = caption
+                            .1
+                            .iter()
+                            .filter_map(|block| match *block {
+                                Block::Plain(ref inlines) | Block::Para(ref inlines) => {
+                                    Some(inlines.clone())
+                                }
+                                _ => None,
+                            })
+                            .flatten()
+                            .collect();
+                        let image = Inline::Image(attr.clone(), alt, target.clone());
+                        return self.text(&[image], width);
+                    }
+                }
+                let mut out = self.blocks(content, width);
+                let caption = self.blocks(&caption.1, width);
+                if !caption.is_empty() {
+                    out.push(String::new());
+                    out.extend(caption);
+                }
+                out
+            }
+            Block::HorizontalRule => vec!["-----".to_string()],
+            Block::Table(_, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                let mut out = self
+                    .pipe_table(colspecs, head, bodies, foot)
+                    .unwrap_or_else(|| self.html_table(colspecs, head, bodies, foot, width));
+                let caption = self.blocks(&caption.1, width);
+                if !caption.is_empty() {
+                    out.push(String::new());
+                    out.extend(caption);
+                }
+                out
+            }
+            Block::Div(ref attr, ref blocks) => {
+                let content = self.blocks(blocks, width);
+                if attr.0.is_empty() && attr.1.is_empty() && attr.2.is_empty() {
+                    return content;
+                }
+                let mut out = vec![format!("<div{}>", html_attr(attr)), String::new()];
+                out.extend(content);
+                out.extend([String::new(), "</div>".to_string()]);
+                out
+            }
+        }
+    }
+
+    fn list(
+        &mut self,
+        items: &[Vec<Block>],
+        markers: Vec<String>,
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let mut out = Vec::new();
+        for (item, marker) in items.iter().zip(markers) {
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            let rest = " ".repeat(text_width(&marker) + 1);
+            let task = is_task(item).filter(|_| self.options.task_lists);
+            // `☐ ` becomes the wider `[ ] `
+            let extra = if task.is_some() { 2 } else { 0 };
+            let mut lines = self.blocks(item, narrow(width, rest.len() + extra));
+            if let (Some(checked), Some(first)) = (task, lines.first_mut()) {
+                let text: String = first.chars().skip(2).collect();
+                *first = format!("{} {}", if checked { "[x]" } else { "[ ]" }, text);
+            }
+            if lines.is_empty() {
+                lines.push(String::new());
+            }
+            out.extend(indent(lines, &format!("{} ", marker), &rest));
+        }
+        out
+    }
+
+    fn text(&mut self, inlines: &[Inline], width: Option<usize>) -> Vec<String> {
+        let mut pieces = Vec::new();
+        self.inlines(inlines, &mut pieces);
+        wrap(&pieces, width)
+            .into_iter()
+            .map(escape_line_start)
+            .collect()
+    }
+
+    /// Inline text on a single line, for headers and table cells. Line
+    /// breaks become spaces.
+    fn line(&mut self, inlines: &[Inline]) -> String {
+        let mut pieces = Vec::new();
+        self.inlines(inlines, &mut pieces);
+        let mut line = Vec::new();
+        for piece in pieces {
+            match piece {
+                Piece::Newline => {
+                    if let Some(Piece::Text(ref text)) = line.last() {
+                        if text == "\\" {
+                            line.pop();
+                        }
+                    }
+                    line.push(Piece::Space);
+                }
+                piece => line.push(piece),
+            }
+        }
+        wrap(&line, None).join(" ")
+    }
+
+    fn inlines(&mut self, inlines: &[Inline], out: &mut Vec<Piece>) {
+        for inline in inlines {
+            self.inline(inline, out);
+        }
+    }
+
+    /// Wraps the inlines in markup
+    fn delimited(&mut self, open: &str, inlines: &[Inline], close: &str, out: &mut Vec<Piece>) {
+        out.push(Piece::Text(open.to_string()));
+        self.inlines(inlines, out);
+        out.push(Piece::Text(close.to_string()));
+    }
+
+    fn inline(&mut self, inline: &Inline, out: &mut Vec<Piece>) {
+        match *inline {
+            Inline::Str(ref text) => out.push(Piece::Text(escape(text, self.options.strikeout))),
+            Inline::Emph(ref inlines) => self.delimited("*", inlines, "*", out),
+            Inline::Strong(ref inlines) => self.delimited("**", inlines, "**", out),
+            Inline::Strikeout(ref inlines) if self.options.strikeout => {
+                self.delimited("~~", inlines, "~~", out)
+            }
+            Inline::Strikeout(ref inlines) => self.delimited("<del>", inlines, "</del>", out),
+            Inline::Underline(ref inlines) => self.delimited("<u>", inlines, "</u>", out),
+            Inline::Superscript(ref inlines) => self.delimited("<sup>", inlines, "</sup>", out),
+            Inline::Subscript(ref inlines) => self.delimited("<sub>", inlines, "</sub>", out),
+            Inline::SmallCaps(ref inlines) => self.delimited(
+                "<span style=\"font-variant: small-caps;\">",
+                inlines,
+                "</span>",
+                out,
+            ),
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                self.delimited("\u{2018}", inlines, "\u{2019}", out)
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                self.delimited("\u{201c}", inlines, "\u{201d}", out)
+            }
+            Inline::Cite(_, ref inlines) => self.inlines(inlines, out),
+            Inline::Code(_, ref code) => {
+                let fence = fence(code, 1);
+                let pad = if code.starts_with(['`', ' ']) || code.ends_with(['`', ' ']) {
+                    " "
+                } else {
+                    ""
+                };
+                out.push(Piece::Text(format!(
+                    "{}{}{}{}{}",
+                    fence, pad, code, pad, fence
+                )));
+            }
+            Inline::Math(MathType::InlineMath, ref tex) => {
+                out.push(Piece::Text(format!("${}$", tex)))
+            }
+            Inline::Math(MathType::DisplayMath, ref tex) => {
+                out.push(Piece::Text(format!("$${}$$", tex)))
+            }
+            Inline::RawInline(ref format, ref text) if is_markdown(format) => {
+                out.push(Piece::Text(text.clone()))
+            }
+            Inline::RawInline(..) => {}
+            Inline::Space | Inline::SoftBreak => out.push(Piece::Space),
+            Inline::LineBreak => {
+                out.push(Piece::Text("\\".to_string()));
+                out.push(Piece::Newline);
+            }
+            Inline::Link(_, ref inlines, (ref url, ref title)) => {
+                // `!` in front of the link would make it an image
+                if let Some(Piece::Text(ref mut text)) = out.last_mut() {
+                    if text.ends_with('!') && !text.ends_with("\\!") {
+                        text.insert(text.len() - 1, '\\');
+                    }
+                }
+                let autolink = title.is_empty()
+                    && url.contains(':')
+                    && !url.contains([' ', '<', '>'])
+                    && matches!(inlines[..], [Inline::Str(ref text)] if text == url);
+                if autolink {
+                    out.push(Piece::Text(format!("<{}>", url)));
+                } else {
+                    self.delimited("[", inlines, "]", out);
+                    out.push(Piece::Text(destination(url, title)));
+                }
+            }
+            Inline::Image(_, ref inlines, (ref url, ref title)) => {
+                self.delimited("![", inlines, "]", out);
+                out.push(Piece::Text(destination(url, title)));
+            }
+            Inline::Note(ref blocks) => {
+                self.notes.push(blocks.clone());
+                let n = self.notes.len();
+                out.push(Piece::Text(if self.options.footnotes {
+                    format!("[^{}]", n)
+                } else {
+                    format!("<sup>{}</sup>", n)
+                }));
+            }
+            Inline::Span(ref attr, ref inlines) => {
+                if attr.0.is_empty() && attr.1.is_empty() && attr.2.is_empty() {
+                    self.inlines(inlines, out)
+                } else {
+                    let open = format!("<span{}>", html_attr(attr));
+                    self.delimited(&open, inlines, "</span>", out)
+                }
+            }
+        }
+    }
+
+    fn pipe_table(
+        &mut self,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> Option<Vec<String>> {
+        if !self.options.pipe_tables || head.1.len() > 1 {
+            return None;
+        }
+        let rows: Vec<&Row> = head
+            .1
+            .iter()
+            .chain(bodies.iter().flat_map(|body| body.2.iter().chain(&body.3)))
+            .chain(&foot.1)
+            .collect();
+        let cols = colspecs.len();
+        let simple = rows.iter().all(|row| {
+            row.1.len() == cols
+                && row.1.iter().all(|&(_, _, rowspan, colspan, ref blocks)| {
+                    rowspan <= 1 && colspan <= 1 && simple_cell(blocks).is_some()
+                })
+        });
+        if !simple || cols == 0 {
+            return None;
+        }
+        let mut cells: Vec<Vec<String>> = Vec::new();
+        if head.1.is_empty() {
+            cells.push(vec![String::new(); cols]);
+        }
+        for row in rows {
+            let row = row
+                .1
+                .iter()
+                .map(|cell| self.line(simple_cell(&cell.4).unwrap()).replace('|', "\\|"))
+                .collect();
+            cells.push(row);
+        }
+        let widths: Vec<usize> = (0..cols)
+            .map(|c| {
+                cells
+                    .iter()
+                    .map(|row| text_width(&row[c]))
+                    .max()
+                    .unwrap_or(0)
+                    .max(3)
+            })
+            .collect();
+        let line = |row: Vec<String>| {
+            let cells: Vec<String> = row
+                .into_iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:<1$}", cell, width))
+                .collect();
+            format!("| {} |", cells.join(" | ")).trim_end().to_string()
+        };
+        let mut cells = cells.into_iter();
+        let mut out = vec![line(cells.next().unwrap())];
+        let separator: Vec<String> = colspecs
+            .iter()
+            .zip(&widths)
+            .map(|(spec, &width)| match spec.0 {
+                Alignment::AlignLeft => format!(":{}", "-".repeat(width - 1)),
+                Alignment::AlignRight => format!("{}:", "-".repeat(width - 1)),
+                Alignment::AlignCenter => format!(":{}:", "-".repeat(width - 2)),
+                Alignment::AlignDefault => "-".repeat(width),
+            })
+            .collect();
+        out.push(format!("| {} |", separator.join(" | ")));
+        out.extend(cells.map(line));
+        Some(out)
+    }
+
+    /// A raw HTML table; blank lines around the cell contents let Markdown
+    /// parsers read them as Markdown
+    fn html_table(
+        &mut self,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let mut out = vec!["<table>".to_string()];
+        let mut section = |this: &mut Self, tag: &str, rows: &[Row], heads: usize| {
+            if rows.is_empty() {
+                return;
+            }
+            out.push(format!("<{}>", tag));
+            for row in rows {
+                out.push(format!("<tr{}>", html_attr(&row.0)));
+                for (i, cell) in row.1.iter().enumerate() {
+                    let (ref attr, align, rowspan, colspan, ref blocks) = *cell;
+                    let name = if tag == "thead" || i < heads {
+                        "th"
+                    } else {
+                        "td"
+                    };
+                    let align = match align {
+                        Alignment::AlignDefault => colspecs.get(i).map_or(align, |spec| spec.0),
+                        _ => align,
+                    };
+                    let mut open = format!("<{}{}", name, html_attr(attr));
+                    match align {
+                        Alignment::AlignLeft => open.push_str(" align=\"left\""),
+                        Alignment::AlignRight => open.push_str(" align=\"right\""),
+                        Alignment::AlignCenter => open.push_str(" align=\"center\""),
+                        Alignment::AlignDefault => {}
+                    }
+                    if rowspan > 1 {
+                        open.push_str(&format!(" rowspan=\"{}\"", rowspan));
+                    }
+                    if colspan > 1 {
+                        open.push_str(&format!(" colspan=\"{}\"", colspan));
+                    }
+                    out.push(format!("{}>", open));
+                    let content = this.blocks(blocks, width);
+                    if !content.is_empty() {
+                        out.push(String::new());
+                        out.extend(content);
+                        out.push(String::new());
+                    }
+                    out.push(format!("</{}>", name));
+                }
+                out.push("</tr>".to_string());
+            }
+            out.push(format!("</{}>", tag));
+        };
+        section(self, "thead", &head.1, 0);
+        for body in bodies {
+            let heads = body.1 as usize;
+            section(self, "tbody", &body.2, usize::MAX);
+            section(self, "tbody", &body.3, heads);
+        }
+        section(self, "tfoot", &foot.1, 0);
+        out.push("</table>".to_string());
+        out
+    }
+}
+
+/// The `(url "title")` part of a link or image
+fn destination(url: &str, title: &str) -> String {
+    let url = if url.is_empty() || url.contains([' ', '(', ')', '<', '>']) {
+        format!("<{}>", url.replace('<', "%3C").replace('>', "%3E"))
+    } else {
+        url.to_string()
+    };
+    if title.is_empty() {
+        format!("({})", url)
+    } else {
+        format!("({} \"{}\")", url, title.replace('"', "\\\""))
+    }
+}
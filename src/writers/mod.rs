@@ -0,0 +1,364 @@
+//! Writers rendering documents in other formats without running pandoc
+
+pub mod docbook;
+pub mod html;
+pub mod latex;
+pub mod markdown;
+pub mod org;
+pub mod plain;
+pub mod rst;
+pub mod typst;
+
+use super::*;
+
+/// A piece of inline text to be wrapped into lines
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Piece {
+    /// Text the line must not be broken in
+    Text(String),
+    /// A space the line may be broken at
+    Space,
+    /// A forced line break
+    Newline,
+}
+
+fn text_width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// Fills lines up to `width` characters, words longer than that get a line
+/// of their own. Without a width lines are only broken at `Newline`s.
+fn wrap(pieces: &[Piece], width: Option<usize>) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut word = String::new();
+    let mut space = false;
+    for piece in pieces.iter().chain(Some(&Piece::Space)) {
+        let text = match *piece {
+            Piece::Text(ref text) => {
+                word.push_str(text);
+                continue;
+            }
+            _ => std::mem::take(&mut word),
+        };
+        if !text.is_empty() {
+            let gap = usize::from(space);
+            let fits =
+                width.is_none_or(|width| text_width(&line) + gap + text_width(&text) <= width);
+            if line.is_empty() || !space {
+                line.push_str(&text);
+            } else if fits {
+                line.push(' ');
+                line.push_str(&text);
+            } else {
+                lines.push(std::mem::replace(&mut line, text));
+            }
+        }
+        match *piece {
+            Piece::Newline => {
+                lines.push(std::mem::take(&mut line));
+                space = false;
+            }
+            _ => space = !line.is_empty(),
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Prefixes the first line with `first` and all others with `rest`
+fn indent(lines: Vec<String>, first: &str, rest: &str) -> Vec<String> {
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let prefix = if i == 0 { first } else { rest };
+            if line.is_empty() {
+                prefix.trim_end().to_string()
+            } else {
+                format!("{}{}", prefix, line)
+            }
+        })
+        .collect()
+}
+
+/// The width left after indenting by `by` characters
+fn narrow(width: Option<usize>, by: usize) -> Option<usize> {
+    width.map(|width| width.saturating_sub(by).max(1))
+}
+
+/// The numbers of an ordered list, like `1.`, `ii)` or `(C)`
+fn ordered_markers(attrs: &ListAttributes, count: usize) -> Vec<String> {
+    let (start, style, delim) = *attrs;
+    (0..count as Int)
+        .map(|i| {
+            let number = style.format(start + i);
+            match delim {
+                ListNumberDelim::OneParen => format!("{})", number),
+                ListNumberDelim::TwoParens => format!("({})", number),
+                ListNumberDelim::DefaultDelim | ListNumberDelim::Period => format!("{}.", number),
+            }
+        })
+        .collect()
+}
+
+/// A table cell with its position in the grid of a table
+struct GridCell<'a> {
+    row: usize,
+    col: usize,
+    rows: usize,
+    cols: usize,
+    align: Alignment,
+    blocks: &'a [Block],
+}
+
+/// The rows of a table's head, bodies and foot with row and column spans
+/// resolved
+struct Grid<'a> {
+    rows: usize,
+    cols: usize,
+    /// Number of rows in the head and foot
+    head: usize,
+    foot: usize,
+    widths: Vec<ColWidth>,
+    cells: Vec<GridCell<'a>>,
+}
+
+impl<'a> Grid<'a> {
+    fn new(
+        colspecs: &[ColSpec],
+        head: &'a TableHead,
+        bodies: &'a [TableBody],
+        foot: &'a TableFoot,
+    ) -> Self {
+        let mut rows: Vec<&'a Row> = head.1.iter().collect();
+        for body in bodies {
+            rows.extend(body.2.iter().chain(&body.3));
+        }
+        rows.extend(&foot.1);
+        let cols = colspecs.len().max(1);
+        let mut taken = vec![vec![false; cols]; rows.len()];
+        let mut cells = Vec::new();
+        for (r, row) in rows.iter().enumerate() {
+            let mut c = 0;
+            for cell in &row.1 {
+                while c < cols && taken[r][c] {
+                    c += 1;
+                }
+                if c == cols {
+                    break;
+                }
+                let (_, align, rowspan, colspan, ref blocks) = *cell;
+                let row_count = (rowspan.max(1) as usize).min(rows.len() - r);
+                let col_count = (colspan.max(1) as usize).min(cols - c);
+                for taken in &mut taken[r..r + row_count] {
+                    for taken in &mut taken[c..c + col_count] {
+                        *taken = true;
+                    }
+                }
+                let align = match align {
+                    Alignment::AlignDefault => colspecs.get(c).map_or(align, |spec| spec.0),
+                    _ => align,
+                };
+                cells.push(GridCell {
+                    row: r,
+                    col: c,
+                    rows: row_count,
+                    cols: col_count,
+                    align,
+                    blocks,
+                });
+                c += col_count;
+            }
+        }
+        // rows with too few cells are padded with empty ones
+        for (r, taken) in taken.iter().enumerate() {
+            for (c, _) in taken.iter().enumerate().filter(|(_, taken)| !**taken) {
+                cells.push(GridCell {
+                    row: r,
+                    col: c,
+                    rows: 1,
+                    cols: 1,
+                    align: colspecs
+                        .get(c)
+                        .map_or(Alignment::AlignDefault, |spec| spec.0),
+                    blocks: &[],
+                });
+            }
+        }
+        Grid {
+            rows: rows.len(),
+            cols,
+            head: head.1.len(),
+            foot: foot.1.len(),
+            widths: colspecs.iter().map(|spec| spec.1).collect(),
+            cells,
+        }
+    }
+
+    /// Draws the table as a grid table like
+    ///
+    /// ```text
+    /// +-----+-----+
+    /// | a   | b   |
+    /// +=====+=====+
+    /// | 1         |
+    /// +-----+-----+
+    /// ```
+    ///
+    /// Columns with a relative width get that share of `width`, the others
+    /// are as wide as their content. `render` lays out the cell contents.
+    fn draw(
+        &self,
+        width: Option<usize>,
+        mut render: impl FnMut(&'a [Block], Option<usize>) -> Vec<String>,
+    ) -> Vec<String> {
+        if self.rows == 0 {
+            return Vec::new();
+        }
+        let fixed: Vec<Option<usize>> = (0..self.cols)
+            .map(|c| match (self.widths.get(c), width) {
+                (Some(ColWidth::ColWidth(share)), Some(width)) => {
+                    let available = width.saturating_sub(3 * self.cols + 1) as f64;
+                    Some(((share * available) as usize).max(1))
+                }
+                _ => None,
+            })
+            .collect();
+        let contents: Vec<Vec<String>> = self
+            .cells
+            .iter()
+            .map(|cell| {
+                let width = fixed[cell.col..cell.col + cell.cols]
+                    .iter()
+                    .try_fold(3 * (cell.cols - 1), |sum, w| w.map(|w| sum + w));
+                render(cell.blocks, width)
+            })
+            .collect();
+
+        // widen columns and rows to fit the contents, single cells first
+        let mut widths: Vec<usize> = fixed.iter().map(|w| w.unwrap_or(1)).collect();
+        let mut heights = vec![1; self.rows];
+        let mut order: Vec<usize> = (0..self.cells.len()).collect();
+        order.sort_by_key(|&i| (self.cells[i].cols, self.cells[i].rows));
+        for &i in &order {
+            let cell = &self.cells[i];
+            let needed = contents[i].iter().map(|l| text_width(l)).max().unwrap_or(0);
+            let span = &mut widths[cell.col..cell.col + cell.cols];
+            let have = span.iter().sum::<usize>() + 3 * (cell.cols - 1);
+            if needed > have {
+                *span.last_mut().unwrap() += needed - have;
+            }
+            let span = &mut heights[cell.row..cell.row + cell.rows];
+            let have = span.iter().sum::<usize>() + cell.rows - 1;
+            if contents[i].len() > have {
+                *span.last_mut().unwrap() += contents[i].len() - have;
+            }
+        }
+
+        let mut xs = vec![0];
+        for w in &widths {
+            xs.push(xs.last().unwrap() + w + 3);
+        }
+        let mut ys = vec![0];
+        for h in &heights {
+            ys.push(ys.last().unwrap() + h + 1);
+        }
+        let separators = [
+            Some(self.head).filter(|&h| h > 0 && h < self.rows),
+            Some(self.rows - self.foot).filter(|&f| f > 0 && f < self.rows),
+        ];
+        let mut canvas = vec![vec![' '; xs[self.cols] + 1]; ys[self.rows] + 1];
+        let mut set = |y: usize, x: usize, c: char| {
+            if canvas[y][x] != '+' {
+                canvas[y][x] = c;
+            }
+        };
+        for (cell, lines) in self.cells.iter().zip(contents) {
+            let (x0, x1) = (xs[cell.col], xs[cell.col + cell.cols]);
+            let (y0, y1) = (ys[cell.row], ys[cell.row + cell.rows]);
+            for y in [y0, y1] {
+                let row = ys.iter().position(|&b| b == y);
+                let c = if separators.contains(&row) { '=' } else { '-' };
+                for x in x0 + 1..x1 {
+                    set(y, x, c);
+                }
+            }
+            for y in y0 + 1..y1 {
+                set(y, x0, '|');
+                set(y, x1, '|');
+            }
+            for (y, x) in [(y0, x0), (y0, x1), (y1, x0), (y1, x1)] {
+                set(y, x, '+');
+            }
+            let inner = x1 - x0 - 3;
+            for (i, line) in lines.iter().enumerate() {
+                let pad = inner.saturating_sub(text_width(line));
+                let left = match cell.align {
+                    Alignment::AlignRight => pad,
+                    Alignment::AlignCenter => pad / 2,
+                    _ => 0,
+                };
+                for (j, c) in line.chars().enumerate() {
+                    set(y0 + 1 + i, x0 + 2 + left + j, c);
+                }
+            }
+        }
+        canvas
+            .into_iter()
+            .map(|line| line.into_iter().collect::<String>().trim_end().to_string())
+            .collect()
+    }
+}
+
+/// Escapes `&`, `<`, `>` and `"` for use in HTML text and attribute values
+pub fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether `key` is an XML name like `data-x` or `xml:lang`, i.e. matches
+/// `[A-Za-z_:][-A-Za-z0-9_:.]*`
+fn is_attr_name(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == ':')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+}
+
+/// Renders attributes as HTML attributes with a leading space, like
+/// ` id="intro" class="a b" data-x="1"`. Keys that aren't known HTML
+/// attributes get a `data-` prefix, keys that aren't names at all are
+/// dropped.
+pub fn html_attr(attr: &Attr) -> String {
+    let (ref id, ref classes, ref kvs) = *attr;
+    let mut out = String::new();
+    if !id.is_empty() {
+        out.push_str(&format!(" id=\"{}\"", escape_html(id)));
+    }
+    if !classes.is_empty() {
+        out.push_str(&format!(" class=\"{}\"", escape_html(&classes.join(" "))));
+    }
+    for (key, value) in kvs.iter().filter(|(key, _)| is_attr_name(key)) {
+        let known = matches!(
+            &key[..],
+            "style" | "title" | "lang" | "dir" | "width" | "height" | "align"
+        ) || key.starts_with("data-")
+            || key.starts_with("aria-");
+        let prefix = if known { "" } else { "data-" };
+        out.push_str(&format!(" {}{}=\"{}\"", prefix, key, escape_html(value)));
+    }
+    out
+}
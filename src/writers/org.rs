@@ -0,0 +1,482 @@
+//! Emacs Org mode
+//!
+//! Header identifiers and attributes go into properties drawers, identifiers
+//! pandoc's org reader would generate anyway are left out. Org tables can't
+//! span cells, spanned cells are left empty.
+
+use super::*;
+
+/// Options for the Org writer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrgOptions {
+    /// Wrap lines at this many characters, or not at all
+    pub width: Option<usize>,
+}
+
+impl Default for OrgOptions {
+    fn default() -> Self {
+        OrgOptions { width: Some(72) }
+    }
+}
+
+/// Renders a document as Org, with the title, author and date from the
+/// metadata as `#+title:` lines and the like
+pub fn write(pandoc: &Pandoc, options: &OrgOptions) -> String {
+    let mut writer = Writer::default();
+    let mut lines = Vec::new();
+    for key in ["title", "author", "date"] {
+        let values = match pandoc.meta.get(key) {
+            Some(MetaValue::MetaList(values)) => values.iter().collect(),
+            Some(value) => vec![value],
+            None => vec![],
+        };
+        for value in values {
+            let text = match *value {
+                MetaValue::MetaInlines(ref inlines) => writer.line(inlines),
+                ref other => other.stringify(),
+            };
+            lines.push(format!("#+{}: {}", key, text));
+        }
+    }
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+    lines.extend(writer.document(&pandoc.blocks, options.width));
+    finish(lines)
+}
+
+/// Renders blocks as Org
+pub fn write_blocks(blocks: &[Block], options: &OrgOptions) -> String {
+    finish(Writer::default().document(blocks, options.width))
+}
+
+fn finish(lines: Vec<String>) -> String {
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn is_org(format: &Format) -> bool {
+    format.base_name() == "org"
+}
+
+/// Escapes lines of a source block that Org would read as headlines or
+/// keywords with a comma after the indentation
+fn escape_code(code: &str) -> Vec<String> {
+    code.lines()
+        .map(|line| {
+            let content = line.trim_start();
+            let unescaped = content.trim_start_matches(',');
+            if unescaped.starts_with('*') || unescaped.starts_with("#+") {
+                let indentation = &line[..line.len() - content.len()];
+                format!("{},{}", indentation, content)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Puts a zero width space, Org's escape character, in front of a line of
+/// text that would be read as a headline, a list item, a table, a rule, a
+/// keyword, a comment or a footnote definition
+fn escape_line_start(line: String) -> String {
+    let marker = line.split(' ').next().unwrap_or("");
+    let number = marker
+        .strip_suffix(['.', ')'])
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()));
+    let stars = !marker.is_empty() && marker.chars().all(|c| c == '*');
+    let rule = marker.len() >= 5 && line.chars().all(|c| c == '-');
+    let escape = matches!(marker, "-" | "+" | "#" | ":")
+        || stars
+        || number
+        || rule
+        || line.starts_with("#+")
+        || line.starts_with('|')
+        || line.starts_with("[fn:");
+    if escape {
+        format!("\u{200b}{}", line)
+    } else {
+        line
+    }
+}
+
+/// A block like `#+begin_quote` around the lines
+fn greater_block(name: &str, args: &str, lines: Vec<String>) -> Vec<String> {
+    let mut out = vec![format!("#+begin_{}{}", name, args)];
+    out.extend(lines);
+    out.push(format!("#+end_{}", name));
+    out
+}
+
+#[derive(Default)]
+struct Writer {
+    notes: Vec<Vec<Block>>,
+    ids: IdGenerator,
+}
+
+impl Writer {
+    /// Blocks followed by the definitions of the footnotes they use
+    fn document(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        let mut lines = self.blocks(blocks, width);
+        // notes can contain notes themselves, which get appended while
+        // rendering
+        let mut i = 0;
+        while i < self.notes.len() {
+            let note = self.notes[i].clone();
+            let note = self.blocks(&note, width);
+            lines.push(String::new());
+            match note {
+                note if note.is_empty() => lines.push(format!("[fn:{}]", i + 1)),
+                note => lines.extend(indent(note, &format!("[fn:{}] ", i + 1), "")),
+            }
+            i += 1;
+        }
+        lines
+    }
+
+    fn blocks(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        let is_list = |block: Option<&Block>| {
+            matches!(
+                block,
+                Some(Block::BulletList(_) | Block::OrderedList(..) | Block::DefinitionList(_))
+            )
+        };
+        let mut out = Vec::new();
+        let mut previous = None;
+        for block in blocks {
+            let lines = self.block(block, width);
+            if lines.is_empty() {
+                continue;
+            }
+            if !out.is_empty() {
+                out.push(String::new());
+            }
+            // two blank lines end a list, or the next one would continue it
+            if is_list(previous) && is_list(Some(block)) {
+                out.push(String::new());
+            }
+            previous = Some(block);
+            out.extend(lines);
+        }
+        out
+    }
+
+    fn block(&mut self, block: &Block, width: Option<usize>) -> Vec<String> {
+        match *block {
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => self.text(inlines, width),
+            Block::LineBlock(ref lines) => {
+                let lines = lines.iter().map(|line| self.line(line)).collect();
+                greater_block("verse", "", lines)
+            }
+            Block::Header(level, (ref id, ref classes, ref kvs), ref inlines) => {
+                let stars = "*".repeat(level.max(1) as usize);
+                let mut out = vec![format!("{} {}", stars, self.line(inlines))];
+                let mut properties = Vec::new();
+                let mut probe = self.ids.clone();
+                if !id.is_empty() && *id == probe.generate(inlines) {
+                    self.ids = probe;
+                } else if !id.is_empty() {
+                    self.ids.reserve(id.clone());
+                    properties.push(format!(":CUSTOM_ID: {}", id));
+                }
+                if classes.iter().any(|c| c == "unnumbered") {
+                    properties.push(":UNNUMBERED: t".to_string());
+                }
+                for (key, value) in kvs {
+                    properties.push(format!(":{}: {}", key, value));
+                }
+                if !properties.is_empty() {
+                    out.push(":PROPERTIES:".to_string());
+                    out.extend(properties);
+                    out.push(":END:".to_string());
+                }
+                out
+            }
+            Block::CodeBlock((_, ref classes, _), ref code) => match classes.first() {
+                Some(language) => {
+                    greater_block("src", &format!(" {}", language), escape_code(code))
+                }
+                None => greater_block("example", "", escape_code(code)),
+            },
+            Block::RawBlock(ref format, ref text) if is_org(format) => {
+                text.lines().map(String::from).collect()
+            }
+            Block::RawBlock(ref format, ref text) => greater_block(
+                "export",
+                &format!(" {}", format.base_name()),
+                text.lines().map(String::from).collect(),
+            ),
+            Block::Null => Vec::new(),
+            Block::BlockQuote(ref blocks) => greater_block("quote", "", self.blocks(blocks, width)),
+            Block::BulletList(ref items) => {
+                self.list(items, vec!["-".to_string(); items.len()], width)
+            }
+            Block::OrderedList((start, _, delim), ref items) => {
+                let delim = if delim == ListNumberDelim::OneParen {
+                    ')'
+                } else {
+                    '.'
+                };
+                let mut markers: Vec<String> = (0..items.len() as Int)
+                    .map(|i| format!("{}{}", start + i, delim))
+                    .collect();
+                // Org counts from one unless told otherwise
+                if start != 1 {
+                    if let Some(first) = markers.first_mut() {
+                        first.push_str(&format!(" [@{}]", start));
+                    }
+                }
+                self.list(items, markers, width)
+            }
+            Block::DefinitionList(ref items) => {
+                let mut out = Vec::new();
+                for (term, definitions) in items {
+                    let mut lines = Vec::new();
+                    for definition in definitions {
+                        if !lines.is_empty() {
+                            lines.push(String::new());
+                        }
+                        lines.extend(self.blocks(definition, narrow(width, 2)));
+                    }
+                    if lines.is_empty() {
+                        lines.push(String::new());
+                    }
+                    let marker = format!("- {} :: ", self.line(term));
+                    out.extend(indent(lines, &marker, "  "));
+                }
+                out
+            }
+            Block::Figure((ref id, _, _), ref caption, ref content) => {
+                let mut out = self.affiliated(id, &caption.1);
+                out.extend(self.blocks(content, width));
+                out
+            }
+            Block::HorizontalRule => vec!["-----".to_string()],
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                let mut out = self.affiliated(&attr.0, &caption.1);
+                out.extend(self.table(colspecs, head, bodies, foot));
+                out
+            }
+            Block::Div((ref id, ref classes, _), ref blocks) => {
+                let mut out = Vec::new();
+                if !id.is_empty() {
+                    out.push(format!("<<{}>>", id));
+                }
+                let content = self.blocks(blocks, width);
+                match classes.first() {
+                    Some(class) => out.extend(greater_block(class, "", content)),
+                    None => out.extend(content),
+                }
+                out
+            }
+        }
+    }
+
+    /// `#+caption:` and `#+name:` lines for the element that follows
+    fn affiliated(&mut self, id: &str, caption: &[Block]) -> Vec<String> {
+        let mut out = Vec::new();
+        let caption: Vec<String> = caption
+            .iter()
+            .filter_map(|block| match *block {
+                Block::Plain(ref inlines) | Block::Para(ref inlines) => Some(self.line(inlines)),
+                _ => None,
+            })
+            .collect();
+        if !caption.is_empty() {
+            out.push(format!("#+caption: {}", caption.join(" ")));
+        }
+        if !id.is_empty() {
+            out.push(format!("#+name: {}", id));
+        }
+        out
+    }
+
+    fn list(
+        &mut self,
+        items: &[Vec<Block>],
+        markers: Vec<String>,
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let mut out = Vec::new();
+        for (item, marker) in items.iter().zip(markers) {
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            // the `[@n]` cookie doesn't count towards the indentation
+            let number = marker.split(' ').next().unwrap_or("");
+            let rest = " ".repeat(text_width(number) + 1);
+            let mut lines = Vec::new();
+            for block in item {
+                let block = self.block(block, narrow(width, rest.len()));
+                // nested lists in tight items follow without a blank line
+                if !lines.is_empty() && !block.is_empty() && !tight {
+                    lines.push(String::new());
+                }
+                lines.extend(block);
+            }
+            if lines.is_empty() {
+                lines.push(String::new());
+            }
+            out.extend(indent(lines, &format!("{} ", marker), &rest));
+        }
+        out
+    }
+
+    /// A table without spans, the head separated by a rule
+    fn table(
+        &mut self,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> Vec<String> {
+        let grid = Grid::new(colspecs, head, bodies, foot);
+        let mut rows = vec![vec![String::new(); grid.cols]; grid.rows];
+        for cell in &grid.cells {
+            let inlines: Vec<String> = cell
+                .blocks
+                .iter()
+                .filter_map(|block| match *block {
+                    Block::Plain(ref inlines) | Block::Para(ref inlines) => {
+                        Some(self.line(inlines))
+                    }
+                    _ => None,
+                })
+                .collect();
+            rows[cell.row][cell.col] = inlines.join(" ").replace('|', "\\vert{}");
+        }
+        let widths: Vec<usize> = (0..grid.cols)
+            .map(|c| {
+                rows.iter()
+                    .map(|row| text_width(&row[c]))
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let rule: Vec<String> = widths.iter().map(|&w| "-".repeat(w + 2)).collect();
+        let rule = format!("|{}|", rule.join("+"));
+        let mut out = Vec::new();
+        for (r, row) in rows.into_iter().enumerate() {
+            if r > 0 && r == grid.head {
+                out.push(rule.clone());
+            }
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, &width)| format!(" {:<1$} ", cell, width))
+                .collect();
+            out.push(format!("|{}|", cells.join("|")));
+        }
+        out
+    }
+
+    fn text(&mut self, inlines: &[Inline], width: Option<usize>) -> Vec<String> {
+        let mut pieces = Vec::new();
+        self.inlines(inlines, &mut pieces);
+        wrap(&pieces, width)
+            .into_iter()
+            .map(escape_line_start)
+            .collect()
+    }
+
+    /// Inline text on a single line, for headlines, keywords and cells
+    fn line(&mut self, inlines: &[Inline]) -> String {
+        let mut pieces = Vec::new();
+        self.inlines(inlines, &mut pieces);
+        let pieces: Vec<Piece> = pieces
+            .into_iter()
+            .filter(|piece| *piece != Piece::Text("\\\\".to_string()))
+            .map(|piece| match piece {
+                Piece::Newline => Piece::Space,
+                piece => piece,
+            })
+            .collect();
+        wrap(&pieces, None).join(" ")
+    }
+
+    fn inlines(&mut self, inlines: &[Inline], out: &mut Vec<Piece>) {
+        for inline in inlines {
+            self.inline(inline, out);
+        }
+    }
+
+    fn delimited(&mut self, open: &str, inlines: &[Inline], close: &str, out: &mut Vec<Piece>) {
+        out.push(Piece::Text(open.to_string()));
+        self.inlines(inlines, out);
+        out.push(Piece::Text(close.to_string()));
+    }
+
+    fn inline(&mut self, inline: &Inline, out: &mut Vec<Piece>) {
+        match *inline {
+            Inline::Str(ref text) => out.push(Piece::Text(text.clone())),
+            Inline::Emph(ref inlines) => self.delimited("/", inlines, "/", out),
+            Inline::Strong(ref inlines) => self.delimited("*", inlines, "*", out),
+            Inline::Underline(ref inlines) => self.delimited("_", inlines, "_", out),
+            Inline::Strikeout(ref inlines) => self.delimited("+", inlines, "+", out),
+            Inline::Superscript(ref inlines) => self.delimited("^{", inlines, "}", out),
+            Inline::Subscript(ref inlines) => self.delimited("_{", inlines, "}", out),
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                self.delimited("'", inlines, "'", out)
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                self.delimited("\"", inlines, "\"", out)
+            }
+            Inline::SmallCaps(ref inlines) | Inline::Cite(_, ref inlines) => {
+                self.inlines(inlines, out)
+            }
+            Inline::Code(_, ref code) => {
+                // verbatim can't contain its own delimiter
+                let mark = if code.contains('=') && !code.contains('~') {
+                    '~'
+                } else {
+                    '='
+                };
+                out.push(Piece::Text(format!("{0}{1}{0}", mark, code)))
+            }
+            Inline::Math(MathType::InlineMath, ref tex) => {
+                out.push(Piece::Text(format!("\\({}\\)", tex)))
+            }
+            Inline::Math(MathType::DisplayMath, ref tex) => {
+                out.push(Piece::Text(format!("\\[{}\\]", tex)))
+            }
+            Inline::RawInline(ref format, ref text) if is_org(format) => {
+                out.push(Piece::Text(text.clone()))
+            }
+            Inline::RawInline(ref format, ref text) => {
+                out.push(Piece::Text(format!("@@{}:{}@@", format.base_name(), text)))
+            }
+            Inline::Space | Inline::SoftBreak => out.push(Piece::Space),
+            Inline::LineBreak => {
+                out.push(Piece::Text("\\\\".to_string()));
+                out.push(Piece::Newline);
+            }
+            Inline::Link(_, ref inlines, (ref url, _)) => {
+                if matches!(inlines[..], [Inline::Str(ref text)] if text == url) {
+                    return out.push(Piece::Text(format!("[[{}]]", url)));
+                }
+                self.delimited(&format!("[[{}][", url), inlines, "]]", out)
+            }
+            Inline::Image(_, _, (ref url, _)) => {
+                let scheme = url.split_once(':').is_some_and(|(scheme, _)| {
+                    scheme.len() > 1 && scheme.chars().all(|c| c.is_ascii_alphabetic())
+                });
+                let prefix = if scheme { "" } else { "file:" };
+                out.push(Piece::Text(format!("[[{}{}]]", prefix, url)))
+            }
+            Inline::Note(ref blocks) => {
+                self.notes.push(blocks.clone());
+                out.push(Piece::Text(format!("[fn:{}]", self.notes.len())));
+            }
+            Inline::Span((ref id, _, _), ref inlines) => {
+                if !id.is_empty() {
+                    out.push(Piece::Text(format!("<<{}>>", id)));
+                }
+                self.inlines(inlines, out)
+            }
+        }
+    }
+}
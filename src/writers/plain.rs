@@ -0,0 +1,211 @@
+//! Plain text without markup, like pandoc's `plain` format
+//!
+//! Formatting is dropped, links and images are reduced to their text and
+//! footnotes are numbered like `[1]` and listed at the end. Tables are drawn
+//! as grid tables.
+
+use super::*;
+
+/// Options for the plain text writer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PlainOptions {
+    /// Wrap lines at this many characters, or not at all
+    pub width: Option<usize>,
+}
+
+impl Default for PlainOptions {
+    fn default() -> Self {
+        PlainOptions { width: Some(72) }
+    }
+}
+
+/// Renders the blocks of a document as plain text
+pub fn write(pandoc: &Pandoc, options: &PlainOptions) -> String {
+    write_blocks(&pandoc.blocks, options)
+}
+
+/// Renders blocks as plain text
+pub fn write_blocks(blocks: &[Block], options: &PlainOptions) -> String {
+    let mut writer = Writer { notes: Vec::new() };
+    let mut lines = writer.blocks(blocks, options.width);
+    // notes can contain notes themselves, which get appended while rendering
+    let mut i = 0;
+    while i < writer.notes.len() {
+        let marker = format!("[{}] ", i + 1);
+        let note = writer.blocks(writer.notes[i], narrow(options.width, 4));
+        lines.push(String::new());
+        lines.extend(indent(note, &marker, "    "));
+        i += 1;
+    }
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+struct Writer<'a> {
+    notes: Vec<&'a [Block]>,
+}
+
+impl<'a> Writer<'a> {
+    fn blocks(&mut self, blocks: &'a [Block], width: Option<usize>) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut tight = false;
+        for block in blocks {
+            let lines = self.block(block, width);
+            if lines.is_empty() {
+                continue;
+            }
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            tight = matches!(block, Block::Plain(_));
+            out.extend(lines);
+        }
+        out
+    }
+
+    fn block(&mut self, block: &'a Block, width: Option<usize>) -> Vec<String> {
+        match *block {
+            Block::Plain(ref inlines)
+            | Block::Para(ref inlines)
+            | Block::Header(_, _, ref inlines) => self.text(inlines, width),
+            Block::LineBlock(ref lines) => lines
+                .iter()
+                .flat_map(|line| match self.text(line, width) {
+                    lines if lines.is_empty() => vec![String::new()],
+                    lines => lines,
+                })
+                .collect(),
+            Block::CodeBlock(_, ref code) => {
+                indent(code.lines().map(String::from).collect(), "    ", "    ")
+            }
+            Block::RawBlock(ref format, ref text) if format.matches(&Format::new("plain")) => {
+                text.lines().map(String::from).collect()
+            }
+            Block::RawBlock(..) | Block::Null => Vec::new(),
+            Block::BlockQuote(ref blocks) => {
+                indent(self.blocks(blocks, narrow(width, 2)), "  ", "  ")
+            }
+            Block::OrderedList(ref attrs, ref items) => {
+                self.list(items, ordered_markers(attrs, items.len()), width)
+            }
+            Block::BulletList(ref items) => {
+                self.list(items, vec!["-".to_string(); items.len()], width)
+            }
+            Block::DefinitionList(ref items) => {
+                let mut out = Vec::new();
+                for (term, definitions) in items {
+                    if !out.is_empty() {
+                        out.push(String::new());
+                    }
+                    out.extend(self.text(term, width));
+                    for definition in definitions {
+                        if matches!(definition.first(), Some(Block::Para(_))) {
+                            out.push(String::new());
+                        }
+                        let lines = self.blocks(definition, narrow(width, 4));
+                        out.extend(indent(lines, "    ", "    "));
+                    }
+                }
+                out
+            }
+            Block::Figure(_, ref caption, ref blocks) => {
+                let mut out = self.blocks(blocks, width);
+                let caption = self.blocks(&caption.1, width);
+                if !out.is_empty() && !caption.is_empty() {
+                    out.push(String::new());
+                }
+                out.extend(caption);
+                out
+            }
+            Block::HorizontalRule => vec!["-".repeat(width.unwrap_or(72))],
+            Block::Table(_, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                let grid = Grid::new(colspecs, head, bodies, foot);
+                let mut out = grid.draw(width, |blocks, width| self.blocks(blocks, width));
+                let caption = self.blocks(&caption.1, width);
+                if !out.is_empty() && !caption.is_empty() {
+                    out.push(String::new());
+                }
+                out.extend(caption);
+                out
+            }
+            Block::Div(_, ref blocks) => self.blocks(blocks, width),
+        }
+    }
+
+    fn list(
+        &mut self,
+        items: &'a [Vec<Block>],
+        markers: Vec<String>,
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let marker_width = markers.iter().map(|m| text_width(m)).max().unwrap_or(0) + 1;
+        let rest = " ".repeat(marker_width);
+        let mut out = Vec::new();
+        for (item, marker) in items.iter().zip(markers) {
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            let first = format!("{:<1$}", marker, marker_width);
+            match self.blocks(item, narrow(width, marker_width)) {
+                lines if lines.is_empty() => out.push(marker),
+                lines => out.extend(indent(lines, &first, &rest)),
+            }
+        }
+        out
+    }
+
+    fn text(&mut self, inlines: &'a [Inline], width: Option<usize>) -> Vec<String> {
+        let mut pieces = Vec::new();
+        self.inlines(inlines, &mut pieces);
+        wrap(&pieces, width)
+    }
+
+    fn inlines(&mut self, inlines: &'a [Inline], out: &mut Vec<Piece>) {
+        for inline in inlines {
+            self.inline(inline, out);
+        }
+    }
+
+    fn inline(&mut self, inline: &'a Inline, out: &mut Vec<Piece>) {
+        match *inline {
+            Inline::Str(ref text) => out.push(Piece::Text(text.clone())),
+            Inline::Emph(ref inlines)
+            | Inline::Underline(ref inlines)
+            | Inline::Strong(ref inlines)
+            | Inline::Strikeout(ref inlines)
+            | Inline::Superscript(ref inlines)
+            | Inline::Subscript(ref inlines)
+            | Inline::SmallCaps(ref inlines)
+            | Inline::Cite(_, ref inlines)
+            | Inline::Link(_, ref inlines, _)
+            | Inline::Image(_, ref inlines, _)
+            | Inline::Span(_, ref inlines) => self.inlines(inlines, out),
+            Inline::Quoted(quote, ref inlines) => {
+                let (open, close) = match quote {
+                    QuoteType::SingleQuote => ("\u{2018}", "\u{2019}"),
+                    QuoteType::DoubleQuote => ("\u{201c}", "\u{201d}"),
+                };
+                out.push(Piece::Text(open.to_string()));
+                self.inlines(inlines, out);
+                out.push(Piece::Text(close.to_string()));
+            }
+            Inline::Code(_, ref text) | Inline::Math(_, ref text) => {
+                out.push(Piece::Text(text.clone()))
+            }
+            Inline::RawInline(ref format, ref text) if format.matches(&Format::new("plain")) => {
+                out.push(Piece::Text(text.clone()))
+            }
+            Inline::RawInline(..) => {}
+            Inline::Space | Inline::SoftBreak => out.push(Piece::Space),
+            Inline::LineBreak => out.push(Piece::Newline),
+            Inline::Note(ref blocks) => {
+                self.notes.push(blocks);
+                out.push(Piece::Text(format!("[{}]", self.notes.len())));
+            }
+        }
+    }
+}
@@ -0,0 +1,662 @@
+//! reStructuredText, as read by docutils and Sphinx
+//!
+//! Inline markup can't be nested in RST, markup inside other markup is
+//! reduced to its text. Superscripts, subscripts and math use the standard
+//! roles. Notes become numbered footnotes and inline images substitutions,
+//! both defined at the end of the document.
+
+use super::*;
+use std::collections::HashSet;
+
+/// Options for the reStructuredText writer
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RstOptions {
+    /// Wrap lines at this many characters, or not at all
+    pub width: Option<usize>,
+}
+
+impl Default for RstOptions {
+    fn default() -> Self {
+        RstOptions { width: Some(72) }
+    }
+}
+
+/// Characters underlining headers of level 1 to 6
+const ADORNMENTS: [char; 6] = ['=', '-', '~', '^', '\'', '`'];
+
+/// Div classes written as admonition directives
+const ADMONITIONS: [&str; 9] = [
+    "attention",
+    "caution",
+    "danger",
+    "error",
+    "hint",
+    "important",
+    "note",
+    "tip",
+    "warning",
+];
+
+/// Renders a document as reStructuredText, with the title and subtitle from
+/// the metadata as the document title and the authors and date as
+/// bibliographic fields
+pub fn write(pandoc: &Pandoc, options: &RstOptions) -> String {
+    let mut writer = Writer::default();
+    let mut lines = Vec::new();
+    for (key, adornment) in [("title", '='), ("subtitle", '-')] {
+        if let Some(value) = pandoc.meta.get(key) {
+            let title = writer.line(&meta_inlines(value));
+            let rule = adornment.to_string().repeat(text_width(&title));
+            lines.extend([rule.clone(), title, rule, String::new()]);
+        }
+    }
+    let authors = match pandoc.meta.get("author") {
+        Some(MetaValue::MetaList(authors)) => authors.iter().collect(),
+        Some(author) => vec![author],
+        None => vec![],
+    };
+    let mut fields = Vec::new();
+    for author in authors {
+        fields.push(format!(":Author: {}", writer.line(&meta_inlines(author))));
+    }
+    if let Some(date) = pandoc.meta.get("date") {
+        fields.push(format!(":Date: {}", writer.line(&meta_inlines(date))));
+    }
+    if !fields.is_empty() {
+        lines.extend(fields);
+        lines.push(String::new());
+    }
+    lines.extend(writer.document(&pandoc.blocks, options.width));
+    finish(lines)
+}
+
+/// Renders blocks as reStructuredText
+pub fn write_blocks(blocks: &[Block], options: &RstOptions) -> String {
+    finish(Writer::default().document(blocks, options.width))
+}
+
+fn finish(lines: Vec<String>) -> String {
+    let mut text = lines.join("\n");
+    if !text.is_empty() {
+        text.push('\n');
+    }
+    text
+}
+
+fn meta_inlines(value: &MetaValue) -> Vec<Inline> {
+    match *value {
+        MetaValue::MetaInlines(ref inlines) => inlines.clone(),
+        MetaValue::MetaBlocks(ref blocks) => match blocks[..] {
+            [Block::Plain(ref inlines)] | [Block::Para(ref inlines)] => inlines.clone(),
+            _ => vec![Inline::Str(value.stringify())],
+        },
+        _ => vec![Inline::Str(value.stringify())],
+    }
+}
+
+fn is_rst(format: &Format) -> bool {
+    format.base_name() == "rst"
+}
+
+/// The identifier docutils derives from a section title, which needs no
+/// explicit target
+fn implicit_id(title: &str) -> String {
+    let mut id = String::new();
+    for c in title.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            id.push(c);
+        } else if !id.is_empty() && !id.ends_with('-') {
+            id.push('-');
+        }
+    }
+    id.trim_end_matches('-').to_string()
+}
+
+/// Escapes characters that would otherwise be read as inline markup
+fn escape(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, &c) in chars.iter().enumerate() {
+        let escape = match c {
+            '\\' | '*' | '`' | '|' => true,
+            // `name_` is a reference, `snake_case` isn't
+            '_' => !chars.get(i + 1).is_some_and(|c| c.is_alphanumeric()),
+            _ => false,
+        };
+        if escape {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Escapes the start of a paragraph that would otherwise be read as a list
+/// item, directive, field, transition or section adornment
+fn escape_line_start(line: String) -> String {
+    let mut chars = line.chars();
+    let first = match chars.next() {
+        Some(c) => c,
+        None => return line,
+    };
+    let second = chars.next();
+    let adornment = first.is_ascii_punctuation() && line.chars().all(|c| c == first);
+    let escape = match first {
+        '-' | '*' | '+' => second.is_none_or(|c| c == ' ') || adornment,
+        '.' => line.starts_with(".. ") || line == ".." || adornment,
+        ':' => true,
+        '#' => line.starts_with("#. ") || adornment,
+        _ => adornment && line.len() >= 4,
+    };
+    if escape {
+        return format!("\\{}", line);
+    }
+    // enumerators like `1.`, `a)`, `(ii)` or `#)`
+    let marker = line.split(' ').next().unwrap_or("");
+    let number = marker
+        .strip_prefix('(')
+        .and_then(|m| m.strip_suffix(')'))
+        .or_else(|| marker.strip_suffix(['.', ')']));
+    if let Some(number) = number {
+        let all = |f: fn(&char) -> bool| !number.is_empty() && number.chars().all(|c| f(&c));
+        let digits = all(char::is_ascii_digit);
+        let letter = number.len() == 1 && all(char::is_ascii_alphabetic);
+        let roman = all(|c| "ivxlcdm".contains(*c)) || all(|c| "IVXLCDM".contains(*c));
+        if digits || letter || roman || number == "#" {
+            let split = marker.len() - 1;
+            return format!("{}\\{}", &line[..split], &line[split..]);
+        }
+    }
+    line
+}
+
+/// Indents directive content by three spaces
+fn body(lines: Vec<String>) -> Vec<String> {
+    indent(lines, "   ", "   ")
+}
+
+#[derive(Default)]
+struct Writer {
+    notes: Vec<Vec<Block>>,
+    /// Names and URLs of inline image substitutions
+    images: Vec<(String, String)>,
+    /// Inside inline markup, which can't contain other markup
+    in_markup: bool,
+    /// The last piece closed inline markup, so a following word character
+    /// needs an escaped space
+    after_markup: bool,
+    /// Inside a list, quote or directive, where sections aren't allowed
+    nested: usize,
+    /// Identifiers internal links point to. docutils resolves references
+    /// by name, so linked sections need an explicit target.
+    linked: HashSet<String>,
+    /// Identifiers of spans in the current block, which get a target
+    /// before it
+    targets: Vec<String>,
+}
+
+impl Writer {
+    /// Blocks followed by the footnotes and substitutions they use
+    fn document(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        struct Links<'a>(&'a mut HashSet<String>);
+        impl Visitor for Links<'_> {
+            fn visit_inline(&mut self, inline: &Inline) {
+                if let Inline::Link(_, _, (ref url, _)) = *inline {
+                    if let Some(id) = url.strip_prefix('#') {
+                        self.0.insert(id.to_string());
+                    }
+                }
+                self.walk_inline(inline)
+            }
+        }
+        let mut links = Links(&mut self.linked);
+        for block in blocks {
+            links.visit_block(block);
+        }
+        let mut lines = self.blocks(blocks, width);
+        // notes can contain notes themselves, which get appended while
+        // rendering
+        let mut i = 0;
+        while i < self.notes.len() {
+            let note = self.notes[i].clone();
+            self.nested += 1;
+            let note = self.blocks(&note, narrow(width, 3));
+            self.nested -= 1;
+            lines.push(String::new());
+            match note {
+                note if note.is_empty() => lines.push(format!(".. [{}]", i + 1)),
+                note => lines.extend(indent(note, &format!(".. [{}] ", i + 1), "   ")),
+            }
+            i += 1;
+        }
+        if !self.images.is_empty() {
+            lines.push(String::new());
+        }
+        for (name, url) in &self.images {
+            lines.push(format!(".. |{}| image:: {}", name, url));
+        }
+        lines
+    }
+
+    fn blocks(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        let mut out = Vec::new();
+        let mut previous: Option<&Block> = None;
+        for block in blocks {
+            let content = self.block(block, width);
+            let mut lines = Vec::new();
+            for id in std::mem::take(&mut self.targets) {
+                lines.extend([format!(".. _{}:", id), String::new()]);
+            }
+            lines.extend(content);
+            if lines.is_empty() {
+                continue;
+            }
+            // a quote after a list or quote would be read as part of it,
+            // and lists of the same kind would be merged
+            let separate = matches!(
+                (previous, block),
+                (
+                    Some(Block::BulletList(_))
+                        | Some(Block::OrderedList(..))
+                        | Some(Block::DefinitionList(_))
+                        | Some(Block::BlockQuote(_)),
+                    Block::BlockQuote(_)
+                ) | (Some(Block::BulletList(_)), Block::BulletList(_))
+                    | (Some(Block::OrderedList(..)), Block::OrderedList(..))
+            );
+            if !out.is_empty() {
+                out.push(String::new());
+            }
+            if separate {
+                out.extend(["..".to_string(), String::new()]);
+            }
+            previous = Some(block);
+            out.extend(lines);
+        }
+        out
+    }
+
+    fn nested_blocks(&mut self, blocks: &[Block], width: Option<usize>) -> Vec<String> {
+        self.nested += 1;
+        let lines = self.blocks(blocks, width);
+        self.nested -= 1;
+        lines
+    }
+
+    fn block(&mut self, block: &Block, width: Option<usize>) -> Vec<String> {
+        match *block {
+            Block::Para(ref inlines) if matches!(inlines[..], [Inline::Math(..)]) => {
+                let Inline::Math(_, ref tex) = inlines[0] else {
+                    unreachable!()
+                };
+                let mut out = vec![".. math::".to_string(), String::new()];
+                out.extend(body(tex.trim().lines().map(String::from).collect()));
+                out
+            }
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => self.text(inlines, width),
+            Block::LineBlock(ref lines) => lines
+                .iter()
+                .map(|line| match self.line(line) {
+                    line if line.is_empty() => "|".to_string(),
+                    line => format!("| {}", line),
+                })
+                .collect(),
+            Block::Header(level, (ref id, _, _), ref inlines) => {
+                let title = self.line(inlines);
+                let mut out = Vec::new();
+                let implicit = *id == implicit_id(&stringify(inlines));
+                if !id.is_empty() && (!implicit || self.linked.contains(id)) {
+                    out.extend([format!(".. _{}:", id), String::new()]);
+                }
+                if self.nested > 0 {
+                    out.push(format!(".. rubric:: {}", title));
+                    return out;
+                }
+                let adornment = ADORNMENTS[level.clamp(1, 6) as usize - 1];
+                let rule = adornment.to_string().repeat(text_width(&title).max(1));
+                out.extend([title, rule]);
+                out
+            }
+            Block::CodeBlock((ref id, ref classes, _), ref code) => {
+                let mut out = match classes.first() {
+                    Some(language) => vec![format!(".. code:: {}", language)],
+                    None if id.is_empty() => vec!["::".to_string()],
+                    None => vec![".. code::".to_string()],
+                };
+                if !id.is_empty() {
+                    out.push(format!("   :name: {}", id));
+                }
+                out.push(String::new());
+                out.extend(body(code.lines().map(String::from).collect()));
+                out
+            }
+            Block::RawBlock(ref format, ref text) if is_rst(format) => {
+                text.lines().map(String::from).collect()
+            }
+            Block::RawBlock(ref format, ref text) => {
+                let mut out = vec![format!(".. raw:: {}", format.base_name()), String::new()];
+                out.extend(body(text.lines().map(String::from).collect()));
+                out
+            }
+            Block::Null => Vec::new(),
+            Block::BlockQuote(ref blocks) => body(self.nested_blocks(blocks, narrow(width, 3))),
+            Block::BulletList(ref items) => {
+                self.list(items, vec!["-".to_string(); items.len()], width)
+            }
+            Block::OrderedList(ref attrs, ref items) => {
+                self.list(items, ordered_markers(attrs, items.len()), width)
+            }
+            Block::DefinitionList(ref items) => {
+                let mut out = Vec::new();
+                for (term, definitions) in items {
+                    out.push(escape_line_start(self.line(term)));
+                    let mut lines = Vec::new();
+                    for definition in definitions {
+                        if !lines.is_empty() {
+                            lines.push(String::new());
+                        }
+                        lines.extend(self.nested_blocks(definition, narrow(width, 3)));
+                    }
+                    if lines.is_empty() {
+                        lines.push("..".to_string());
+                    }
+                    out.extend(body(lines));
+                }
+                out
+            }
+            Block::Figure((ref id, _, _), ref caption, ref content) => {
+                let image = match content[..] {
+                    [Block::Plain(ref inlines)] | [Block::Para(ref inlines)] => match inlines[..] {
+                        [Inline::Image(_, ref alt, (ref url, _))] => Some((alt, url)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+                let caption = self.nested_blocks(&caption.1, narrow(width, 3));
+                let mut out;
+                match image {
+                    Some((alt, url)) => {
+                        out = vec![format!(".. figure:: {}", url)];
+                        if !alt.is_empty() {
+                            out.push(format!("   :alt: {}", stringify(alt)));
+                        }
+                        if !id.is_empty() {
+                            out.push(format!("   :name: {}", id));
+                        }
+                        if !caption.is_empty() {
+                            out.push(String::new());
+                            out.extend(body(caption));
+                        }
+                    }
+                    None => {
+                        out = vec![".. container:: figure".to_string()];
+                        if !id.is_empty() {
+                            out.push(format!("   :name: {}", id));
+                        }
+                        out.push(String::new());
+                        let mut lines = self.nested_blocks(content, narrow(width, 3));
+                        if !caption.is_empty() {
+                            lines.push(String::new());
+                            lines.extend(caption);
+                        }
+                        out.extend(body(lines));
+                    }
+                }
+                out
+            }
+            Block::HorizontalRule => vec!["-".repeat(14)],
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                let grid = Grid::new(colspecs, head, bodies, foot);
+                self.nested += 1;
+                let mut table = grid.draw(width, |blocks, width| self.blocks(blocks, width));
+                self.nested -= 1;
+                // RST only allows a single `=` line, below the head
+                let mut heads = usize::from(grid.head == 0);
+                for line in table.iter_mut().filter(|l| l.starts_with("+=")) {
+                    heads += 1;
+                    if heads > 1 {
+                        *line = line.replace('=', "-");
+                    }
+                }
+                let caption = self.line(&caption_inlines(&caption.1));
+                if caption.is_empty() && attr.0.is_empty() {
+                    return table;
+                }
+                let mut out = vec![format!(".. table:: {}", caption).trim_end().to_string()];
+                if !attr.0.is_empty() {
+                    out.push(format!("   :name: {}", attr.0));
+                }
+                out.push(String::new());
+                out.extend(body(table));
+                out
+            }
+            Block::Div((ref id, ref classes, _), ref blocks) => {
+                let admonition = classes.first().filter(|c| ADMONITIONS.contains(&&c[..]));
+                let directive = match admonition {
+                    Some(class) => format!(".. {}::", class),
+                    None if !classes.is_empty() => format!(".. container:: {}", classes.join(" ")),
+                    None => {
+                        let mut out = Vec::new();
+                        if !id.is_empty() {
+                            out.extend([format!(".. _{}:", id), String::new()]);
+                        }
+                        out.extend(self.blocks(blocks, width));
+                        return out;
+                    }
+                };
+                let mut out = vec![directive];
+                if !id.is_empty() {
+                    out.push(format!("   :name: {}", id));
+                }
+                out.push(String::new());
+                out.extend(body(self.nested_blocks(blocks, narrow(width, 3))));
+                out
+            }
+        }
+    }
+
+    fn list(
+        &mut self,
+        items: &[Vec<Block>],
+        markers: Vec<String>,
+        width: Option<usize>,
+    ) -> Vec<String> {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let mut out = Vec::new();
+        for (item, marker) in items.iter().zip(markers) {
+            if !out.is_empty() && !tight {
+                out.push(String::new());
+            }
+            let rest = " ".repeat(text_width(&marker) + 1);
+            match self.nested_blocks(item, narrow(width, rest.len())) {
+                lines if lines.is_empty() => out.push(marker),
+                lines => out.extend(indent(lines, &format!("{} ", marker), &rest)),
+            }
+        }
+        out
+    }
+
+    fn text(&mut self, inlines: &[Inline], width: Option<usize>) -> Vec<String> {
+        let mut pieces = Vec::new();
+        self.after_markup = false;
+        self.inlines(inlines, &mut pieces);
+        let mut lines = wrap(&pieces, width);
+        if let Some(first) = lines.first_mut() {
+            *first = escape_line_start(std::mem::take(first));
+        }
+        lines
+    }
+
+    /// Inline text on a single line, for titles and table captions
+    fn line(&mut self, inlines: &[Inline]) -> String {
+        let mut pieces = Vec::new();
+        self.after_markup = false;
+        self.inlines(inlines, &mut pieces);
+        wrap(&pieces, None).join(" ")
+    }
+
+    fn inlines(&mut self, inlines: &[Inline], out: &mut Vec<Piece>) {
+        for inline in inlines {
+            self.inline(inline, out);
+        }
+    }
+
+    fn push(&mut self, text: String, out: &mut Vec<Piece>) {
+        let escaped = self.after_markup && text.starts_with(char::is_alphanumeric);
+        self.after_markup = false;
+        if escaped {
+            out.push(Piece::Text("\\ ".to_string()));
+        }
+        out.push(Piece::Text(text));
+    }
+
+    /// Wraps the inlines in markup, or writes just their text inside other
+    /// markup. Markup must not start or end with a space and needs an escaped
+    /// space between it and adjacent word characters.
+    fn delimited(&mut self, open: &str, inlines: &[Inline], close: &str, out: &mut Vec<Piece>) {
+        if self.in_markup {
+            return self.inlines(inlines, out);
+        }
+        let is_space = |i: &Inline| matches!(i, Inline::Space | Inline::SoftBreak);
+        let start = inlines.iter().position(|i| !is_space(i));
+        let end = inlines.iter().rposition(|i| !is_space(i));
+        let (start, end) = match (start, end) {
+            (Some(start), Some(end)) => (start, end + 1),
+            _ => return self.inlines(inlines, out),
+        };
+        if start > 0 {
+            out.push(Piece::Space);
+        }
+        if let Some(Piece::Text(ref mut text)) = out.last_mut() {
+            if text.ends_with(char::is_alphanumeric) {
+                text.push_str("\\ ");
+            }
+        }
+        self.after_markup = false;
+        out.push(Piece::Text(open.to_string()));
+        self.in_markup = true;
+        self.inlines(&inlines[start..end], out);
+        self.in_markup = false;
+        out.push(Piece::Text(close.to_string()));
+        self.after_markup = true;
+        if end < inlines.len() {
+            out.push(Piece::Space);
+        }
+    }
+
+    /// Literal text in markup like a role or inline literal
+    fn literal(&mut self, open: &str, text: &str, close: &str, out: &mut Vec<Piece>) {
+        if self.in_markup {
+            return self.push(escape(text), out);
+        }
+        let text = text.replace('\n', " ");
+        self.delimited(
+            open,
+            &[Inline::RawInline(Format::new("rst"), text)],
+            close,
+            out,
+        )
+    }
+
+    fn inline(&mut self, inline: &Inline, out: &mut Vec<Piece>) {
+        match *inline {
+            Inline::Str(ref text) => self.push(escape(text), out),
+            Inline::Emph(ref inlines) => self.delimited("*", inlines, "*", out),
+            Inline::Strong(ref inlines) => self.delimited("**", inlines, "**", out),
+            Inline::Superscript(ref inlines) => self.delimited(":sup:`", inlines, "`", out),
+            Inline::Subscript(ref inlines) => self.delimited(":sub:`", inlines, "`", out),
+            Inline::Underline(ref inlines)
+            | Inline::Strikeout(ref inlines)
+            | Inline::SmallCaps(ref inlines)
+            | Inline::Cite(_, ref inlines) => self.inlines(inlines, out),
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                self.push("\u{2018}".to_string(), out);
+                self.inlines(inlines, out);
+                self.push("\u{2019}".to_string(), out);
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                self.push("\u{201c}".to_string(), out);
+                self.inlines(inlines, out);
+                self.push("\u{201d}".to_string(), out);
+            }
+            Inline::Code(_, ref code) => self.literal("``", code, "``", out),
+            Inline::Math(_, ref tex) => self.literal(":math:`", tex, "`", out),
+            Inline::RawInline(ref format, ref text) if is_rst(format) => {
+                self.push(text.clone(), out)
+            }
+            Inline::RawInline(..) => {}
+            Inline::Space | Inline::SoftBreak | Inline::LineBreak => {
+                self.after_markup = false;
+                out.push(Piece::Space)
+            }
+            Inline::Link(_, ref inlines, (ref url, _)) => {
+                let standalone = url.contains(':')
+                    && matches!(inlines[..], [Inline::Str(ref text)] if text == url);
+                if standalone {
+                    return self.push(url.clone(), out);
+                }
+                // internal links refer to the target of the section or div
+                let target = match url.strip_prefix('#') {
+                    Some(id) => format!("{}_", id),
+                    None => url.replace('<', "%3C").replace('>', "%3E"),
+                };
+                let close = format!(" <{}>`__", target);
+                if inlines.is_empty() {
+                    let text = [Inline::Str(url.clone())];
+                    return self.delimited("`", &text, &close, out);
+                }
+                self.delimited("`", inlines, &close, out)
+            }
+            Inline::Image(_, ref alt, (ref url, _)) => {
+                let name = match stringify(alt) {
+                    name if name.is_empty() || self.images.iter().any(|i| i.0 == name) => {
+                        format!("image{}", self.images.len() + 1)
+                    }
+                    name => name,
+                };
+                self.images.push((name.clone(), url.clone()));
+                let name = [Inline::RawInline(Format::new("rst"), name)];
+                self.delimited("|", &name, "|", out)
+            }
+            Inline::Note(ref blocks) => {
+                self.notes.push(blocks.clone());
+                let reference = format!("[{}]_", self.notes.len());
+                match out.last_mut() {
+                    // footnote references need a space before them
+                    Some(Piece::Text(ref mut text)) if !self.in_markup => {
+                        text.push_str("\\ ");
+                        text.push_str(&reference);
+                    }
+                    _ => self.push(reference, out),
+                }
+                self.after_markup = true;
+            }
+            Inline::Span((ref id, _, _), ref inlines) => {
+                // an inline target is named by its text, so other
+                // identifiers need a target before the block
+                if id.is_empty() {
+                    self.inlines(inlines, out)
+                } else if !self.in_markup && *id == implicit_id(&stringify(inlines)) {
+                    self.delimited("_`", inlines, "`", out)
+                } else {
+                    self.targets.push(id.clone());
+                    self.inlines(inlines, out)
+                }
+            }
+        }
+    }
+}
+
+/// The inlines of a caption's paragraphs
+fn caption_inlines(blocks: &[Block]) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for block in blocks {
+        if let Block::Plain(ref content) | Block::Para(ref content) = *block {
+            if !inlines.is_empty() {
+                inlines.push(Inline::Space);
+            }
+            inlines.extend(content.iter().cloned());
+        }
+    }
+    inlines
+}
@@ -0,0 +1,692 @@
+//! Typst markup, like pandoc's `typst` format
+//!
+//! Identifiers become labels; TeX math is converted as far as it is
+//! simple: symbols, scripts, fractions, roots, accents and font commands.
+
+use super::*;
+
+/// Renders the blocks of a document as Typst
+pub fn write(pandoc: &Pandoc) -> String {
+    write_blocks(&pandoc.blocks)
+}
+
+/// Renders blocks as Typst
+pub fn write_blocks(blocks: &[Block]) -> String {
+    let mut typst = Writer.blocks(blocks);
+    if !typst.is_empty() {
+        typst.push('\n');
+    }
+    typst
+}
+
+/// Renders inlines as Typst
+pub fn write_inlines(inlines: &[Inline]) -> String {
+    Writer.inlines(inlines)
+}
+
+/// Escapes text for Typst markup
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' | '#' | '$' | '*' | '_' | '`' | '<' | '>' | '@' | '[' | ']' | '~' | '"' | '\''
+            | '=' => {
+                out.push('\\');
+                out.push(c);
+            }
+            // would start a comment
+            '/' if matches!(chars.peek(), Some('/' | '*')) => out.push_str("\\/"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Where to escape the start of a line that would be read as a list item,
+/// an enumeration or a term
+fn list_marker(line: &str) -> Option<usize> {
+    let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(0);
+    if line.starts_with(['-', '+']) || line.starts_with("/ ") {
+        Some(0)
+    } else if digits > 0 && line[digits..].starts_with('.') {
+        Some(digits)
+    } else {
+        None
+    }
+}
+
+/// A string literal for code mode
+fn string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn label(id: &str) -> String {
+    if id.is_empty() {
+        String::new()
+    } else {
+        format!(" <{}>", id)
+    }
+}
+
+fn is_typst(format: &Format) -> bool {
+    format.matches(&Format::new("typst"))
+}
+
+/// Indents all lines but the first
+fn hang(text: &str, by: usize) -> String {
+    let pad = " ".repeat(by);
+    let lines: Vec<String> = text
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            if i == 0 || line.is_empty() {
+                line.to_string()
+            } else {
+                format!("{}{}", pad, line)
+            }
+        })
+        .collect();
+    lines.join("\n")
+}
+
+fn alignment(align: Alignment) -> &'static str {
+    match align {
+        Alignment::AlignLeft => "left",
+        Alignment::AlignRight => "right",
+        Alignment::AlignCenter => "center",
+        Alignment::AlignDefault => "auto",
+    }
+}
+
+struct Writer;
+
+impl Writer {
+    fn blocks(&mut self, blocks: &[Block]) -> String {
+        let blocks: Vec<String> = blocks
+            .iter()
+            .map(|block| self.block(block))
+            .filter(|block| !block.is_empty())
+            .collect();
+        blocks.join("\n\n")
+    }
+
+    fn block(&mut self, block: &Block) -> String {
+        match *block {
+            Block::Plain(ref inlines) | Block::Para(ref inlines) => self.inlines(inlines),
+            Block::LineBlock(ref lines) => {
+                let lines: Vec<String> = lines.iter().map(|line| self.inlines(line)).collect();
+                lines.join(" \\\n")
+            }
+            Block::CodeBlock((_, ref classes, _), ref code) => {
+                let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+                let fence = "`".repeat(longest.max(2) + 1);
+                let lang = classes.first().map_or("", |lang| &lang[..]);
+                format!(
+                    "{}{}\n{}\n{}",
+                    fence,
+                    lang,
+                    code.trim_end_matches('\n'),
+                    fence
+                )
+            }
+            Block::RawBlock(ref format, ref text) if is_typst(format) => text.clone(),
+            Block::RawBlock(..) | Block::Null => String::new(),
+            Block::BlockQuote(ref blocks) => {
+                format!("#quote(block: true)[\n{}\n]", self.blocks(blocks))
+            }
+            Block::OrderedList((start, style, delim), ref items) => {
+                let list = self.list("+ ", items);
+                let number = match style {
+                    ListNumberStyle::LowerRoman => "i",
+                    ListNumberStyle::UpperRoman => "I",
+                    ListNumberStyle::LowerAlpha => "a",
+                    ListNumberStyle::UpperAlpha => "A",
+                    _ => "1",
+                };
+                let numbering = match delim {
+                    ListNumberDelim::OneParen => format!("{})", number),
+                    ListNumberDelim::TwoParens => format!("({})", number),
+                    _ => format!("{}.", number),
+                };
+                if numbering == "1." && start == 1 {
+                    return list;
+                }
+                format!(
+                    "#block[\n#set enum(numbering: {}, start: {})\n{}\n]",
+                    string(&numbering),
+                    start,
+                    list
+                )
+            }
+            Block::BulletList(ref items) => self.list("- ", items),
+            Block::DefinitionList(ref items) => {
+                let items: Vec<String> = items
+                    .iter()
+                    .map(|(term, definitions)| {
+                        let definitions: Vec<String> =
+                            definitions.iter().map(|d| self.blocks(d)).collect();
+                        let item =
+                            format!("/ {}: {}", self.inlines(term), definitions.join("\n\n"));
+                        hang(&item, 2)
+                    })
+                    .collect();
+                items.join("\n")
+            }
+            Block::Figure((ref id, _, _), ref caption, ref blocks) => {
+                let body = match blocks[..] {
+                    [Block::Plain(ref inlines) | Block::Para(ref inlines)]
+                        if matches!(inlines[..], [Inline::Image(..)]) =>
+                    {
+                        self.image(&inlines[0])
+                    }
+                    [Block::Table(_, _, ref colspecs, ref head, ref bodies, ref foot)] => {
+                        self.table(colspecs, head, bodies, foot)
+                    }
+                    _ => format!("[\n{}\n]", self.blocks(blocks)),
+                };
+                let mut typst = format!("#figure({}", body);
+                if !caption.1.is_empty() {
+                    typst.push_str(&format!(", caption: [{}]", self.blocks(&caption.1)));
+                }
+                typst.push(')');
+                typst.push_str(&label(id));
+                typst
+            }
+            Block::Header(level, (ref id, ref classes, _), ref inlines) => {
+                let text = self.inlines(inlines);
+                let level = level.max(1);
+                if classes.iter().any(|c| c == "unnumbered") {
+                    format!(
+                        "#heading(level: {}, numbering: none)[{}]{}",
+                        level,
+                        text,
+                        label(id)
+                    )
+                } else {
+                    let marker = "=".repeat(level as usize);
+                    format!("{} {}{}", marker, text, label(id))
+                }
+            }
+            Block::HorizontalRule => "#line(length: 100%)".to_string(),
+            Block::Table(ref attr, ref caption, ref colspecs, ref head, ref bodies, ref foot) => {
+                let table = self.table(colspecs, head, bodies, foot);
+                if caption.1.is_empty() && attr.0.is_empty() {
+                    return format!("#{}", table);
+                }
+                let mut typst = format!("#figure({}", table);
+                if !caption.1.is_empty() {
+                    typst.push_str(&format!(", caption: [{}]", self.blocks(&caption.1)));
+                }
+                typst.push_str(", kind: table)");
+                typst.push_str(&label(&attr.0));
+                typst
+            }
+            Block::Div((ref id, _, _), ref blocks) => {
+                let content = self.blocks(blocks);
+                if id.is_empty() {
+                    content
+                } else {
+                    format!("#block[\n{}\n]{}", content, label(id))
+                }
+            }
+        }
+    }
+
+    fn list(&mut self, marker: &str, items: &[Vec<Block>]) -> String {
+        let tight = !items.iter().flatten().any(|b| matches!(b, Block::Para(_)));
+        let items: Vec<String> = items
+            .iter()
+            .map(|item| hang(&format!("{}{}", marker, self.blocks(item)), 2))
+            .collect();
+        items.join(if tight { "\n" } else { "\n\n" })
+    }
+
+    /// A `table(..)` call, without the `#`
+    fn table(
+        &mut self,
+        colspecs: &[ColSpec],
+        head: &TableHead,
+        bodies: &[TableBody],
+        foot: &TableFoot,
+    ) -> String {
+        let columns: Vec<String> = colspecs
+            .iter()
+            .map(|spec| match spec.1 {
+                ColWidth::ColWidth(width) => format!("{}%", (width * 100.0).round()),
+                ColWidth::ColWidthDefault => "auto".to_string(),
+            })
+            .collect();
+        let columns = if columns.iter().all(|c| c == "auto") {
+            columns.len().to_string()
+        } else {
+            format!("({},)", columns.join(", "))
+        };
+        let aligns: Vec<&str> = colspecs.iter().map(|spec| alignment(spec.0)).collect();
+        let mut typst = format!("table(\n  columns: {},\n", columns);
+        if aligns.iter().any(|a| *a != "auto") {
+            typst.push_str(&format!("  align: ({},),\n", aligns.join(", ")));
+        }
+        if !head.1.is_empty() {
+            typst.push_str(&format!("  table.header({}),\n", self.rows(&head.1, ", ")));
+        }
+        for body in bodies {
+            for rows in [&body.2, &body.3] {
+                if !rows.is_empty() {
+                    typst.push_str(&format!("  {},\n", self.rows(rows, ",\n  ")));
+                }
+            }
+        }
+        if !foot.1.is_empty() {
+            typst.push_str(&format!("  table.footer({}),\n", self.rows(&foot.1, ", ")));
+        }
+        typst.push(')');
+        typst
+    }
+
+    /// The cells of rows, rows joined with `separator`
+    fn rows(&mut self, rows: &[Row], separator: &str) -> String {
+        let rows: Vec<String> = rows
+            .iter()
+            .map(|(_, cells)| {
+                let cells: Vec<String> = cells
+                    .iter()
+                    .map(|&(_, align, rowspan, colspan, ref blocks)| {
+                        let mut args = Vec::new();
+                        if rowspan > 1 {
+                            args.push(format!("rowspan: {}", rowspan));
+                        }
+                        if colspan > 1 {
+                            args.push(format!("colspan: {}", colspan));
+                        }
+                        if align != Alignment::AlignDefault {
+                            args.push(format!("align: {}", alignment(align)));
+                        }
+                        let content = format!("[{}]", self.blocks(blocks));
+                        if args.is_empty() {
+                            content
+                        } else {
+                            format!("table.cell({}){}", args.join(", "), content)
+                        }
+                    })
+                    .collect();
+                cells.join(", ")
+            })
+            .collect();
+        rows.join(separator)
+    }
+
+    /// An `image(..)` call for an `Image`, without the `#`
+    fn image(&mut self, image: &Inline) -> String {
+        let Inline::Image((_, _, ref kvs), _, (ref url, _)) = *image else {
+            return String::new();
+        };
+        let mut args = vec![string(url)];
+        for (key, value) in kvs {
+            let units = ["%", "pt", "mm", "cm", "in", "em"];
+            if (key == "width" || key == "height") && units.iter().any(|u| value.ends_with(u)) {
+                args.push(format!("{}: {}", key, value));
+            }
+        }
+        format!("image({})", args.join(", "))
+    }
+
+    fn inlines(&mut self, inlines: &[Inline]) -> String {
+        let mut typst = String::new();
+        let mut starts = vec![0];
+        for inline in inlines {
+            typst.push_str(&self.inline(inline));
+            if let Inline::LineBreak = *inline {
+                starts.push(typst.len());
+            }
+        }
+        // a line must not start like a list item or a term
+        for start in starts.into_iter().rev() {
+            let line = &typst[start..];
+            let start = start + line.len() - line.trim_start_matches(' ').len();
+            if let Some(marker) = list_marker(&typst[start..]) {
+                typst.insert(start + marker, '\\');
+            }
+        }
+        typst
+    }
+
+    fn call(&mut self, name: &str, inlines: &[Inline]) -> String {
+        format!("#{}[{}]", name, self.inlines(inlines))
+    }
+
+    fn inline(&mut self, inline: &Inline) -> String {
+        match *inline {
+            Inline::Str(ref text) => escape(text),
+            Inline::Emph(ref inlines) => self.call("emph", inlines),
+            Inline::Strong(ref inlines) => self.call("strong", inlines),
+            Inline::Underline(ref inlines) => self.call("underline", inlines),
+            Inline::Strikeout(ref inlines) => self.call("strike", inlines),
+            Inline::Superscript(ref inlines) => self.call("super", inlines),
+            Inline::Subscript(ref inlines) => self.call("sub", inlines),
+            Inline::SmallCaps(ref inlines) => self.call("smallcaps", inlines),
+            Inline::Quoted(QuoteType::SingleQuote, ref inlines) => {
+                format!("'{}'", self.inlines(inlines))
+            }
+            Inline::Quoted(QuoteType::DoubleQuote, ref inlines) => {
+                format!("\"{}\"", self.inlines(inlines))
+            }
+            Inline::Cite(ref citations, _) => {
+                let keys: Vec<String> = citations
+                    .iter()
+                    .map(|c| format!("#cite(label({}))", string(&c.citationId)))
+                    .collect();
+                keys.join(" ")
+            }
+            Inline::Code(_, ref code) if !code.contains('`') => format!("`{}`", code),
+            Inline::Code(_, ref code) => format!("#raw({})", string(code)),
+            Inline::Space | Inline::SoftBreak => " ".to_string(),
+            Inline::LineBreak => "\\\n".to_string(),
+            Inline::Math(MathType::InlineMath, ref tex) => format!("${}$", tex_to_typst(tex)),
+            Inline::Math(MathType::DisplayMath, ref tex) => format!("$ {} $", tex_to_typst(tex)),
+            Inline::RawInline(ref format, ref text) if is_typst(format) => text.clone(),
+            Inline::RawInline(..) => String::new(),
+            Inline::Link(_, ref inlines, (ref url, _)) => {
+                let target = match url.strip_prefix('#') {
+                    Some(id) => format!("<{}>", id),
+                    None => string(url),
+                };
+                if stringify(inlines) == *url {
+                    format!("#link({})", target)
+                } else {
+                    format!("#link({})[{}]", target, self.inlines(inlines))
+                }
+            }
+            Inline::Image(..) => format!("#box({})", self.image(inline)),
+            Inline::Note(ref blocks) => format!("#footnote[{}]", self.blocks(blocks)),
+            Inline::Span((ref id, _, _), ref inlines) => {
+                let content = self.inlines(inlines);
+                if id.is_empty() {
+                    content
+                } else {
+                    format!("#[{}]<{}>", content, id)
+                }
+            }
+        }
+    }
+}
+
+/// Converts TeX math to Typst math. Commands without a Typst counterpart
+/// are kept by name, which Typst often knows as well.
+pub fn tex_to_typst(tex: &str) -> String {
+    TexMath {
+        chars: tex.chars().collect(),
+        pos: 0,
+    }
+    .sequence()
+}
+
+/// A converted piece of math
+enum Item {
+    /// Separated from the previous one by a space
+    Atom(String),
+    /// Attached to the previous one, like scripts and primes
+    Attach(String),
+}
+
+struct TexMath {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+/// The Typst name of a TeX symbol command
+fn symbol(name: &str) -> &str {
+    match name {
+        "cdot" => "dot.op",
+        "pm" => "plus.minus",
+        "mp" => "minus.plus",
+        "le" | "leq" => "<=",
+        "ge" | "geq" => ">=",
+        "ne" | "neq" => "!=",
+        "ll" => "<<",
+        "gg" => ">>",
+        "sim" => "tilde.op",
+        "propto" => "prop",
+        "infty" => "infinity",
+        "partial" => "diff",
+        "to" | "rightarrow" => "->",
+        "leftarrow" | "gets" => "<-",
+        "Rightarrow" | "implies" => "=>",
+        "Leftarrow" => "arrow.l.double",
+        "leftrightarrow" => "<->",
+        "Leftrightarrow" | "iff" => "<=>",
+        "mapsto" => "|->",
+        "notin" => "in.not",
+        "subseteq" => "subset.eq",
+        "supseteq" => "supset.eq",
+        "cup" => "union",
+        "cap" => "sect",
+        "bigcup" => "union.big",
+        "bigcap" => "sect.big",
+        "setminus" => "without",
+        "neg" | "lnot" => "not",
+        "land" | "wedge" => "and",
+        "lor" | "vee" => "or",
+        "ldots" | "dots" => "...",
+        "cdots" => "dots.h.c",
+        "vdots" => "dots.v",
+        "ddots" => "dots.down",
+        "prod" => "product",
+        "coprod" => "product.co",
+        "int" => "integral",
+        "iint" => "integral.double",
+        "oint" => "integral.cont",
+        "langle" => "angle.l",
+        "rangle" => "angle.r",
+        "lfloor" => "floor.l",
+        "rfloor" => "floor.r",
+        "lceil" => "ceil.l",
+        "rceil" => "ceil.r",
+        "qquad" => "wide",
+        "circ" => "circle.small",
+        "hbar" => "planck.reduce",
+        "varepsilon" => "epsilon.alt",
+        "vartheta" => "theta.alt",
+        "varphi" => "phi.alt",
+        "varrho" => "rho.alt",
+        "varpi" => "pi.alt",
+        "varsigma" => "sigma.alt",
+        "mid" => "|",
+        "vert" => "|",
+        "Vert" | "|" => "||",
+        "lbrace" => "{",
+        "rbrace" => "}",
+        "," | ":" => "thin",
+        ";" => "med",
+        " " => "space",
+        other => other,
+    }
+}
+
+impl TexMath {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn skip_space(&mut self) {
+        while self.peek().is_some_and(char::is_whitespace) {
+            self.pos += 1;
+        }
+    }
+
+    /// Converts up to the end or the closing brace of the current group
+    fn sequence(&mut self) -> String {
+        let mut out = String::new();
+        while let Some(item) = self.item() {
+            match item {
+                Item::Atom(atom) if atom.is_empty() => {}
+                Item::Atom(atom) => {
+                    if !out.is_empty() {
+                        out.push(' ');
+                    }
+                    out.push_str(&atom);
+                }
+                Item::Attach(attach) => out.push_str(&attach),
+            }
+        }
+        out
+    }
+
+    /// The next item, `None` at the end of the group
+    fn item(&mut self) -> Option<Item> {
+        self.skip_space();
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(match c {
+            '}' => return None,
+            '{' => Item::Atom(self.sequence()),
+            '^' | '_' => {
+                let argument = self.argument();
+                let simple = argument.chars().all(|c| c.is_alphanumeric() || c == '.');
+                if simple && !argument.is_empty() {
+                    Item::Attach(format!("{}{}", c, argument))
+                } else {
+                    Item::Attach(format!("{}({})", c, argument))
+                }
+            }
+            '\'' => Item::Attach("'".to_string()),
+            '\\' => return self.command(),
+            c if c.is_ascii_digit() => {
+                let mut number = c.to_string();
+                while let Some(d) = self.peek().filter(|d| d.is_ascii_digit() || *d == '.') {
+                    number.push(d);
+                    self.pos += 1;
+                }
+                Item::Atom(number)
+            }
+            '/' | '"' | '#' | '$' => Item::Atom(format!("\\{}", c)),
+            c => Item::Atom(c.to_string()),
+        })
+    }
+
+    /// A braced group or a single item, as taken by commands and scripts
+    fn argument(&mut self) -> String {
+        self.skip_space();
+        if self.peek() == Some('{') {
+            self.pos += 1;
+            return self.sequence();
+        }
+        // like in TeX, `x^23` only raises the 2
+        if let Some(digit) = self.peek().filter(char::is_ascii_digit) {
+            self.pos += 1;
+            return digit.to_string();
+        }
+        match self.item() {
+            Some(Item::Atom(atom) | Item::Attach(atom)) => atom,
+            None => String::new(),
+        }
+    }
+
+    /// The raw text of a braced group
+    fn text_argument(&mut self) -> String {
+        self.skip_space();
+        if self.peek() != Some('{') {
+            return self
+                .peek()
+                .map(|c| {
+                    self.pos += 1;
+                    c.to_string()
+                })
+                .unwrap_or_default();
+        }
+        self.pos += 1;
+        let mut depth = 0;
+        let mut text = String::new();
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+            match c {
+                '{' => depth += 1,
+                '}' if depth == 0 => break,
+                '}' => depth -= 1,
+                _ => {}
+            }
+            text.push(c);
+        }
+        text
+    }
+
+    fn command(&mut self) -> Option<Item> {
+        let mut name = String::new();
+        while let Some(c) = self.peek().filter(char::is_ascii_alphabetic) {
+            name.push(c);
+            self.pos += 1;
+        }
+        if name.is_empty() {
+            match self.peek() {
+                Some(c) => {
+                    name.push(c);
+                    self.pos += 1;
+                }
+                None => return Some(Item::Atom("\\".to_string())),
+            }
+        }
+        let function = |this: &mut Self, f: &str| Item::Atom(format!("{}({})", f, this.argument()));
+        Some(match &name[..] {
+            "frac" | "dfrac" | "tfrac" => {
+                let numerator = self.argument();
+                let denominator = self.argument();
+                Item::Atom(format!("frac({}, {})", numerator, denominator))
+            }
+            "sqrt" => {
+                self.skip_space();
+                if self.peek() == Some('[') {
+                    self.pos += 1;
+                    let end = self.chars[self.pos..]
+                        .iter()
+                        .position(|c| *c == ']')
+                        .map_or(self.chars.len(), |i| self.pos + i);
+                    let index: String = self.chars[self.pos..end].iter().collect();
+                    self.pos = (end + 1).min(self.chars.len());
+                    let radicand = self.argument();
+                    Item::Atom(format!("root({}, {})", tex_to_typst(&index), radicand))
+                } else {
+                    function(self, "sqrt")
+                }
+            }
+            "text" | "textrm" | "mbox" => Item::Atom(string(&self.text_argument())),
+            "operatorname" => Item::Atom(format!("op({})", string(&self.text_argument()))),
+            "mathrm" => function(self, "upright"),
+            "mathbf" | "boldsymbol" => function(self, "bold"),
+            "mathit" => function(self, "italic"),
+            "mathcal" => function(self, "cal"),
+            "mathbb" => function(self, "bb"),
+            "mathsf" => function(self, "sans"),
+            "mathtt" => function(self, "mono"),
+            "hat" | "widehat" => function(self, "hat"),
+            "tilde" | "widetilde" => function(self, "tilde"),
+            "bar" => function(self, "macron"),
+            "overline" => function(self, "overline"),
+            "underline" => function(self, "underline"),
+            "vec" => function(self, "arrow"),
+            "dot" => function(self, "dot"),
+            "ddot" => function(self, "dot.double"),
+            "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "!" => {
+                // the delimiter after `\left` is an item of its own
+                if self.peek() == Some('.') {
+                    self.pos += 1;
+                }
+                Item::Atom(String::new())
+            }
+            "\\" => Item::Atom("\\".to_string()),
+            "{" | "}" | "_" | "&" | "#" | "%" | "$" => Item::Atom(format!("\\{}", name)),
+            name => Item::Atom(symbol(name).to_string()),
+        })
+    }
+}
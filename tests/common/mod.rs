@@ -0,0 +1,13 @@
+use pandoc_ast::Inline;
+
+/// Words separated by single spaces as `Str`s joined by `Space`s
+pub fn text(text: &str) -> Vec<Inline> {
+    let mut inlines = Vec::new();
+    for word in text.split(' ') {
+        if !inlines.is_empty() {
+            inlines.push(Inline::Space);
+        }
+        inlines.push(Inline::Str(word.to_string()));
+    }
+    inlines
+}
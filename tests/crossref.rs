@@ -0,0 +1,134 @@
+use pandoc_ast::crossref::{crossref, Crossref, Kind, Label};
+use pandoc_ast::*;
+
+fn figure(id: &str, caption: &str) -> String {
+    format!(
+        r#"{{"t":"Figure","c":[["{}",[],[]],[null,[{{"t":"Plain","c":[{{"t":"Str","c":"{}"}}]}}]],[]]}}"#,
+        id, caption
+    )
+}
+
+fn cite(ids: &[&str], mode: &str) -> String {
+    let citations: Vec<String> = ids
+        .iter()
+        .map(|id| {
+            format!(
+                r#"{{"citationId":"{}","citationPrefix":[],"citationSuffix":[],"citationMode":{{"t":"{}"}},"citationNoteNum":1,"citationHash":0}}"#,
+                id, mode
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"t":"Para","c":[{{"t":"Cite","c":[[{}],[]]}}]}}"#,
+        citations.join(",")
+    )
+}
+
+fn doc(meta: &str, blocks: &[String]) -> Pandoc {
+    let json = format!(
+        r#"{{"pandoc-api-version":[1,23],"meta":{},"blocks":[{}]}}"#,
+        meta,
+        blocks.join(",")
+    );
+    Pandoc::from_json(&json)
+}
+
+#[test]
+fn captions_and_references() {
+    let mut pandoc = doc(
+        "{}",
+        &[
+            figure("fig:a", "A"),
+            figure("fig:b", "B"),
+            figure("fig:c", "C"),
+            figure("fig:d", "D"),
+            cite(&["fig:a", "fig:b", "fig:c"], "NormalCitation"),
+            cite(&["Fig:d"], "NormalCitation"),
+            cite(&["fig:b", "fig:d"], "SuppressAuthor"),
+            cite(&["fig:missing"], "NormalCitation"),
+        ],
+    );
+    crossref(&mut pandoc).unwrap();
+    match pandoc.blocks[1] {
+        Block::Figure(_, ref caption, _) => assert_eq!(stringify(caption), "Figure 2: B"),
+        ref other => panic!("{:?}", other),
+    }
+    let text: Vec<String> = pandoc.blocks[4..].iter().map(stringify).collect();
+    assert_eq!(
+        text,
+        [
+            "Figs.\u{a0}1\u{2013}3",
+            "Fig.\u{a0}4",
+            "2, 4",
+            "Fig.\u{a0}\u{bf}fig:missing?"
+        ]
+    );
+    match pandoc.blocks[5] {
+        Block::Para(ref inlines) => assert!(matches!(
+            inlines.last(),
+            Some(Inline::Link(_, _, (url, _))) if url == "#fig:d"
+        )),
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn equations_listings_and_sections() {
+    let mut pandoc = doc(
+        r#"{"crossref":{"t":"MetaMap","c":{"eqnPrefix":{"t":"MetaList","c":[{"t":"MetaString","c":"equation"},{"t":"MetaString","c":"equations"}]}}}}"#,
+        &[
+            r#"{"t":"Header","c":[1,["sec:intro",[],[]],[{"t":"Str","c":"Intro"}]]}"#.to_string(),
+            r#"{"t":"Para","c":[{"t":"Math","c":[{"t":"DisplayMath"},"x"]},{"t":"Space"},{"t":"Str","c":"{#eq:x}"}]}"#.to_string(),
+            r#"{"t":"CodeBlock","c":[["lst:code",["rust"],[["caption","Main"]]],"fn main() {}"]}"#.to_string(),
+            cite(&["eq:x", "lst:code", "sec:intro"], "NormalCitation"),
+        ],
+    );
+    let mut crossref = Crossref::from_meta(&pandoc.meta).unwrap();
+    crossref.process(&mut pandoc);
+    assert_eq!(
+        crossref.labels().get("eq:x"),
+        Some(&Label {
+            kind: Kind::Equation,
+            number: "1".to_string()
+        })
+    );
+    match pandoc.blocks[1] {
+        Block::Para(ref inlines) => match inlines[..] {
+            [Inline::Span((ref id, _, _), ref math)] => {
+                assert_eq!(id, "eq:x");
+                assert_eq!(stringify(math), "x\\qquad(1)");
+            }
+            ref other => panic!("{:?}", other),
+        },
+        ref other => panic!("{:?}", other),
+    }
+    match pandoc.blocks[2] {
+        Block::Div((ref id, _, _), ref content) => {
+            assert_eq!(id, "lst:code");
+            assert_eq!(stringify(&content[0]), "Listing 1: Main");
+            assert!(matches!(content[1], Block::CodeBlock(..)));
+        }
+        ref other => panic!("{:?}", other),
+    }
+    assert_eq!(
+        stringify(&pandoc.blocks[3]),
+        "equation\u{a0}1, Listing\u{a0}1, Sec.\u{a0}1"
+    );
+}
+
+#[test]
+fn chapters() {
+    let mut pandoc = doc(
+        r#"{"chapters":{"t":"MetaBool","c":true}}"#,
+        &[
+            r#"{"t":"Header","c":[1,["",[],[]],[{"t":"Str","c":"One"}]]}"#.to_string(),
+            figure("fig:a", "A"),
+            r#"{"t":"Header","c":[1,["",[],[]],[{"t":"Str","c":"Two"}]]}"#.to_string(),
+            figure("fig:b", "B"),
+        ],
+    );
+    let mut crossref = Crossref::from_meta(&pandoc.meta).unwrap();
+    crossref.process(&mut pandoc);
+    assert_eq!(crossref.labels()["fig:a"].number, "1.1");
+    assert_eq!(crossref.labels()["fig:b"].number, "2.1");
+}
@@ -0,0 +1,169 @@
+use pandoc_ast::writers::docbook::{write, write_blocks};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn header(level: i64, id: &str, title: &str) -> Block {
+    Block::Header(level, (id.to_string(), vec![], vec![]), text(title))
+}
+
+#[test]
+fn sections_and_info() {
+    let mut meta = Map::new();
+    meta.insert("title".to_string(), MetaValue::MetaInlines(text("A & B")));
+    meta.insert(
+        "author".to_string(),
+        MetaValue::MetaList(vec![
+            MetaValue::MetaInlines(text("Jane Doe")),
+            MetaValue::MetaString("Max".to_string()),
+        ]),
+    );
+    let pandoc = Pandoc {
+        meta,
+        blocks: vec![
+            header(1, "one", "One"),
+            Block::Para(text("x")),
+            header(2, "two", "Two"),
+            Block::CodeBlock(
+                (String::new(), vec!["rust".to_string()], vec![]),
+                "a < b\u{1}".to_string(),
+            ),
+            header(1, "three", "Three"),
+        ],
+        pandoc_api_version: vec![1, 23, 1],
+    };
+    assert_eq!(
+        write(&pandoc),
+        "<?xml version=\"1.0\" encoding=\"utf-8\" ?>\n\
+         <article xmlns=\"http://docbook.org/ns/docbook\" \
+         xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"5.0\">\n\
+         <info>\n<title>A &amp; B</title>\n\
+         <author>\n<personname>Jane Doe</personname>\n</author>\n\
+         <author>\n<personname>Max</personname>\n</author>\n</info>\n\
+         <section xml:id=\"one\">\n<title>One</title>\n<para>x</para>\n\
+         <section xml:id=\"two\">\n<title>Two</title>\n\
+         <programlisting language=\"rust\">a &lt; b</programlisting>\n\
+         </section>\n</section>\n\
+         <section xml:id=\"three\">\n<title>Three</title>\n</section>\n\
+         </article>\n"
+    );
+}
+
+#[test]
+fn lists_notes_and_citations() {
+    let citation = Citation {
+        citationId: "doe".to_string(),
+        citationPrefix: vec![],
+        citationSuffix: vec![],
+        citationMode: CitationMode::NormalCitation,
+        citationNoteNum: 0,
+        citationHash: 0,
+    };
+    let mut inlines = vec![Inline::Strong(text("b"))];
+    inlines.push(Inline::Note(vec![Block::Para(text("note"))]));
+    inlines.push(Inline::Space);
+    inlines.push(Inline::Cite(vec![citation], text("[@doe]")));
+    inlines.push(Inline::Space);
+    inlines.push(Inline::Link(
+        Default::default(),
+        text("here"),
+        ("#one".to_string(), String::new()),
+    ));
+    let blocks = vec![
+        Block::OrderedList(
+            (3, ListNumberStyle::LowerAlpha, ListNumberDelim::Period),
+            vec![vec![Block::Plain(inlines)]],
+        ),
+        Block::BulletList(vec![vec![Block::Para(text("p"))]]),
+    ];
+    assert_eq!(
+        write_blocks(&blocks),
+        "<orderedlist numeration=\"loweralpha\" startingnumber=\"3\" \
+         spacing=\"compact\">\n<listitem>\n\
+         <para><emphasis role=\"strong\">b</emphasis><footnote>\n<para>note</para>\n\
+         </footnote> <citation>doe</citation> <link linkend=\"one\">here</link></para>\n\
+         </listitem>\n</orderedlist>\n\
+         <itemizedlist>\n<listitem>\n<para>p</para>\n</listitem>\n</itemizedlist>\n"
+    );
+}
+
+#[test]
+fn figures_and_tables() {
+    let image = Inline::Image(
+        Default::default(),
+        text("alt"),
+        ("a.png".to_string(), String::new()),
+    );
+    let figure = Block::Figure(
+        ("fig".to_string(), vec![], vec![]),
+        (None, vec![Block::Plain(text("A figure"))]),
+        vec![Block::Plain(vec![image])],
+    );
+    let cell = |s: &str, rowspan, colspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            rowspan,
+            colspan,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let table = Block::Table(
+        Default::default(),
+        (None, vec![Block::Plain(text("Spans"))]),
+        vec![
+            (Alignment::AlignDefault, ColWidth::ColWidthDefault),
+            (Alignment::AlignRight, ColWidth::ColWidth(0.5)),
+        ],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("wide", 1, 2)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![
+                (Default::default(), vec![cell("a", 2, 1), cell("b", 1, 1)]),
+                (Default::default(), vec![cell("c", 1, 1)]),
+            ],
+        )],
+        (Default::default(), vec![]),
+    );
+    assert_eq!(
+        write_blocks(&[figure, table]),
+        "<figure xml:id=\"fig\">\n<title>A figure</title>\n<mediaobject>\n\
+         <imageobject><imagedata fileref=\"a.png\" /></imageobject>\n\
+         <textobject><phrase>alt</phrase></textobject>\n</mediaobject>\n</figure>\n\
+         <table>\n<title>Spans</title>\n<tgroup cols=\"2\">\n\
+         <colspec colname=\"c1\" />\n\
+         <colspec colname=\"c2\" align=\"right\" colwidth=\"50*\" />\n\
+         <thead>\n<row>\n<entry namest=\"c1\" nameend=\"c2\">wide</entry>\n</row>\n</thead>\n\
+         <tbody>\n<row>\n<entry morerows=\"1\">a</entry>\n<entry>b</entry>\n</row>\n\
+         <row>\n<entry>c</entry>\n</row>\n</tbody>\n</tgroup>\n</table>\n"
+    );
+}
+
+#[test]
+fn citation_affixes() {
+    let mut suffix = vec![Inline::Str(",".to_string()), Inline::Space];
+    suffix.extend(text("p. 12"));
+    let citation = Citation {
+        citationId: "doe".to_string(),
+        citationPrefix: text("see"),
+        citationSuffix: suffix,
+        citationMode: CitationMode::NormalCitation,
+        citationNoteNum: 0,
+        citationHash: 0,
+    };
+    let inlines = vec![
+        Inline::Cite(vec![citation], text("[see @doe, p. 12]")),
+        Inline::Space,
+        Inline::Cite(vec![], text("[@x]")),
+    ];
+    assert_eq!(
+        write_blocks(&[Block::Para(inlines)]),
+        "<para>see <citation>doe</citation>, p. 12 [@x]</para>\n"
+    );
+}
@@ -0,0 +1,111 @@
+use pandoc_ast::*;
+
+fn image(id: &str, title: &str) -> Inline {
+    Inline::Image(
+        (id.to_string(), vec![], vec![]),
+        vec![Inline::Str("Plot".to_string())],
+        ("plot.png".to_string(), title.to_string()),
+    )
+}
+
+#[test]
+fn upgrade() {
+    let mut blocks = vec![
+        Block::Para(vec![image("fig:plot", "fig:Title")]),
+        Block::Para(vec![image("", "")]),
+        Block::Para(vec![image("", ""), Inline::Space]),
+    ];
+    upgrade_figures(&mut blocks, false);
+    let expected = Block::Figure(
+        ("fig:plot".to_string(), vec![], vec![]),
+        (
+            None,
+            vec![Block::Plain(vec![Inline::Str("Plot".to_string())])],
+        ),
+        vec![Block::Plain(vec![image("", "Title")])],
+    );
+    assert_eq!(blocks[0], expected);
+    assert!(matches!(blocks[1], Block::Para(_)));
+
+    upgrade_figures(&mut blocks, true);
+    assert!(matches!(blocks[1], Block::Figure(..)));
+    assert!(matches!(blocks[2], Block::Para(_)));
+}
+
+#[test]
+fn round_trip() {
+    let original = vec![Block::BlockQuote(vec![Block::Para(vec![image(
+        "fig:plot",
+        "fig:Title",
+    )])])];
+    let mut blocks = original.clone();
+    upgrade_figures(&mut blocks, false);
+    assert_ne!(blocks, original);
+    downgrade_figures(&mut blocks);
+    assert_eq!(blocks, original);
+}
+
+#[test]
+fn downgrade_other_content() {
+    let mut blocks = vec![Block::Figure(
+        ("fig:code".to_string(), vec![], vec![]),
+        (
+            None,
+            vec![Block::Plain(vec![Inline::Str("Code".to_string())])],
+        ),
+        vec![Block::CodeBlock(Default::default(), "x".to_string())],
+    )];
+    downgrade_figures(&mut blocks);
+    match blocks[0] {
+        Block::Div((ref id, ref classes, _), ref content) => {
+            assert_eq!(id, "fig:code");
+            assert_eq!(classes, &["figure"]);
+            assert_eq!(content.len(), 2);
+            assert_eq!(stringify(&content[1]), "Code");
+        }
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn downgrade_keeps_figure_attributes() {
+    let image = Inline::Image(
+        (
+            String::new(),
+            vec!["wide".to_string()],
+            vec![("width".to_string(), "50%".to_string())],
+        ),
+        vec![],
+        ("plot.png".to_string(), String::new()),
+    );
+    let mut blocks = vec![Block::Figure(
+        (
+            "fig".to_string(),
+            vec!["framed".to_string(), "wide".to_string()],
+            vec![
+                ("width".to_string(), "80%".to_string()),
+                ("source".to_string(), "data.csv".to_string()),
+            ],
+        ),
+        (None, vec![]),
+        vec![Block::Plain(vec![image])],
+    )];
+    downgrade_figures(&mut blocks);
+    match blocks[..] {
+        [Block::Para(ref inlines)] => match inlines[..] {
+            [Inline::Image((ref id, ref classes, ref kvs), _, _)] => {
+                assert_eq!(id, "fig");
+                assert_eq!(classes, &["wide", "framed"]);
+                assert_eq!(
+                    kvs,
+                    &[
+                        ("width".to_string(), "50%".to_string()),
+                        ("source".to_string(), "data.csv".to_string()),
+                    ]
+                );
+            }
+            ref other => panic!("{:?}", other),
+        },
+        ref other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,38 @@
+use pandoc_ast::*;
+
+#[test]
+fn base_name() {
+    assert_eq!(
+        Format::from("markdown+smart-citations").base_name(),
+        "markdown"
+    );
+    assert_eq!(Format::from("tex").base_name(), "latex");
+    assert_eq!(Format::from("HTML5").base_name(), "html");
+}
+
+#[test]
+fn extensions() {
+    let ext = Format::from("markdown+smart-citations+footnotes-footnotes").extensions();
+    assert!(ext.is_enabled("smart"));
+    assert!(ext.is_disabled("citations"));
+    assert!(ext.is_disabled("footnotes"));
+    assert_eq!(ext.get("pipe_tables"), None);
+    assert_eq!(ext.enabled().collect::<Vec<_>>(), ["smart"]);
+    assert!(Format::from("latex").extensions().is_empty());
+    assert!(ext.is_enabled("Smart"));
+    assert!(Extensions::parse("smart").is_empty());
+    assert!(Extensions::parse("é").is_empty());
+    assert!(Extensions::parse("x+é").is_enabled("é"));
+}
+
+#[test]
+fn matches() {
+    let latex = Format::from("latex");
+    assert!(Format::from("tex").matches(&latex));
+    assert!(Format::from("latex").matches(&Format::from("beamer")));
+    assert!(!Format::from("beamer").matches(&latex));
+    assert!(Format::from("html").matches(&Format::from("html5")));
+    assert!(Format::from("html").matches(&Format::from("epub3")));
+    assert!(Format::from("markdown").matches(&Format::from("markdown+smart-citations")));
+    assert!(!Format::from("html").matches(&latex));
+}
@@ -0,0 +1,72 @@
+use pandoc_ast::*;
+
+fn header(level: Int, text: &str) -> Block {
+    Block::Header(
+        level,
+        (text.to_lowercase(), vec![], vec![]),
+        vec![Inline::Str(text.to_string())],
+    )
+}
+
+fn doc(blocks: Vec<Block>) -> Pandoc {
+    let mut pandoc = Pandoc::from_json(r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[]}"#);
+    pandoc.blocks = blocks;
+    pandoc
+}
+
+fn levels(pandoc: &Pandoc) -> Vec<Int> {
+    pandoc
+        .blocks
+        .iter()
+        .filter_map(|block| match *block {
+            Block::Header(level, ..) => Some(level),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn shift() {
+    let original = doc(vec![header(1, "A"), header(5, "B"), header(6, "C")]);
+
+    let mut pandoc = original.clone();
+    pandoc.shift_headings(2, Overflow::Clamp).unwrap();
+    assert_eq!(levels(&pandoc), [3, 6, 6]);
+
+    let mut pandoc = original.clone();
+    pandoc.shift_headings(1, Overflow::Paragraph).unwrap();
+    assert_eq!(levels(&pandoc), [2, 6]);
+    assert_eq!(
+        pandoc.blocks[2],
+        Block::Para(vec![Inline::Strong(vec![Inline::Str("C".to_string())])])
+    );
+
+    let mut pandoc = original.clone();
+    let error = pandoc.shift_headings(-1, Overflow::Error).unwrap_err();
+    assert_eq!(error.id, "a");
+    assert_eq!(error.level, 0);
+    assert_eq!(pandoc, original);
+}
+
+#[test]
+fn normalize() {
+    let mut pandoc = doc(vec![
+        header(2, "A"),
+        header(4, "B"),
+        header(5, "C"),
+        header(3, "D"),
+        header(2, "E"),
+    ]);
+    pandoc.normalize_headings();
+    assert_eq!(levels(&pandoc), [2, 3, 4, 3, 2]);
+}
+
+#[test]
+fn title() {
+    let mut pandoc = doc(vec![header(1, "Book"), header(2, "Chapter")]);
+    assert!(pandoc.lift_title());
+    assert_eq!(pandoc.meta["title"].stringify(), "Book");
+    assert_eq!(levels(&pandoc), [2]);
+    pandoc.blocks.insert(0, header(1, "Other"));
+    assert!(!pandoc.lift_title());
+}
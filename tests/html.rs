@@ -0,0 +1,146 @@
+use pandoc_ast::writers::html::{write_blocks, HtmlOptions, HtmlOverrides, HtmlWriter};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+#[test]
+fn escaping_attributes_and_notes() {
+    let attr = (
+        "top".to_string(),
+        vec!["a".to_string(), "b".to_string()],
+        vec![("kind".to_string(), "x\"y".to_string())],
+    );
+    let mut inlines = text("a<b & c");
+    inlines.extend([
+        Inline::Space,
+        Inline::Math(MathType::InlineMath, "x<1".to_string()),
+        Inline::Note(vec![Block::Para(text("note"))]),
+        Inline::RawInline(Format::new("html"), "<br>".to_string()),
+        Inline::RawInline(Format::new("latex"), "\\relax".to_string()),
+    ]);
+    let blocks = vec![Block::Header(1, attr, text("Title")), Block::Para(inlines)];
+    assert_eq!(
+        write_blocks(&blocks, &HtmlOptions::default()),
+        "<h1 id=\"top\" class=\"a b\" data-kind=\"x&quot;y\">Title</h1>\n\
+         <p>a&lt;b &amp; c <span class=\"math inline\">\\(x&lt;1\\)</span>\
+         <a href=\"#fn1\" class=\"footnote-ref\" id=\"fnref1\" role=\"doc-noteref\">\
+         <sup>1</sup></a><br></p>\n\
+         <section id=\"footnotes\" class=\"footnotes footnotes-end-of-document\" \
+         role=\"doc-endnotes\">\n<hr />\n<ol>\n\
+         <li id=\"fn1\"><p>note<a href=\"#fnref1\" class=\"footnote-back\" \
+         role=\"doc-backlink\">\u{21a9}\u{fe0e}</a></p></li>\n</ol>\n</section>\n"
+    );
+}
+
+#[test]
+fn figures_and_tables() {
+    let image = Inline::Image(
+        Default::default(),
+        text("alt"),
+        ("a.png".to_string(), String::new()),
+    );
+    let figure = Block::Figure(
+        ("fig".to_string(), vec![], vec![]),
+        (None, vec![Block::Plain(text("A figure"))]),
+        vec![Block::Plain(vec![image])],
+    );
+    let cell = |s: &str, rowspan, colspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            rowspan,
+            colspan,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let mut styled = cell("b", 1, 1);
+    styled
+        .0
+         .2
+        .push(("style".to_string(), "color: red;".to_string()));
+    let table = Block::Table(
+        Default::default(),
+        (None, vec![]),
+        vec![
+            (Alignment::AlignDefault, ColWidth::ColWidthDefault),
+            (Alignment::AlignRight, ColWidth::ColWidthDefault),
+        ],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("wide", 1, 2)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![(Default::default(), vec![cell("a", 2, 1), styled])],
+        )],
+        (Default::default(), vec![]),
+    );
+    let options = HtmlOptions {
+        id_prefix: "p-".to_string(),
+    };
+    assert_eq!(
+        write_blocks(&[figure, table], &options),
+        "<figure id=\"p-fig\">\n<img src=\"a.png\" alt=\"alt\" />\n\
+         <figcaption>A figure</figcaption>\n</figure>\n\
+         <table>\n<thead>\n<tr>\n<th colspan=\"2\">wide</th>\n</tr>\n</thead>\n\
+         <tbody>\n<tr>\n<td rowspan=\"2\">a</td>\n\
+         <td style=\"color: red; text-align: right;\">b</td>\n</tr>\n</tbody>\n</table>\n"
+    );
+}
+
+#[test]
+fn escaped_id_prefix() {
+    let options = HtmlOptions {
+        id_prefix: "a\"b-".to_string(),
+    };
+    let note = Inline::Note(vec![Block::Plain(text("n"))]);
+    assert_eq!(
+        write_blocks(&[Block::Para(vec![note])], &options),
+        "<p><a href=\"#a&quot;b-fn1\" class=\"footnote-ref\" id=\"a&quot;b-fnref1\" \
+         role=\"doc-noteref\"><sup>1</sup></a></p>\n\
+         <section id=\"a&quot;b-footnotes\" class=\"footnotes footnotes-end-of-document\" \
+         role=\"doc-endnotes\">\n<hr />\n<ol>\n\
+         <li id=\"a&quot;b-fn1\">n<a href=\"#a&quot;b-fnref1\" class=\"footnote-back\" \
+         role=\"doc-backlink\">\u{21a9}\u{fe0e}</a></li>\n</ol>\n</section>\n"
+    );
+}
+
+#[test]
+fn overrides() {
+    #[derive(Default)]
+    struct Counted(usize);
+
+    impl HtmlOverrides for Counted {
+        fn block(writer: &mut HtmlWriter<Self>, block: &Block) -> Option<String> {
+            match *block {
+                Block::Header(_, _, ref inlines) => {
+                    writer.overrides.0 += 1;
+                    let title = writer.inlines(inlines);
+                    Some(format!("<p class=\"title\">{}</p>", title))
+                }
+                _ => None,
+            }
+        }
+
+        fn inline(_: &mut HtmlWriter<Self>, inline: &Inline) -> Option<String> {
+            match *inline {
+                Inline::Emph(_) => Some("*".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    let blocks = vec![
+        Block::Header(2, Default::default(), vec![Inline::Emph(text("x"))]),
+        Block::Para(text("y")),
+    ];
+    let mut writer = HtmlWriter::new(HtmlOptions::default(), Counted::default());
+    assert_eq!(
+        writer.blocks(&blocks),
+        "<p class=\"title\">*</p>\n<p>y</p>\n"
+    );
+    assert_eq!(writer.overrides.0, 1);
+}
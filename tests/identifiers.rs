@@ -0,0 +1,48 @@
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+#[test]
+fn auto_identifiers() {
+    let ids = IdGenerator::new(IdentifierStyle::Pandoc);
+    assert_eq!(
+        ids.identifier(&text("Heading identifiers in HTML")),
+        "heading-identifiers-in-html"
+    );
+    assert_eq!(ids.identifier(&text("Maître d'hôtel")), "maître-dhôtel");
+    assert_eq!(
+        ids.identifier(&text("*Dogs*?--in *my* house?")),
+        "dogs--in-my-house"
+    );
+    assert_eq!(ids.identifier(&text("3. Applications")), "applications");
+    assert_eq!(ids.identifier(&text("33")), "");
+    let ascii = IdGenerator::new(IdentifierStyle::Pandoc).ascii(true);
+    assert_eq!(ascii.identifier(&text("Maître d'hôtel")), "maitre-dhotel");
+}
+
+#[test]
+fn gfm_auto_identifiers() {
+    let ids = IdGenerator::from_extensions(&Format::from("gfm+gfm_auto_identifiers").extensions());
+    assert_eq!(ids.identifier(&text("3. Applications")), "3-applications");
+    assert_eq!(ids.identifier(&text("Foo_bar  baz!")), "foo_bar--baz");
+}
+
+#[test]
+fn deduplication() {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[
+        {"t":"Header","c":[1,["intro",[],[]],[{"t":"Str","c":"Intro"}]]},
+        {"t":"Div","c":[["section",[],[]],[]]},
+        {"t":"Para","c":[
+            {"t":"Span","c":[["note",[],[]],[]]},
+            {"t":"Code","c":[["other",[],[]],"x"]}
+        ]}
+    ]}"#;
+    let mut ids = IdGenerator::default();
+    ids.seed(&Pandoc::from_json(json));
+    assert_eq!(ids.generate(&text("Intro")), "intro-1");
+    assert_eq!(ids.generate(&text("Intro")), "intro-2");
+    assert_eq!(ids.generate(&text("42")), "section-1");
+    assert_eq!(ids.generate(&text("Note")), "note-1");
+    assert_eq!(ids.generate(&text("Other")), "other");
+}
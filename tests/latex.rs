@@ -0,0 +1,151 @@
+use pandoc_ast::writers::latex::{write_blocks, write_inlines, CiteCommands, LatexOptions};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn citation(id: &str, mode: CitationMode, suffix: &str) -> Citation {
+    Citation {
+        citationId: id.to_string(),
+        citationPrefix: vec![],
+        citationSuffix: if suffix.is_empty() {
+            vec![]
+        } else {
+            text(suffix)
+        },
+        citationMode: mode,
+        citationNoteNum: 0,
+        citationHash: 0,
+    }
+}
+
+#[test]
+fn escaping_and_inlines() {
+    let mut inlines = text("50% of $x & {y} #1 a_b ~ \\");
+    inlines.extend([
+        Inline::Space,
+        Inline::Emph(text("em")),
+        Inline::Strong(text("bf")),
+        Inline::Strikeout(text("gone")),
+        Inline::Link(
+            Default::default(),
+            text("site"),
+            ("https://x.org/a%20b".to_string(), String::new()),
+        ),
+        Inline::Note(vec![Block::Para(text("note"))]),
+        Inline::RawInline(Format::new("tex"), "\\relax".to_string()),
+        Inline::RawInline(Format::new("html"), "<br>".to_string()),
+    ]);
+    assert_eq!(
+        write_inlines(&inlines, &LatexOptions::default()),
+        "50\\% of \\$x \\& \\{y\\} \\#1 a\\_b \\textasciitilde{} \\textbackslash{} \
+         \\emph{em}\\textbf{bf}\\sout{gone}\\href{https://x.org/a\\%20b}{site}\
+         \\footnote{note}\\relax"
+    );
+}
+
+#[test]
+fn image_paths() {
+    let image = Inline::Image(
+        Default::default(),
+        vec![],
+        ("img\\50%#1{a}.png".to_string(), String::new()),
+    );
+    assert_eq!(
+        write_inlines(&[image], &LatexOptions::default()),
+        "\\includegraphics{img/50\\%\\#1\\{a\\}.png}"
+    );
+}
+
+#[test]
+fn citations() {
+    let cite = Inline::Cite(
+        vec![
+            citation("a", CitationMode::NormalCitation, ""),
+            citation("b", CitationMode::NormalCitation, ""),
+            citation("c", CitationMode::NormalCitation, ", p. 3"),
+            citation("d", CitationMode::AuthorInText, ""),
+        ],
+        vec![],
+    );
+    let natbib = LatexOptions::default();
+    assert_eq!(
+        write_inlines(std::slice::from_ref(&cite), &natbib),
+        "\\citep{a,b}; \\citep[p. 3]{c}; \\citet{d}"
+    );
+    let biblatex = LatexOptions {
+        cite_commands: CiteCommands::Biblatex,
+    };
+    assert_eq!(
+        write_inlines(&[cite], &biblatex),
+        "\\autocites{a}{b}[p. 3]{c}; \\textcite{d}"
+    );
+}
+
+#[test]
+fn blocks() {
+    let cell = |s: &str, colspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            1,
+            colspan,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let table = Block::Table(
+        ("tbl".to_string(), vec![], vec![]),
+        (None, vec![Block::Plain(text("Numbers"))]),
+        vec![
+            (Alignment::AlignLeft, ColWidth::ColWidthDefault),
+            (Alignment::AlignRight, ColWidth::ColWidthDefault),
+        ],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("a", 1), cell("b", 1)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![(Default::default(), vec![cell("both", 2)])],
+        )],
+        (Default::default(), vec![]),
+    );
+    let figure = Block::Figure(
+        ("fig".to_string(), vec![], vec![]),
+        (
+            None,
+            vec![Block::Para(text("A")), Block::Plain(text("figure"))],
+        ),
+        vec![Block::Plain(vec![Inline::Image(
+            (
+                String::new(),
+                vec![],
+                vec![("width".to_string(), "50%".to_string())],
+            ),
+            vec![],
+            ("a.png".to_string(), String::new()),
+        )])],
+    );
+    let blocks = vec![
+        Block::Header(2, ("intro".to_string(), vec![], vec![]), text("Intro")),
+        Block::OrderedList(
+            (3, ListNumberStyle::LowerRoman, ListNumberDelim::OneParen),
+            vec![vec![Block::Plain(text("one"))]],
+        ),
+        figure,
+        table,
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &LatexOptions::default()),
+        "\\subsection{Intro}\\label{intro}\n\n\
+         \\begin{enumerate}\n\\def\\labelenumi{\\roman{enumi})}\n\\setcounter{enumi}{2}\n\
+         \\item one\n\\end{enumerate}\n\n\
+         \\begin{figure}\n\\centering\n\\includegraphics[width=0.5\\linewidth]{a.png}\n\
+         \\caption{A figure}\\label{fig}\n\\end{figure}\n\n\
+         \\begin{longtable}[]{@{}lr@{}}\n\\caption{Numbers}\\label{tbl}\\tabularnewline\n\
+         \\toprule\\noalign{}\na & b \\\\\n\\midrule\\noalign{}\n\\endhead\n\
+         \\multicolumn{2}{l}{both} \\\\\n\\bottomrule\\noalign{}\n\\end{longtable}\n"
+    );
+}
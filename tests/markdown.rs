@@ -0,0 +1,168 @@
+use pandoc_ast::writers::markdown::{write, write_blocks, MarkdownOptions};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn cell(text_: &str, colspan: Int) -> Cell {
+    (
+        Default::default(),
+        Alignment::AlignDefault,
+        1,
+        colspan,
+        vec![Block::Plain(text(text_))],
+    )
+}
+
+fn table(rows: Vec<Vec<Cell>>) -> Block {
+    let mut rows = rows.into_iter().map(|cells| (Attr::default(), cells));
+    Block::Table(
+        Default::default(),
+        (None, vec![]),
+        vec![
+            (Alignment::AlignLeft, ColWidth::ColWidthDefault),
+            (Alignment::AlignRight, ColWidth::ColWidthDefault),
+        ],
+        (Default::default(), rows.next().into_iter().collect()),
+        vec![(Default::default(), 0, vec![], rows.collect())],
+        (Default::default(), vec![]),
+    )
+}
+
+#[test]
+fn inlines_and_escaping() {
+    let mut inlines = text("1. *not* a_b _c");
+    inlines.extend([
+        Inline::Space,
+        Inline::Strong(text("bold")),
+        Inline::Space,
+        Inline::Underline(text("under")),
+        Inline::Space,
+        Inline::Strikeout(text("gone")),
+        Inline::Space,
+        Inline::Code(Default::default(), "a`b".to_string()),
+        Inline::Space,
+        Inline::Link(
+            Default::default(),
+            text("link"),
+            ("http://x.org".to_string(), String::new()),
+        ),
+        Inline::Note(vec![Block::Para(text("note"))]),
+    ]);
+    let options = MarkdownOptions {
+        width: None,
+        ..MarkdownOptions::gfm()
+    };
+    assert_eq!(
+        write_blocks(&[Block::Para(inlines)], &options),
+        "1\\. \\*not\\* a_b \\_c **bold** <u>under</u> ~~gone~~ ``a`b`` \
+         [link](http://x.org)[^1]\n\n[^1]: note\n"
+    );
+}
+
+#[test]
+fn blocks() {
+    let blocks = vec![
+        Block::Header(2, Default::default(), text("Title")),
+        Block::BulletList(vec![
+            vec![Block::Plain(text("\u{2612} done"))],
+            vec![Block::Plain(text("\u{2610} todo"))],
+        ]),
+        Block::BulletList(vec![vec![Block::Plain(text("other"))]]),
+        Block::BlockQuote(vec![Block::Para(text("- quoted"))]),
+        Block::CodeBlock(
+            (String::new(), vec!["rust".to_string()], vec![]),
+            "fn main() {}".to_string(),
+        ),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &MarkdownOptions::gfm()),
+        "## Title\n\n- [x] done\n- [ ] todo\n\n<!-- -->\n\n- other\n\n> \\- quoted\n\n\
+         ```rust\nfn main() {}\n```\n"
+    );
+}
+
+#[test]
+fn tables() {
+    let simple = table(vec![
+        vec![cell("a", 1), cell("b", 1)],
+        vec![cell("1|2", 1), cell("3", 1)],
+    ]);
+    assert_eq!(
+        write_blocks(std::slice::from_ref(&simple), &MarkdownOptions::gfm()),
+        "| a    | b   |\n| :--- | --: |\n| 1\\|2 | 3   |\n"
+    );
+    let html = write_blocks(&[simple], &MarkdownOptions::default());
+    assert!(html.starts_with("<table>\n<thead>\n<tr>\n<th align=\"left\">\n\na\n\n</th>"));
+
+    let spanned = table(vec![
+        vec![cell("a", 1), cell("b", 1)],
+        vec![cell("wide", 2)],
+    ]);
+    let html = write_blocks(&[spanned], &MarkdownOptions::gfm());
+    assert!(html.contains("<td align=\"left\" colspan=\"2\">\n\nwide\n\n</td>"));
+}
+
+#[test]
+fn front_matter() {
+    let mut pandoc = Pandoc {
+        meta: Map::new(),
+        blocks: vec![Block::Para(text("body"))],
+        pandoc_api_version: vec![1, 23],
+    };
+    pandoc.meta_set("title", MetaValue::MetaInlines(text("A: title")));
+    pandoc.meta_set("author", vec![MetaValue::from("Ann")]);
+    pandoc.meta_set("draft", true);
+    assert_eq!(
+        write(&pandoc, &MarkdownOptions::default()),
+        "---\nauthor:\n  - Ann\ndraft: true\ntitle: \"A: title\"\n---\n\nbody\n"
+    );
+}
+
+#[test]
+fn html_attributes() {
+    let attr = (
+        "x".to_string(),
+        vec![],
+        vec![
+            ("x\" onclick=\"alert(1)".to_string(), "1".to_string()),
+            ("title".to_string(), "t".to_string()),
+            ("kind".to_string(), "k".to_string()),
+            ("".to_string(), "empty".to_string()),
+        ],
+    );
+    assert_eq!(
+        write_blocks(
+            &[Block::Div(attr, vec![Block::Para(text("a"))])],
+            &MarkdownOptions::default()
+        ),
+        "<div id=\"x\" title=\"t\" data-kind=\"k\">\n\na\n\n</div>\n"
+    );
+}
+
+#[test]
+fn header_and_code_attributes() {
+    let broken = vec![
+        Inline::Str("a".to_string()),
+        Inline::LineBreak,
+        Inline::Str("b".to_string()),
+    ];
+    let blocks = vec![
+        Block::Header(1, ("a-b".to_string(), vec![], vec![]), broken),
+        Block::Header(2, ("title-1".to_string(), vec![], vec![]), text("Title")),
+        Block::Header(
+            2,
+            ("intro".to_string(), vec!["x".to_string()], vec![]),
+            text("Title"),
+        ),
+        Block::CodeBlock(
+            ("main".to_string(), vec!["rust".to_string()], vec![]),
+            "a < b\n\nc".to_string(),
+        ),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &MarkdownOptions::default()),
+        "# a b\n\n## Title\n\n<h2 id=\"intro\" class=\"x\">\n\nTitle\n\n</h2>\n\n\
+         <pre id=\"main\" class=\"rust\"><code>a &lt; b\n\nc</code></pre>\n"
+    );
+}
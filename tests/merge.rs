@@ -0,0 +1,82 @@
+use pandoc_ast::*;
+
+fn doc(meta: &str, blocks: &str) -> Pandoc {
+    Pandoc::from_json(&format!(
+        r#"{{"pandoc-api-version":[1,23],"meta":{},"blocks":{}}}"#,
+        meta, blocks
+    ))
+}
+
+fn chapter(title: &str) -> Pandoc {
+    doc(
+        &format!(
+            r#"{{"title":{{"t":"MetaString","c":"{}"}},"tags":{{"t":"MetaList","c":[{{"t":"MetaString","c":"{}"}}]}}}}"#,
+            title, title
+        ),
+        r##"[
+            {"t":"Header","c":[1,["intro",[],[]],[{"t":"Str","c":"Intro"}]]},
+            {"t":"Para","c":[{"t":"Link","c":[["",[],[]],[{"t":"Str","c":"see"}],["#intro",""]]}]}
+        ]"##,
+    )
+}
+
+#[test]
+fn rename_ids() {
+    let docs = vec![
+        ("a.md".to_string(), chapter("A")),
+        ("b.md".to_string(), chapter("B")),
+    ];
+    let merged = Pandoc::merge(docs, MergeOptions::default()).unwrap();
+    assert_eq!(merged.blocks.len(), 4);
+    match (&merged.blocks[2], &merged.blocks[3]) {
+        (Block::Header(_, (id, _, _), _), Block::Para(inlines)) => {
+            assert_eq!(id, "intro-1");
+            match inlines[0] {
+                Inline::Link(_, _, (ref url, _)) => assert_eq!(url, "#intro-1"),
+                ref other => panic!("{:?}", other),
+            }
+        }
+        other => panic!("{:?}", other),
+    }
+    assert_eq!(merged.meta["title"].stringify(), "B");
+}
+
+#[test]
+fn meta_policy_and_wrap() {
+    let docs = vec![
+        ("a.md".to_string(), chapter("A")),
+        ("b.md".to_string(), chapter("B")),
+    ];
+    let options = MergeOptions {
+        meta: MetaPolicy::FirstWins,
+        lists: ListMerge::Append,
+        wrap: true,
+    };
+    let merged = Pandoc::merge(docs, options).unwrap();
+    assert_eq!(merged.meta["title"].stringify(), "A");
+    assert_eq!(merged.meta["tags"].stringify(), "AB");
+    match merged.blocks[1] {
+        Block::Div((_, _, ref kvs), _) => {
+            assert_eq!(kvs, &[("source".to_string(), "b.md".to_string())])
+        }
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn errors() {
+    assert_eq!(
+        Pandoc::merge(Vec::new(), MergeOptions::default()),
+        Err(MergeError::Empty)
+    );
+    let mut old = chapter("B");
+    old.pandoc_api_version = vec![1, 22];
+    let docs = vec![
+        ("a.md".to_string(), chapter("A")),
+        ("b.md".to_string(), old),
+    ];
+    match Pandoc::merge(docs, MergeOptions::default()) {
+        Err(MergeError::VersionMismatch { name, .. }) => assert_eq!(name, "b.md"),
+        other => panic!("{:?}", other),
+    }
+}
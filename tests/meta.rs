@@ -0,0 +1,126 @@
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    let s = r#"{"pandoc-api-version":[1,23],"blocks":[],"meta":{
+        "title":{"t":"MetaInlines","c":[{"t":"Str","c":"A"},{"t":"Space"},{"t":"Emph","c":[{"t":"Str","c":"title"}]}]},
+        "draft":{"t":"MetaBool","c":true},
+        "author":{"t":"MetaList","c":[{"t":"MetaMap","c":{"name":{"t":"MetaString","c":"Jane"}}}]}
+    }}"#;
+    Pandoc::from_json(s)
+}
+
+#[test]
+fn get() {
+    let pandoc = doc();
+    assert_eq!(
+        pandoc.meta_get("author.0.name").and_then(MetaValue::as_str),
+        Some("Jane")
+    );
+    assert_eq!(
+        pandoc.meta_get("draft").and_then(MetaValue::as_bool),
+        Some(true)
+    );
+    assert_eq!(
+        pandoc
+            .meta_get("author")
+            .and_then(MetaValue::as_list)
+            .map(<[_]>::len),
+        Some(1)
+    );
+    assert!(pandoc.meta_get("author.1.name").is_none());
+    assert!(pandoc.meta_get("draft.x").is_none());
+    assert_eq!(pandoc.meta_get("title").unwrap().stringify(), "A title");
+}
+
+#[test]
+fn set() {
+    let mut pandoc = doc();
+    pandoc.meta_set("toc", true);
+    pandoc.meta_set("crossref.figPrefix", "Fig.");
+    pandoc.meta_set("author.0.email", "jane@example.com");
+    pandoc.meta_set("author.1.name", "John");
+    assert_eq!(pandoc.meta["toc"], MetaValue::MetaBool(true));
+    assert_eq!(
+        pandoc
+            .meta_get("crossref.figPrefix")
+            .and_then(MetaValue::as_str),
+        Some("Fig.")
+    );
+    assert_eq!(
+        pandoc.meta_get("author.0.name").and_then(MetaValue::as_str),
+        Some("Jane")
+    );
+    assert_eq!(
+        pandoc
+            .meta_get("author.0.email")
+            .and_then(MetaValue::as_str),
+        Some("jane@example.com")
+    );
+    assert_eq!(
+        pandoc.meta_get("author.1.name").and_then(MetaValue::as_str),
+        Some("John")
+    );
+}
+
+#[test]
+fn json() {
+    let json = serde_json::json!({
+        "title": "A  title\nhere",
+        "toc": true,
+        "depth": 3,
+        "tags": ["a", "b"],
+    });
+    let options = JsonOptions::default();
+    let meta = MetaValue::from_json_value(&json, &options);
+    assert_eq!(
+        meta.get("depth"),
+        Some(&MetaValue::MetaString("3".to_string()))
+    );
+    assert_eq!(meta.get("toc"), Some(&MetaValue::MetaBool(true)));
+    let options = JsonOptions {
+        parse_numbers: true,
+        ..options
+    };
+    assert_eq!(meta.to_json_value(&options), json);
+
+    let options = JsonOptions {
+        strings_as_inlines: true,
+        ..options
+    };
+    let meta = MetaValue::from_json_value(&json, &options);
+    assert_eq!(
+        meta.get("title"),
+        Some(&MetaValue::MetaInlines(vec![
+            Inline::Str("A".to_string()),
+            Inline::Space,
+            Inline::Str("title".to_string()),
+            Inline::SoftBreak,
+            Inline::Str("here".to_string()),
+        ]))
+    );
+}
+
+#[test]
+fn merge() {
+    let options = JsonOptions::default();
+    let base = serde_json::json!({"toc": true, "tags": ["a"], "crossref": {"chapters": true}});
+    let other = serde_json::json!({"toc": false, "tags": ["b"], "crossref": {"figPrefix": "Fig."}});
+    let base = MetaValue::from_json_value(&base, &options)
+        .into_map()
+        .unwrap();
+    let other = MetaValue::from_json_value(&other, &options)
+        .into_map()
+        .unwrap();
+
+    let mut replaced = base.clone();
+    merge_meta(&mut replaced, other.clone(), ListMerge::Replace);
+    let mut appended = base;
+    merge_meta(&mut appended, other, ListMerge::Append);
+
+    let json = |meta: Map<String, MetaValue>| MetaValue::from(meta).to_json_value(&options);
+    assert_eq!(
+        json(replaced),
+        serde_json::json!({"toc": false, "tags": ["b"], "crossref": {"chapters": true, "figPrefix": "Fig."}})
+    );
+    assert_eq!(json(appended)["tags"], serde_json::json!(["a", "b"]));
+}
@@ -0,0 +1,72 @@
+use pandoc_ast::*;
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct Crossref {
+    fig_prefix: String,
+    chapters: bool,
+    depth: u32,
+    labels: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+struct Config {
+    crossref: Crossref,
+    mode: Mode,
+}
+
+#[derive(Deserialize, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum Mode {
+    Draft,
+    Final,
+}
+
+fn doc() -> Pandoc {
+    let s = r#"{"pandoc-api-version":[1,23],"blocks":[],"meta":{
+        "mode":{"t":"MetaInlines","c":[{"t":"Str","c":"final"}]},
+        "crossref":{"t":"MetaMap","c":{
+            "figPrefix":{"t":"MetaInlines","c":[{"t":"Str","c":"Fig."}]},
+            "chapters":{"t":"MetaBool","c":false},
+            "depth":{"t":"MetaString","c":"3"}
+        }}
+    }}"#;
+    Pandoc::from_json(s)
+}
+
+#[test]
+fn round_trip() {
+    let cfg: Config = from_meta(&doc().meta).unwrap();
+    assert_eq!(
+        cfg,
+        Config {
+            crossref: Crossref {
+                fig_prefix: "Fig.".to_string(),
+                chapters: false,
+                depth: 3,
+                labels: None,
+            },
+            mode: Mode::Final,
+        }
+    );
+    let meta = to_meta(&cfg).unwrap();
+    assert_eq!(meta["mode"], MetaValue::MetaString("final".to_string()));
+    let crossref = meta["crossref"].as_map().unwrap();
+    assert_eq!(*crossref["depth"], MetaValue::MetaString("3".to_string()));
+    assert!(!crossref.contains_key("labels"));
+    assert_eq!(from_meta::<Config>(&meta).unwrap(), cfg);
+}
+
+#[test]
+fn error_path() {
+    let mut pandoc = doc();
+    pandoc.meta_set("crossref.depth", "three");
+    let err = from_meta::<Config>(&pandoc.meta).unwrap_err();
+    assert_eq!(err.path(), Some("crossref.depth"));
+
+    pandoc.meta_set("crossref.labels", vec![MetaValue::MetaBool(true)]);
+    pandoc.meta_set("crossref.depth", "3");
+    let err = from_meta::<Config>(&pandoc.meta).unwrap_err();
+    assert_eq!(err.path(), Some("crossref.labels.0"));
+}
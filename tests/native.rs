@@ -0,0 +1,147 @@
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn document(blocks: Vec<Block>) -> Pandoc {
+    Pandoc {
+        meta: Map::new(),
+        blocks,
+        pandoc_api_version: vec![1, 23, 1],
+    }
+}
+
+#[test]
+fn layout() {
+    let mut pandoc = document(vec![
+        Block::Para(vec![
+            Inline::Str("caf\u{e9}1".to_string()),
+            Inline::Emph(text("x")),
+        ]),
+        Block::Header(
+            1,
+            ("intro".to_string(), vec![], vec![]),
+            text("An introduction to the subject"),
+        ),
+    ]);
+    pandoc
+        .meta
+        .insert("title".to_string(), MetaValue::MetaInlines(text("Hi")));
+    assert_eq!(
+        pandoc.to_native(),
+        r#"Pandoc
+  Meta
+    { unMeta = fromList [ ( "title" , MetaInlines [ Str "Hi" ] ) ] }
+  [ Para [ Str "caf\233\&1" , Emph [ Str "x" ] ]
+  , Header
+      1
+      ( "intro" , [] , [] )
+      [ Str "An"
+      , Space
+      , Str "introduction"
+      , Space
+      , Str "to"
+      , Space
+      , Str "the"
+      , Space
+      , Str "subject"
+      ]
+  ]
+"#
+    );
+}
+
+#[test]
+fn round_trip() {
+    let cell = |s: &str, rowspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            rowspan,
+            1,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let citation = Citation {
+        citationId: "doe".to_string(),
+        citationPrefix: text("see"),
+        citationSuffix: vec![],
+        citationMode: CitationMode::NormalCitation,
+        citationNoteNum: 1,
+        citationHash: 0,
+    };
+    let mut pandoc = document(vec![
+        Block::Table(
+            ("t".to_string(), vec!["wide".to_string()], vec![]),
+            (Some(text("short")), vec![Block::Plain(text("A table"))]),
+            vec![
+                (Alignment::AlignLeft, ColWidth::ColWidth(0.25)),
+                (Alignment::AlignRight, ColWidth::ColWidthDefault),
+            ],
+            (Default::default(), vec![]),
+            vec![(
+                Default::default(),
+                -1,
+                vec![],
+                vec![(Default::default(), vec![cell("a", 2), cell("b", 1)])],
+            )],
+            (Default::default(), vec![]),
+        ),
+        Block::OrderedList(
+            (3, ListNumberStyle::LowerRoman, ListNumberDelim::OneParen),
+            vec![vec![Block::Plain(vec![
+                Inline::Cite(vec![citation], text("[see @doe]")),
+                Inline::Str("\"tab\there\\ \u{e}H \u{7f}".to_string()),
+                Inline::Link(
+                    Default::default(),
+                    text("x"),
+                    ("https://x.org".to_string(), String::new()),
+                ),
+                Inline::RawInline(Format::new("html"), "<br>".to_string()),
+            ])]],
+        ),
+    ]);
+    let mut map = Map::new();
+    map.insert("draft".to_string(), Box::new(MetaValue::MetaBool(true)));
+    map.insert(
+        "tags".to_string(),
+        Box::new(MetaValue::MetaList(vec![MetaValue::MetaString(
+            "a".to_string(),
+        )])),
+    );
+    pandoc
+        .meta
+        .insert("options".to_string(), MetaValue::MetaMap(map));
+    let native = pandoc.to_native();
+    assert!(native.contains(r#"Str "\"tab\there\\ \SO\&H \DEL""#));
+    assert!(native.contains("(RowHeadColumns (-1))"));
+    assert_eq!(Pandoc::from_native(&native), Ok(pandoc));
+}
+
+#[test]
+fn parse_compact_and_errors() {
+    let native = r#"[Para [Str "caf\233",Space,Quoted DoubleQuote [Str "x"]]
+,CodeBlock ("",["rust"],[]) "fn main() {}\n"]"#;
+    let pandoc = Pandoc::from_native(native).unwrap();
+    assert_eq!(
+        pandoc.blocks,
+        vec![
+            Block::Para(vec![
+                Inline::Str("caf\u{e9}".to_string()),
+                Inline::Space,
+                Inline::Quoted(QuoteType::DoubleQuote, text("x")),
+            ]),
+            Block::CodeBlock(
+                (String::new(), vec!["rust".to_string()], vec![]),
+                "fn main() {}\n".to_string()
+            ),
+        ]
+    );
+
+    let err = Pandoc::from_native("[ Para [ Str \"x\" ]\n, Para [ Str ] ]").unwrap_err();
+    assert_eq!(err.position(), Some((2, 10)));
+    assert_eq!(err.message(), "expected an inline, found `Str`");
+    let err = Pandoc::from_native("[ Para [ Str \"x\" ]\n, Para [ Str \"y\" ").unwrap_err();
+    assert_eq!(err.position(), Some((2, 18)));
+    assert_eq!(err.to_string(), "2:18: expected `,` or `]`");
+}
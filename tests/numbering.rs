@@ -0,0 +1,81 @@
+use pandoc_ast::*;
+
+fn header(level: Int, classes: &[&str], text: &str) -> Block {
+    let classes = classes.iter().map(|c| c.to_string()).collect();
+    Block::Header(
+        level,
+        (String::new(), classes, vec![]),
+        vec![Inline::Str(text.to_string())],
+    )
+}
+
+fn numbers(blocks: &[Block]) -> Vec<String> {
+    blocks
+        .iter()
+        .map(|block| match *block {
+            Block::Header(_, _, ref title) => stringify(title),
+            _ => panic!(),
+        })
+        .collect()
+}
+
+#[test]
+fn styles() {
+    assert_eq!(ListNumberStyle::LowerRoman.format(14), "xiv");
+    assert_eq!(ListNumberStyle::UpperAlpha.format(28), "AB");
+    assert_eq!(ListNumberStyle::Decimal.format(7), "7");
+}
+
+#[test]
+fn numbering() {
+    let mut blocks = vec![
+        header(1, &[], "A"),
+        header(2, &[], "B"),
+        header(3, &[], "C"),
+        header(2, &["unnumbered"], "D"),
+        header(2, &[], "E"),
+        header(1, &["unnumbered", "appendix"], "Appendices"),
+        header(1, &[], "F"),
+        header(2, &[], "G"),
+    ];
+    let options = NumberingOptions {
+        formats: vec![ListNumberStyle::Decimal, ListNumberStyle::LowerAlpha],
+        ..NumberingOptions::default()
+    };
+    number_sections(&mut blocks, &options);
+    assert_eq!(
+        numbers(&blocks),
+        [
+            "1 A",
+            "1.a B",
+            "1.a.1 C",
+            "D",
+            "1.b E",
+            "Appendices",
+            "A F",
+            "A.a G"
+        ]
+    );
+    match blocks[1] {
+        Block::Header(_, (_, _, ref kvs), _) => {
+            assert_eq!(kvs, &[("number".to_string(), "1.a".to_string())])
+        }
+        _ => panic!(),
+    }
+    // numbering again replaces the old numbers
+    number_sections(&mut blocks, &options);
+    assert_eq!(numbers(&blocks)[0], "1 A");
+}
+
+#[test]
+fn offset_from_meta() {
+    let mut pandoc = Pandoc {
+        meta: Map::new(),
+        blocks: vec![header(2, &[], "A"), header(1, &[], "B")],
+        pandoc_api_version: vec![1, 23],
+    };
+    pandoc.meta_set("number-offset", "1,4");
+    let options = NumberingOptions::from_meta(&pandoc.meta);
+    pandoc.number_sections(&options);
+    assert_eq!(numbers(&pandoc.blocks), ["1.5 A", "2 B"]);
+}
@@ -0,0 +1,262 @@
+use pandoc_ast::readers::org::read;
+use pandoc_ast::writers::org::{write, write_blocks, OrgOptions};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn plain_cell(s: &str) -> Cell {
+    (
+        Attr::default(),
+        Alignment::AlignDefault,
+        1,
+        1,
+        vec![Block::Plain(text(s))],
+    )
+}
+
+#[test]
+fn writer() {
+    let header = Block::Header(
+        1,
+        (
+            "intro".to_string(),
+            vec!["unnumbered".to_string()],
+            vec![("owner".to_string(), "max".to_string())],
+        ),
+        text("Getting started"),
+    );
+    let mut inlines = text("Use");
+    inlines.extend([
+        Inline::Space,
+        Inline::Code(Default::default(), "cargo".to_string()),
+        Inline::Space,
+        Inline::Emph(text("or")),
+        Inline::Space,
+        Inline::Link(
+            Default::default(),
+            text("the docs"),
+            ("https://x.org".to_string(), String::new()),
+        ),
+        Inline::Note(vec![Block::Para(text("Really."))]),
+    ]);
+    let blocks = vec![
+        header,
+        Block::Header(2, ("next".to_string(), vec![], vec![]), text("Next")),
+        Block::Para(inlines),
+        Block::CodeBlock(
+            (String::new(), vec!["rust".to_string()], vec![]),
+            "* not a headline".to_string(),
+        ),
+        Block::OrderedList(
+            (3, ListNumberStyle::Decimal, ListNumberDelim::Period),
+            vec![
+                vec![Block::Plain(text("three"))],
+                vec![Block::Plain(text("four"))],
+            ],
+        ),
+        Block::Table(
+            Default::default(),
+            (None, vec![Block::Plain(text("Sizes"))]),
+            vec![(Alignment::AlignDefault, ColWidth::ColWidthDefault); 2],
+            (
+                Default::default(),
+                vec![(Default::default(), vec![plain_cell("a"), plain_cell("bb")])],
+            ),
+            vec![(
+                Default::default(),
+                0,
+                vec![],
+                vec![(Default::default(), vec![plain_cell("1"), plain_cell("2")])],
+            )],
+            (Default::default(), vec![]),
+        ),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &OrgOptions::default()),
+        "* Getting started\n\
+         :PROPERTIES:\n:CUSTOM_ID: intro\n:UNNUMBERED: t\n:owner: max\n:END:\n\n\
+         ** Next\n\n\
+         Use =cargo= /or/ [[https://x.org][the docs]][fn:1]\n\n\
+         #+begin_src rust\n,* not a headline\n#+end_src\n\n\
+         3. [@3] three\n4. four\n\n\
+         #+caption: Sizes\n\
+         | a | bb |\n|---+----|\n| 1 | 2  |\n\n\
+         [fn:1] Really.\n"
+    );
+}
+
+#[test]
+fn reader() {
+    let org = "#+TITLE: Notes\n\
+               #+author: Max\n\
+               \n\
+               * Tasks :work:\n\
+               :PROPERTIES:\n\
+               :CUSTOM_ID: tasks\n\
+               :END:\n\
+               Some *bold* and /emphasis/,\n\
+               a [[https://x.org][link]][fn:a].\n\
+               \n\
+               - [ ] first\n\
+               - second\n\
+               \x20 1) nested\n\
+               \n\
+               #+begin_src python\n\
+               ,#+not a keyword\n\
+               #+end_src\n\
+               \n\
+               | x | y |\n\
+               |---+---|\n\
+               | 1 |   |\n\
+               \n\
+               [fn:a] The note.\n";
+    let pandoc = read(org);
+    assert_eq!(
+        pandoc.meta.get("title"),
+        Some(&MetaValue::MetaInlines(text("Notes")))
+    );
+    let mut para = text("Some");
+    para.extend([
+        Inline::Space,
+        Inline::Strong(text("bold")),
+        Inline::Space,
+        Inline::Str("and".to_string()),
+        Inline::Space,
+        Inline::Emph(text("emphasis")),
+        Inline::Str(",".to_string()),
+        Inline::SoftBreak,
+        Inline::Str("a".to_string()),
+        Inline::Space,
+        Inline::Link(
+            Default::default(),
+            text("link"),
+            ("https://x.org".to_string(), String::new()),
+        ),
+        Inline::Note(vec![Block::Para(text("The note."))]),
+        Inline::Str(".".to_string()),
+    ]);
+    let empty: Cell = (Attr::default(), Alignment::AlignDefault, 1, 1, vec![]);
+    assert_eq!(
+        pandoc.blocks,
+        vec![
+            Block::Header(
+                1,
+                ("tasks".to_string(), vec!["work".to_string()], vec![]),
+                text("Tasks")
+            ),
+            Block::Para(para),
+            Block::BulletList(vec![
+                vec![Block::Plain(text("\u{2610} first"))],
+                vec![
+                    Block::Plain(text("second")),
+                    Block::OrderedList(
+                        (1, ListNumberStyle::Decimal, ListNumberDelim::OneParen),
+                        vec![vec![Block::Plain(text("nested"))]],
+                    ),
+                ],
+            ]),
+            Block::CodeBlock(
+                (String::new(), vec!["python".to_string()], vec![]),
+                "#+not a keyword".to_string()
+            ),
+            Block::Table(
+                Default::default(),
+                (None, vec![]),
+                vec![(Alignment::AlignDefault, ColWidth::ColWidthDefault); 2],
+                (
+                    Default::default(),
+                    vec![(Default::default(), vec![plain_cell("x"), plain_cell("y")])],
+                ),
+                vec![(
+                    Default::default(),
+                    0,
+                    vec![],
+                    vec![(Default::default(), vec![plain_cell("1"), empty])],
+                )],
+                (Default::default(), vec![]),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn round_trip() {
+    let mut meta = Map::new();
+    meta.insert("title".to_string(), MetaValue::MetaInlines(text("Doc")));
+    let pandoc = Pandoc {
+        meta,
+        blocks: vec![
+            Block::Header(1, ("doc".to_string(), vec![], vec![]), text("Doc")),
+            Block::Header(2, ("custom".to_string(), vec![], vec![]), text("Part")),
+            Block::Para(vec![
+                Inline::Underline(text("under")),
+                Inline::Space,
+                Inline::Strikeout(text("gone")),
+                Inline::Space,
+                Inline::Math(MathType::InlineMath, "x^2".to_string()),
+            ]),
+            Block::BlockQuote(vec![Block::Para(text("quoted"))]),
+            Block::BulletList(vec![
+                vec![Block::Para(text("loose"))],
+                vec![Block::Para(text("items"))],
+            ]),
+            Block::DefinitionList(vec![(text("term"), vec![vec![Block::Plain(text("def"))]])]),
+            Block::CodeBlock(Attr::default(), "  indented\n*stars".to_string()),
+            Block::HorizontalRule,
+        ],
+        pandoc_api_version: vec![1, 23, 1],
+    };
+    assert_eq!(read(&write(&pandoc, &OrgOptions::default())), pandoc);
+}
+
+#[test]
+fn list_with_unicode_indentation() {
+    assert_eq!(
+        read("- a\n \u{a0}b\n").blocks,
+        vec![Block::BulletList(vec![vec![Block::Plain(vec![
+            Inline::Str("a".to_string()),
+            Inline::SoftBreak,
+            Inline::Str("b".to_string()),
+        ])]])]
+    );
+}
+
+#[test]
+fn self_referencing_footnote() {
+    let mut note = text("See");
+    note.extend([Inline::Space, Inline::Str("also[fn:1]".to_string())]);
+    assert_eq!(
+        read("Text[fn:1]\n\n[fn:1] See also[fn:1]\n").blocks,
+        vec![Block::Para(vec![
+            Inline::Str("Text".to_string()),
+            Inline::Note(vec![Block::Para(note)]),
+        ])]
+    );
+}
+
+#[test]
+fn escaping() {
+    let mut code = text("use");
+    code.extend([
+        Inline::Space,
+        Inline::Code(Default::default(), "a == b".to_string()),
+    ]);
+    let blocks = vec![
+        Block::Para(text("* not a headline")),
+        Block::Para(text("1. not a list")),
+        Block::Para(text("a long line | pipe")),
+        Block::Para(code),
+    ];
+    let org = write_blocks(&blocks, &OrgOptions { width: Some(12) });
+    assert_eq!(
+        org,
+        "\u{200b}* not a\nheadline\n\n\u{200b}1. not a\nlist\n\n\
+         a long line\n\u{200b}| pipe\n\nuse ~a == b~\n"
+    );
+    assert!(read(&org)
+        .blocks
+        .iter()
+        .all(|b| matches!(b, Block::Para(_))));
+    assert_eq!(read(&org).blocks[3], blocks[3]);
+}
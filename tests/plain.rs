@@ -0,0 +1,97 @@
+use pandoc_ast::writers::plain::{write_blocks, PlainOptions};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn cell(text_: &str, rowspan: Int, colspan: Int) -> Cell {
+    (
+        Default::default(),
+        Alignment::AlignDefault,
+        rowspan,
+        colspan,
+        vec![Block::Plain(text(text_))],
+    )
+}
+
+fn width(width: usize) -> PlainOptions {
+    PlainOptions { width: Some(width) }
+}
+
+#[test]
+fn wrapping_and_notes() {
+    let mut para = text("one two three four five");
+    para.push(Inline::Note(vec![Block::Para(text("a note"))]));
+    para.push(Inline::Space);
+    para.push(Inline::Emph(text("six")));
+    let blocks = vec![
+        Block::Header(1, Default::default(), text("Title")),
+        Block::Para(para),
+        Block::BlockQuote(vec![Block::Para(text("quoted text here"))]),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &width(14)),
+        "Title\n\none two three\nfour five[1]\nsix\n\n  quoted text\n  here\n\n[1] a note\n"
+    );
+}
+
+#[test]
+fn lists() {
+    let items = |n: usize| -> Vec<Vec<Block>> {
+        (0..n)
+            .map(|i| vec![Block::Plain(text(&format!("item {}", i)))])
+            .collect()
+    };
+    let mut nested = items(2);
+    nested[1].push(Block::BulletList(items(1)));
+    let blocks = vec![
+        Block::OrderedList(
+            (9, ListNumberStyle::LowerRoman, ListNumberDelim::TwoParens),
+            nested,
+        ),
+        Block::OrderedList(
+            (1, ListNumberStyle::UpperAlpha, ListNumberDelim::Period),
+            items(1),
+        ),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &PlainOptions::default()),
+        "(ix) item 0\n(x)  item 1\n     - item 0\n\nA. item 0\n"
+    );
+}
+
+#[test]
+fn table_with_spans() {
+    let row = |cells: Vec<Cell>| (Attr::default(), cells);
+    let table = Block::Table(
+        Default::default(),
+        (None, vec![Block::Plain(text("Caption"))]),
+        vec![(Alignment::AlignDefault, ColWidth::ColWidthDefault); 3],
+        (
+            Default::default(),
+            vec![row(vec![cell("a", 1, 1), cell("b", 1, 1), cell("c", 1, 1)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![
+                row(vec![cell("wide cell", 1, 2), cell("tall", 2, 1)]),
+                row(vec![cell("1", 1, 1), cell("2", 1, 1)]),
+            ],
+        )],
+        (Default::default(), vec![]),
+    );
+    let expected = "\
++---+-------+------+
+| a | b     | c    |
++===+=======+======+
+| wide cell | tall |
++---+-------+      |
+| 1 | 2     |      |
++---+-------+------+
+
+Caption
+";
+    assert_eq!(write_blocks(&[table], &PlainOptions::default()), expected);
+}
@@ -0,0 +1,209 @@
+use pandoc_ast::writers::rst::{write, write_blocks, RstOptions};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn attr(id: &str, classes: &[&str]) -> Attr {
+    (
+        id.to_string(),
+        classes.iter().map(|c| c.to_string()).collect(),
+        vec![],
+    )
+}
+
+#[test]
+fn headers_and_directives() {
+    let mut meta = Map::new();
+    meta.insert("title".to_string(), MetaValue::MetaInlines(text("Guide")));
+    meta.insert(
+        "author".to_string(),
+        MetaValue::MetaString("Max".to_string()),
+    );
+    let pandoc = Pandoc {
+        meta,
+        blocks: vec![
+            Block::Header(1, attr("first-steps", &[]), text("First steps")),
+            Block::Header(2, attr("setup", &[]), text("Installing it")),
+            Block::CodeBlock(attr("", &["rust"]), "let x = 1;\n".to_string()),
+            Block::Div(
+                attr("careful", &["warning"]),
+                vec![Block::Para(text("Mind *this*."))],
+            ),
+            Block::BulletList(vec![vec![
+                Block::Plain(text("item")),
+                Block::Header(3, attr("", &[]), text("Nested")),
+            ]]),
+        ],
+        pandoc_api_version: vec![1, 23, 1],
+    };
+    assert_eq!(
+        write(&pandoc, &RstOptions::default()),
+        "=====\nGuide\n=====\n\n:Author: Max\n\n\
+         First steps\n===========\n\n\
+         .. _setup:\n\nInstalling it\n-------------\n\n\
+         .. code:: rust\n\n   let x = 1;\n\n\
+         .. warning::\n   :name: careful\n\n   Mind \\*this\\*.\n\n\
+         - item\n\n  .. rubric:: Nested\n"
+    );
+}
+
+#[test]
+fn internal_links() {
+    let link = Inline::Link(
+        Default::default(),
+        text("start here"),
+        ("#getting-started".to_string(), String::new()),
+    );
+    let blocks = vec![
+        Block::Header(1, attr("getting-started", &[]), text("Getting started")),
+        Block::Header(1, attr("next-steps", &[]), text("Next steps")),
+        Block::Para(vec![link]),
+    ];
+    assert_eq!(
+        write_blocks(&blocks, &RstOptions::default()),
+        ".. _getting-started:\n\n\
+         Getting started\n===============\n\n\
+         Next steps\n==========\n\n\
+         `start here <getting-started_>`__\n"
+    );
+}
+
+#[test]
+fn inline_markup() {
+    let mut inlines = vec![Inline::Str("a".to_string())];
+    inlines.extend([
+        Inline::Emph(vec![
+            Inline::Space,
+            Inline::Str("b".to_string()),
+            Inline::Strong(text("c")),
+        ]),
+        Inline::Str("d_".to_string()),
+        Inline::Space,
+        Inline::Superscript(text("2")),
+        Inline::Space,
+        Inline::Code(Default::default(), "x*y".to_string()),
+        Inline::Space,
+        Inline::Link(
+            Default::default(),
+            text("docs"),
+            ("https://x.org".to_string(), String::new()),
+        ),
+        Inline::Space,
+        Inline::Link(
+            Default::default(),
+            text("above"),
+            ("#setup".to_string(), String::new()),
+        ),
+        Inline::Note(vec![Block::Para(text("A note."))]),
+        Inline::Space,
+        Inline::Image(
+            Default::default(),
+            text("logo"),
+            ("logo.png".to_string(), String::new()),
+        ),
+    ]);
+    let mut blocks: Vec<Block> = [
+        "1. not a list",
+        "ii. not a list",
+        "(iv) also",
+        "IX. nine",
+        "(#) auto",
+        "#) not a list",
+    ]
+    .iter()
+    .map(|s| Block::Para(text(s)))
+    .collect();
+    blocks.push(Block::Para(inlines));
+    assert_eq!(
+        write_blocks(&blocks, &RstOptions { width: None }),
+        "1\\. not a list\n\nii\\. not a list\n\n(iv\\) also\n\nIX\\. nine\n\n\
+         (#\\) auto\n\n#\\) not a list\n\n\
+         a *bc*\\ d\\_ :sup:`2` ``x*y`` `docs <https://x.org>`__ \
+         `above <setup_>`__\\ [1]_ |logo|\n\n\
+         .. [1] A note.\n\n\
+         .. |logo| image:: logo.png\n"
+    );
+}
+
+#[test]
+fn grid_tables_and_figures() {
+    let cell = |s: &str, rowspan, colspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            rowspan,
+            colspan,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let table = Block::Table(
+        attr("tbl", &[]),
+        (None, vec![Block::Plain(text("Spans"))]),
+        vec![
+            (Alignment::AlignDefault, ColWidth::ColWidthDefault),
+            (Alignment::AlignDefault, ColWidth::ColWidthDefault),
+        ],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("wide", 1, 2)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![(Default::default(), vec![cell("a", 1, 1), cell("b", 1, 1)])],
+        )],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("x", 1, 1), cell("y", 1, 1)])],
+        ),
+    );
+    let figure = Block::Figure(
+        attr("fig", &[]),
+        (None, vec![Block::Plain(text("A figure"))]),
+        vec![Block::Plain(vec![Inline::Image(
+            Default::default(),
+            text("alt"),
+            ("a.png".to_string(), String::new()),
+        )])],
+    );
+    assert_eq!(
+        write_blocks(&[table, figure], &RstOptions::default()),
+        ".. table:: Spans\n   :name: tbl\n\n\
+         \x20  +-------+\n\
+         \x20  | wide  |\n\
+         \x20  +===+===+\n\
+         \x20  | a | b |\n\
+         \x20  +---+---+\n\
+         \x20  | x | y |\n\
+         \x20  +---+---+\n\n\
+         .. figure:: a.png\n   :alt: alt\n   :name: fig\n\n   A figure\n"
+    );
+}
+
+#[test]
+fn span_targets() {
+    let link = |id: &str| {
+        Inline::Link(
+            Default::default(),
+            text("see"),
+            (format!("#{}", id), String::new()),
+        )
+    };
+    let mut inlines = vec![Inline::Span(attr("anchor", &[]), text("some words"))];
+    inlines.extend([
+        Inline::Space,
+        Inline::Span(attr("more-words", &[]), text("more words")),
+        Inline::Space,
+        link("anchor"),
+        Inline::Space,
+        link("more-words"),
+    ]);
+    let blocks = vec![Block::Para(inlines)];
+    assert_eq!(
+        write_blocks(&blocks, &RstOptions { width: None }),
+        ".. _anchor:\n\n\
+         some words _`more words` `see <anchor_>`__ `see <more-words_>`__\n"
+    );
+}
@@ -0,0 +1,131 @@
+use pandoc_ast::*;
+
+fn header(level: Int, id: &str, classes: &[&str], text: &str) -> Block {
+    let classes = classes.iter().map(|c| c.to_string()).collect();
+    Block::Header(
+        level,
+        (id.to_string(), classes, vec![]),
+        vec![Inline::Str(text.to_string())],
+    )
+}
+
+fn para(text: &str) -> Block {
+    Block::Para(vec![Inline::Str(text.to_string())])
+}
+
+fn blocks() -> Vec<Block> {
+    vec![
+        para("intro"),
+        header(1, "a", &[], "A"),
+        para("a"),
+        header(2, "b", &[], "B"),
+        para("b"),
+        header(1, "c", &["unnumbered"], "C"),
+        header(2, "d", &[], "D"),
+    ]
+}
+
+fn number(block: &Block) -> Option<&str> {
+    match *block {
+        Block::Div(_, ref blocks) => match blocks[0] {
+            Block::Header(_, (_, _, ref kvs), _) => {
+                kvs.iter().find(|(k, _)| k == "number").map(|(_, v)| &v[..])
+            }
+            _ => panic!("section without header"),
+        },
+        _ => panic!("not a section"),
+    }
+}
+
+#[test]
+fn nesting_and_numbering() {
+    let options = MakeSectionsOptions {
+        number_sections: true,
+        base_level: None,
+    };
+    let sections = make_sections(&blocks(), options);
+    assert_eq!(sections.len(), 3);
+    assert_eq!(sections[0], para("intro"));
+    let a = match sections[1] {
+        Block::Div((ref id, ref classes, _), ref inner) => {
+            assert_eq!(id, "a");
+            assert_eq!(classes, &["section"]);
+            inner
+        }
+        _ => panic!(),
+    };
+    assert_eq!(number(&sections[1]), Some("1"));
+    assert_eq!(a.len(), 3);
+    assert_eq!(number(&a[2]), Some("1.1"));
+    match a[0] {
+        Block::Header(1, (ref id, _, _), ref title) => {
+            assert_eq!(id, "");
+            assert_eq!(title.len(), 3);
+            assert_eq!(stringify(title), "1 A");
+        }
+        _ => panic!(),
+    }
+    let c = match sections[2] {
+        Block::Div(_, ref inner) => inner,
+        _ => panic!(),
+    };
+    assert_eq!(number(&sections[2]), None);
+    assert_eq!(number(&c[1]), Some("1.2"));
+}
+
+#[test]
+fn base_level() {
+    let options = MakeSectionsOptions {
+        number_sections: false,
+        base_level: Some(2),
+    };
+    let sections = make_sections(&blocks(), options);
+    match sections[1] {
+        Block::Div(_, ref inner) => assert!(matches!(inner[0], Block::Header(2, ..))),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let mut pandoc = Pandoc {
+        meta: Map::new(),
+        blocks: blocks(),
+        pandoc_api_version: vec![1, 23],
+    };
+    pandoc.make_sections(MakeSectionsOptions {
+        number_sections: true,
+        base_level: None,
+    });
+    pandoc.flatten_sections();
+    assert_eq!(pandoc.blocks, blocks());
+}
+
+#[test]
+fn extract_section() {
+    let mut pandoc = Pandoc {
+        meta: Map::new(),
+        blocks: blocks(),
+        pandoc_api_version: vec![1, 23],
+    };
+    assert_eq!(pandoc.section("a"), Some(blocks()[1..5].to_vec()));
+    assert_eq!(pandoc.section("b"), Some(blocks()[3..5].to_vec()));
+    assert_eq!(pandoc.section("missing"), None);
+
+    let removed = pandoc.remove_section("b").unwrap();
+    assert_eq!(removed.len(), 2);
+    assert_eq!(pandoc.blocks.len(), 5);
+
+    pandoc.make_sections(MakeSectionsOptions::default());
+    let section = pandoc.section("c").unwrap();
+    assert!(matches!(section[..], [Block::Div(..)]));
+    let old = pandoc.replace_section("d", vec![para("new")]).unwrap();
+    assert!(matches!(old[..], [Block::Div(..)]));
+    assert_eq!(pandoc.section("c").unwrap()[0], {
+        let mut c = section[0].clone();
+        if let Block::Div(_, ref mut inner) = c {
+            inner[1] = para("new");
+        }
+        c
+    });
+}
@@ -0,0 +1,76 @@
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    let json = r##"{"pandoc-api-version":[1,23],"meta":{"title":{"t":"MetaString","c":"Book"}},"blocks":[
+        {"t":"Para","c":[{"t":"Str","c":"Preface"}]},
+        {"t":"Header","c":[1,["intro",[],[]],[{"t":"Str","c":"Intro"}]]},
+        {"t":"Header","c":[2,["detail",[],[]],[{"t":"Str","c":"Detail"}]]},
+        {"t":"Header","c":[1,["",[],[]],[{"t":"Str","c":"Usage"}]]},
+        {"t":"Para","c":[{"t":"Link","c":[["",[],[]],[{"t":"Str","c":"see"}],["#detail",""]]}]},
+        {"t":"Div","c":[["",["chapter"],[]],[{"t":"Para","c":[{"t":"Link","c":[["",[],[]],[{"t":"Str","c":"up"}],["#usage-note",""]]},{"t":"Span","c":[["usage-note",[],[]],[]]}]}]]}
+    ]}"##;
+    Pandoc::from_json(json)
+}
+
+fn slugs(split: &Split) -> Vec<&str> {
+    split.chunks.iter().map(|(slug, _)| &slug[..]).collect()
+}
+
+#[test]
+fn by_level() {
+    let split = doc().split(&SplitOptions::default());
+    assert_eq!(slugs(&split), ["index", "intro", "usage"]);
+    assert_eq!(split.ids["detail"], "intro");
+    let (_, usage) = &split.chunks[2];
+    assert_eq!(usage.meta["title"].stringify(), "Book");
+    match usage.blocks[1] {
+        Block::Para(ref inlines) => match inlines[0] {
+            Inline::Link(_, _, (ref url, _)) => assert_eq!(url, "intro.html#detail"),
+            ref other => panic!("{:?}", other),
+        },
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn by_class() {
+    let options = SplitOptions {
+        level: 2,
+        by_class: Some("chapter".to_string()),
+        extension: "xhtml".to_string(),
+    };
+    let split = doc().split(&options);
+    assert_eq!(
+        slugs(&split),
+        ["index", "intro", "detail", "usage", "section"]
+    );
+    let (_, last) = &split.chunks[4];
+    // links within the same chunk are kept
+    match last.blocks[0] {
+        Block::Div(_, ref inner) => match inner[0] {
+            Block::Para(ref inlines) => match inlines[0] {
+                Inline::Link(_, _, (ref url, _)) => assert_eq!(url, "#usage-note"),
+                ref other => panic!("{:?}", other),
+            },
+            ref other => panic!("{:?}", other),
+        },
+        ref other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn without_extension() {
+    let options = SplitOptions {
+        extension: String::new(),
+        ..SplitOptions::default()
+    };
+    let split = doc().split(&options);
+    let (_, usage) = &split.chunks[2];
+    match usage.blocks[1] {
+        Block::Para(ref inlines) => match inlines[0] {
+            Inline::Link(_, _, (ref url, _)) => assert_eq!(url, "intro#detail"),
+            ref other => panic!("{:?}", other),
+        },
+        ref other => panic!("{:?}", other),
+    }
+}
@@ -0,0 +1,72 @@
+use pandoc_ast::*;
+
+/// hand-written pairs of pandoc JSON and its expected plain text, modelled on
+/// `pandoc.utils.stringify` except that `LineBreak` deliberately becomes "\n"
+/// where pandoc emits a space
+const INLINES: &[(&str, &str)] = &[
+    (
+        r#"[{"t":"Str","c":"Hello"},{"t":"Space"},{"t":"Emph","c":[{"t":"Str","c":"world"}]}]"#,
+        "Hello world",
+    ),
+    (
+        r#"[{"t":"Str","c":"a"},{"t":"SoftBreak"},{"t":"Str","c":"b"},{"t":"LineBreak"},{"t":"Str","c":"c"}]"#,
+        "a b\nc",
+    ),
+    (
+        r#"[{"t":"Quoted","c":[{"t":"DoubleQuote"},[{"t":"Str","c":"hi"}]]},{"t":"Quoted","c":[{"t":"SingleQuote"},[{"t":"Str","c":"x"}]]}]"#,
+        "\u{201C}hi\u{201D}\u{2018}x\u{2019}",
+    ),
+    (
+        r#"[{"t":"Str","c":"text"},{"t":"Note","c":[{"t":"Para","c":[{"t":"Str","c":"note"}]}]}]"#,
+        "text",
+    ),
+    (
+        r#"[{"t":"Code","c":[["",[],[]],"x + 1"]},{"t":"Space"},{"t":"Math","c":[{"t":"InlineMath"},"\\alpha"]}]"#,
+        "x + 1 \\alpha",
+    ),
+    (
+        r##"[{"t":"Link","c":[["",[],[]],[{"t":"Strong","c":[{"t":"Str","c":"link"}]}],["#x",""]]},{"t":"RawInline","c":["latex","\\LaTeX"]}]"##,
+        "link",
+    ),
+];
+
+#[test]
+fn inlines() {
+    for &(json, expected) in INLINES {
+        let inlines: Vec<Inline> = serde_json::from_str(json).unwrap();
+        assert_eq!(stringify(&inlines), expected, "{}", json);
+        let text: String = inlines.iter().map(Stringify::stringify).collect();
+        assert_eq!(text, expected);
+    }
+}
+
+#[test]
+fn blocks() {
+    let json = r#"[
+        {"t":"Header","c":[1,["intro",[],[]],[{"t":"Str","c":"Intro"}]]},
+        {"t":"Para","c":[{"t":"Str","c":"one"}]},
+        {"t":"CodeBlock","c":[["",[],[]],"code"]},
+        {"t":"BulletList","c":[[{"t":"Plain","c":[{"t":"Str","c":"two"}]}]]}
+    ]"#;
+    let blocks: Vec<Block> = serde_json::from_str(json).unwrap();
+    assert_eq!(stringify(&blocks), "Introonetwo");
+    assert_eq!(blocks[0].stringify(), "Intro");
+}
+
+#[test]
+fn captions_and_cells() {
+    let caption: Caption = (
+        Some(vec![Inline::Str("short".to_string())]),
+        vec![Block::Plain(vec![Inline::Str("long".to_string())])],
+    );
+    assert_eq!(caption.stringify(), "shortlong");
+    let cell: Cell = (
+        (String::new(), vec![], vec![]),
+        Alignment::AlignDefault,
+        1,
+        1,
+        vec![Block::Plain(vec![Inline::Str("cell".to_string())])],
+    );
+    assert_eq!(cell.stringify(), "cell");
+    assert_eq!(MetaValue::MetaBool(true).stringify(), "true");
+}
@@ -0,0 +1,69 @@
+use pandoc_ast::*;
+
+fn doc() -> Pandoc {
+    let json = r#"{"pandoc-api-version":[1,23],"meta":{},"blocks":[
+        {"t":"Para","c":[{"t":"Str","c":"[[TOC]]"}]},
+        {"t":"Header","c":[1,["intro",[],[]],[{"t":"Str","c":"Intro"}]]},
+        {"t":"Header","c":[2,["",[],[]],[{"t":"Str","c":"Details"}]]},
+        {"t":"Header","c":[4,["deep",[],[]],[{"t":"Str","c":"Deep"}]]},
+        {"t":"Header","c":[1,["appendix",["unnumbered"],[]],[{"t":"Str","c":"Appendix"}]]}
+    ]}"#;
+    Pandoc::from_json(json)
+}
+
+fn link(id: &str, text: &str) -> Block {
+    Block::Plain(vec![Inline::Link(
+        (format!("toc-{}", id), vec![], vec![]),
+        vec![Inline::Str(text.to_string())],
+        (format!("#{}", id), String::new()),
+    )])
+}
+
+#[test]
+fn nested_list() {
+    let expected = Block::BulletList(vec![
+        vec![
+            link("intro", "Intro"),
+            Block::BulletList(vec![vec![link("details", "Details")]]),
+        ],
+        vec![link("appendix", "Appendix")],
+    ]);
+    assert_eq!(toc(&doc(), TocOptions::default()), expected);
+
+    let options = TocOptions {
+        depth: 1,
+        ordered: true,
+        include_unnumbered: false,
+    };
+    match toc(&doc(), options) {
+        Block::OrderedList(_, items) => assert_eq!(items, vec![vec![link("intro", "Intro")]]),
+        other => panic!("{:?}", other),
+    }
+}
+
+#[test]
+fn sections() {
+    let mut pandoc = doc();
+    pandoc.make_sections(MakeSectionsOptions {
+        number_sections: true,
+        base_level: None,
+    });
+    let list = toc(&pandoc, TocOptions::default());
+    let first = match list {
+        Block::BulletList(ref items) => &items[0][0],
+        _ => panic!(),
+    };
+    assert_eq!(stringify(first), "1 Intro");
+}
+
+#[test]
+fn placeholder() {
+    let mut pandoc = doc();
+    assert!(pandoc.insert_toc(TocOptions::default()));
+    assert!(matches!(pandoc.blocks[0], Block::BulletList(_)));
+    match pandoc.blocks[2] {
+        Block::Header(_, (ref id, _, _), _) => assert_eq!(id, "details"),
+        _ => panic!(),
+    }
+    assert!(!pandoc.insert_toc(TocOptions::default()));
+}
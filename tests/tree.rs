@@ -0,0 +1,97 @@
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+fn doc() -> Pandoc {
+    let mut meta = Map::new();
+    meta.insert("title".to_string(), MetaValue::MetaInlines(text("Doc")));
+    let mut para = text("Hello");
+    para.extend([
+        Inline::Space,
+        Inline::Emph(vec![Inline::Str("a very long word".repeat(3))]),
+    ]);
+    Pandoc {
+        meta,
+        blocks: vec![
+            Block::Header(
+                1,
+                (
+                    "intro".to_string(),
+                    vec!["a".to_string()],
+                    vec![("k".to_string(), "v".to_string())],
+                ),
+                text("Intro"),
+            ),
+            Block::BulletList(vec![vec![Block::Para(para)]]),
+        ],
+        pandoc_api_version: vec![1, 23, 1],
+    }
+}
+
+#[test]
+fn tree_view() {
+    assert_eq!(
+        doc().tree(&TreeOptions::default()),
+        "Pandoc\n\
+         ├─ Meta\n\
+         │  └─ Entry \"title\" MetaInlines\n\
+         │     └─ Str \"Doc\"\n\
+         ├─ Header 1 #intro .a k=\"v\"\n\
+         │  └─ Str \"Intro\"\n\
+         └─ BulletList\n\
+         \x20  └─ Item\n\
+         \x20     └─ Para\n\
+         \x20        ├─ Str \"Hello\"\n\
+         \x20        ├─ Space\n\
+         \x20        └─ Emph\n\
+         \x20           └─ Str \"a very long worda very long worda very l\"…\n"
+    );
+}
+
+#[test]
+fn focus_and_depth() {
+    let path: NodePath = "1.0.0".parse().unwrap();
+    assert_eq!(path, NodePath(vec![1, 0, 0]));
+    assert_eq!(path.to_string(), "1.0.0");
+    let options = TreeOptions {
+        depth: Some(1),
+        focus: Some(path),
+        max_string: 3,
+        ..TreeOptions::default()
+    };
+    assert_eq!(
+        doc().tree(&options),
+        "Para\n├─ Str \"Hel\"…\n├─ Space\n└─ Emph …\n"
+    );
+    let options = TreeOptions {
+        focus: Some(NodePath(vec![5])),
+        ..TreeOptions::default()
+    };
+    assert_eq!(doc().tree(&options), "");
+    let options = TreeOptions {
+        color: true,
+        focus: Some(NodePath(vec![0])),
+        depth: Some(0),
+        ..TreeOptions::default()
+    };
+    assert_eq!(
+        doc().tree(&options),
+        "\x1b[1mHeader\x1b[0m 1 \x1b[33m#intro .a k=\"v\"\x1b[0m …\n"
+    );
+}
+
+#[test]
+fn graphviz() {
+    let options = TreeOptions {
+        focus: Some(NodePath(vec![0])),
+        ..TreeOptions::default()
+    };
+    assert_eq!(
+        doc().to_dot(&options),
+        "digraph pandoc {\n  node [shape=box, fontname=\"monospace\"];\n  \
+         n0 [label=\"Header 1 #intro .a k=\\\"v\\\"\"];\n  \
+         n1 [label=\"Str \\\"Intro\\\"\"];\n  \
+         n0 -> n1;\n}\n"
+    );
+}
@@ -0,0 +1,146 @@
+use pandoc_ast::writers::typst::{tex_to_typst, write_blocks, write_inlines};
+use pandoc_ast::*;
+
+mod common;
+use common::text;
+
+#[test]
+fn inlines() {
+    let mut inlines = text("#1 *not* a_b");
+    inlines.extend([
+        Inline::Space,
+        Inline::Emph(text("em")),
+        Inline::Strikeout(text("gone")),
+        Inline::Note(vec![Block::Para(text("note"))]),
+        Inline::Link(
+            Default::default(),
+            text("see"),
+            ("#intro".to_string(), String::new()),
+        ),
+        Inline::Math(MathType::InlineMath, "x^2".to_string()),
+        Inline::RawInline(Format::new("typst"), "#pagebreak()".to_string()),
+        Inline::RawInline(Format::new("html"), "<br>".to_string()),
+    ]);
+    assert_eq!(
+        write_inlines(&inlines),
+        "\\#1 \\*not\\* a\\_b #emph[em]#strike[gone]#footnote[note]#link(<intro>)[see]\
+         $x^2$#pagebreak()"
+    );
+}
+
+#[test]
+fn line_starts() {
+    let inlines = vec![
+        Inline::Str("/".to_string()),
+        Inline::Space,
+        Inline::Str("term".to_string()),
+        Inline::LineBreak,
+        Inline::Str("-".to_string()),
+        Inline::Space,
+        Inline::Str("a".to_string()),
+        Inline::LineBreak,
+        Inline::Str("2.".to_string()),
+        Inline::Space,
+        Inline::Str("b".to_string()),
+        Inline::Space,
+        Inline::Str("-".to_string()),
+    ];
+    assert_eq!(write_inlines(&inlines), "\\/ term\\\n\\- a\\\n2\\. b -");
+}
+
+#[test]
+fn math() {
+    assert_eq!(
+        tex_to_typst(r"\frac{a+1}{\sqrt{x}} \le \alpha_{ij}^2"),
+        "frac(a + 1, sqrt(x)) <= alpha_(i j)^2"
+    );
+    assert_eq!(
+        tex_to_typst(r"\sum_{k=1}^{n} \mathbf{v}_k \cdot \text{speed} \to \infty"),
+        "sum_(k = 1)^n bold(v)_k dot.op \"speed\" -> infinity"
+    );
+    assert_eq!(
+        tex_to_typst(r"\left( \sqrt[3]{y} \right) / 2"),
+        "( root(3, y) ) \\/ 2"
+    );
+    assert_eq!(tex_to_typst(r"\frac12 + x^23"), "frac(1, 2) + x^2 3");
+}
+
+#[test]
+fn blocks() {
+    let cell = |s: &str, rowspan, colspan| {
+        (
+            Attr::default(),
+            Alignment::AlignDefault,
+            rowspan,
+            colspan,
+            vec![Block::Plain(text(s))],
+        )
+    };
+    let table = Block::Table(
+        ("tbl".to_string(), vec![], vec![]),
+        (None, vec![Block::Plain(text("Numbers"))]),
+        vec![
+            (Alignment::AlignLeft, ColWidth::ColWidthDefault),
+            (Alignment::AlignRight, ColWidth::ColWidthDefault),
+        ],
+        (
+            Default::default(),
+            vec![(Default::default(), vec![cell("a", 1, 1), cell("b", 1, 1)])],
+        ),
+        vec![(
+            Default::default(),
+            0,
+            vec![],
+            vec![
+                (Default::default(), vec![cell("c", 2, 1), cell("d", 1, 1)]),
+                (Default::default(), vec![cell("e", 1, 1)]),
+            ],
+        )],
+        (Default::default(), vec![]),
+    );
+    let figure = Block::Figure(
+        ("fig".to_string(), vec![], vec![]),
+        (None, vec![Block::Plain(text("A figure"))]),
+        vec![Block::Plain(vec![Inline::Image(
+            Default::default(),
+            vec![],
+            ("a.png".to_string(), String::new()),
+        )])],
+    );
+    let blocks = vec![
+        Block::Header(2, ("intro".to_string(), vec![], vec![]), text("Intro")),
+        Block::OrderedList(
+            (2, ListNumberStyle::LowerAlpha, ListNumberDelim::OneParen),
+            vec![
+                vec![Block::Plain(text("one"))],
+                vec![
+                    Block::Plain(text("two")),
+                    Block::BulletList(vec![vec![Block::Plain(text("-1"))]]),
+                ],
+            ],
+        ),
+        figure,
+        table,
+    ];
+    assert_eq!(
+        write_blocks(&blocks),
+        "== Intro <intro>\n\n\
+         #block[\n#set enum(numbering: \"a)\", start: 2)\n+ one\n+ two\n\n  - \\-1\n]\n\n\
+         #figure(image(\"a.png\"), caption: [A figure]) <fig>\n\n\
+         #figure(table(\n  columns: 2,\n  align: (left, right,),\n  table.header([a], [b]),\n  \
+         table.cell(rowspan: 2)[c], [d],\n  [e],\n), caption: [Numbers], kind: table) <tbl>\n"
+    );
+}
+
+#[test]
+fn header_levels() {
+    let unnumbered = ("".to_string(), vec!["unnumbered".to_string()], vec![]);
+    let blocks = vec![
+        Block::Header(0, Default::default(), text("Zero")),
+        Block::Header(0, unnumbered, text("Plain")),
+    ];
+    assert_eq!(
+        write_blocks(&blocks),
+        "= Zero\n\n#heading(level: 1, numbering: none)[Plain]\n"
+    );
+}